@@ -0,0 +1,67 @@
+/// Variables: 0 = "x_0", 1 = "x_1", 2 = "x_2", 3 = "x_3", 4 = "x_4"
+pub fn eval_0(values: &[bool; 5]) -> bool {
+    const NODES: [(usize, usize, usize); 2] = [
+        (0, 0, 0), // 0
+        (0, 0, 0), // 1
+    ];
+    let mut node = 1;
+    while node > 1 {
+        let (var, low, high) = NODES[node];
+        node = if values[var] { high } else { low };
+    }
+    node == 1
+}
+
+/// Variables: 0 = "x_0", 1 = "x_1", 2 = "x_2", 3 = "x_3", 4 = "x_4"
+pub fn eval_1(values: &[bool; 5]) -> bool {
+    const NODES: [(usize, usize, usize); 1] = [
+        (0, 0, 0), // 0
+    ];
+    let mut node = 0;
+    while node > 1 {
+        let (var, low, high) = NODES[node];
+        node = if values[var] { high } else { low };
+    }
+    node == 1
+}
+
+/// Variables: 0 = "x_0", 1 = "x_1", 2 = "x_2", 3 = "x_3", 4 = "x_4"
+pub fn eval_2(values: &[bool; 5]) -> bool {
+    const NODES: [(usize, usize, usize); 7] = [
+        (0, 0, 0), // 0
+        (0, 0, 0), // 1
+        (4, 0, 1), // "x_4"
+        (4, 1, 0), // "x_4"
+        (2, 3, 2), // "x_2"
+        (1, 1, 4), // "x_1"
+        (0, 4, 5), // "x_0"
+    ];
+    let mut node = 6;
+    while node > 1 {
+        let (var, low, high) = NODES[node];
+        node = if values[var] { high } else { low };
+    }
+    node == 1
+}
+
+/// Variables: 0 = "x_0", 1 = "x_1", 2 = "x_2", 3 = "x_3", 4 = "x_4"
+pub fn eval_3(values: &[bool; 5]) -> bool {
+    const NODES: [(usize, usize, usize); 10] = [
+        (0, 0, 0), // 0
+        (0, 0, 0), // 1
+        (4, 0, 1), // "x_4"
+        (3, 2, 1), // "x_3"
+        (3, 1, 2), // "x_3"
+        (2, 4, 3), // "x_2"
+        (2, 3, 4), // "x_2"
+        (1, 6, 5), // "x_1"
+        (1, 5, 6), // "x_1"
+        (0, 8, 7), // "x_0"
+    ];
+    let mut node = 9;
+    while node > 1 {
+        let (var, low, high) = NODES[node];
+        node = if values[var] { high } else { low };
+    }
+    node == 1
+}
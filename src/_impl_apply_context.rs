@@ -0,0 +1,524 @@
+use crate::_impl_bdd::_impl_boolean_ops::apply_with_flip_in_context;
+use crate::_impl_bdd::_impl_nested_ops::nested_apply_in_context;
+use crate::_impl_bdd::_impl_ternary_ops::ternary_apply_in_context;
+use crate::_impl_budget::{unwrap_unlimited, Unlimited};
+use crate::*;
+use fxhash::FxHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
+/// **(internal)** The maximal number of items for which `ApplyContext::clear` keeps
+/// the allocated memory of each buffer.
+const RETAINED_CAPACITY: usize = 1 << 16;
+
+impl Default for ApplyContext {
+    fn default() -> Self {
+        ApplyContext::new()
+    }
+}
+
+impl ApplyContext {
+    /// Create a new empty context without memoisation.
+    pub fn new() -> ApplyContext {
+        ApplyContext::with_capacity(0)
+    }
+
+    /// **(internal)** Create a new context with pre-allocated working memory.
+    pub(crate) fn with_capacity(capacity: usize) -> ApplyContext {
+        ApplyContext {
+            existing: HashMap::with_capacity_and_hasher(capacity, FxBuildHasher::default()),
+            finished: HashMap::with_capacity_and_hasher(capacity, FxBuildHasher::default()),
+            stack: Vec::with_capacity(capacity),
+            inner_finished: HashMap::default(),
+            ternary_finished: HashMap::default(),
+            ternary_stack: Vec::new(),
+            memoisation_limit: 0,
+            interned: Vec::new(),
+            interned_nodes: 0,
+            fingerprints: HashMap::default(),
+            memoised: HashMap::default(),
+        }
+    }
+
+    /// Create a new context which memoises the results of the basic logical operations
+    /// (`and`, `or`, `imp`, `iff`, `xor` and `and_not`).
+    ///
+    /// To memoise a result, the context stores a copy of both operands and of the result
+    /// (every distinct `Bdd` is stored only once). The memory of the memoised results is
+    /// bounded by `max_nodes`, the total number of nodes of all stored `Bdd`s: once the limit
+    /// would be exceeded, all memoised results are discarded, and results which do not fit
+    /// into the limit at all are not memoised.
+    ///
+    /// The operands are first identified using a constant-time fingerprint, but a memoised
+    /// result is only used once the operands are verified to be equal to the stored ones.
+    /// Hence, a lookup takes $O(|left| + |right|)$ time, which is typically much faster than
+    /// `apply`, but it is not free. There are no false positives.
+    pub fn with_memoisation(max_nodes: usize) -> ApplyContext {
+        let mut context = ApplyContext::new();
+        context.memoisation_limit = max_nodes;
+        context
+    }
+
+    /// The number of currently memoised results.
+    pub fn memoised_results(&self) -> usize {
+        self.memoised.len()
+    }
+
+    /// The total number of nodes of the `Bdd`s stored to memoise the current results
+    /// (this is at most the limit given to `ApplyContext::with_memoisation`).
+    pub fn memoised_nodes(&self) -> usize {
+        self.interned_nodes
+    }
+
+    /// Discard all memoised results.
+    ///
+    /// The working memory stays allocated so that it can be reused by the following
+    /// operations, but buffers that grew very large are shrunk.
+    pub fn clear(&mut self) {
+        self.existing.clear();
+        self.existing.shrink_to(RETAINED_CAPACITY);
+        self.finished.clear();
+        self.finished.shrink_to(RETAINED_CAPACITY);
+        self.stack.clear();
+        self.stack.shrink_to(RETAINED_CAPACITY);
+        self.inner_finished.clear();
+        self.inner_finished.shrink_to(RETAINED_CAPACITY);
+        self.ternary_finished.clear();
+        self.ternary_finished.shrink_to(RETAINED_CAPACITY);
+        self.ternary_stack.clear();
+        self.ternary_stack.shrink_to(RETAINED_CAPACITY);
+        self.clear_memoised();
+    }
+
+    /// **(internal)** Discard all memoised results and interned `Bdd`s.
+    fn clear_memoised(&mut self) {
+        self.interned.clear();
+        self.interned.shrink_to(RETAINED_CAPACITY);
+        self.interned_nodes = 0;
+        self.fingerprints.clear();
+        self.fingerprints.shrink_to(RETAINED_CAPACITY);
+        self.memoised.clear();
+        self.memoised.shrink_to(RETAINED_CAPACITY);
+    }
+
+    /// Same as `Bdd::and`, but using this context.
+    pub fn and(&mut self, left: &Bdd, right: &Bdd) -> Bdd {
+        self.memoised_apply(0, left, right, op_function::and)
+    }
+
+    /// Same as `Bdd::or`, but using this context.
+    pub fn or(&mut self, left: &Bdd, right: &Bdd) -> Bdd {
+        self.memoised_apply(1, left, right, op_function::or)
+    }
+
+    /// Same as `Bdd::imp`, but using this context.
+    pub fn imp(&mut self, left: &Bdd, right: &Bdd) -> Bdd {
+        self.memoised_apply(2, left, right, op_function::imp)
+    }
+
+    /// Same as `Bdd::iff`, but using this context.
+    pub fn iff(&mut self, left: &Bdd, right: &Bdd) -> Bdd {
+        self.memoised_apply(3, left, right, op_function::iff)
+    }
+
+    /// Same as `Bdd::xor`, but using this context.
+    pub fn xor(&mut self, left: &Bdd, right: &Bdd) -> Bdd {
+        self.memoised_apply(4, left, right, op_function::xor)
+    }
+
+    /// Same as `Bdd::and_not`, but using this context.
+    pub fn and_not(&mut self, left: &Bdd, right: &Bdd) -> Bdd {
+        self.memoised_apply(5, left, right, op_function::and_not)
+    }
+
+    /// Same as `Bdd::binary_op`, but using this context. The results of general operations
+    /// are never memoised.
+    pub fn binary_op<T>(&mut self, left: &Bdd, right: &Bdd, op_function: T) -> Bdd
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        let result = apply_with_flip_in_context(
+            self,
+            &mut Unlimited,
+            &mut (),
+            left,
+            right,
+            None,
+            None,
+            None,
+            op_function,
+        );
+        unwrap_unlimited(result)
+    }
+
+    /// Same as `Bdd::fused_binary_flip_op`, but using this context. The results of general
+    /// operations are never memoised.
+    pub fn fused_binary_flip_op<T>(
+        &mut self,
+        left: (&Bdd, Option<BddVariable>),
+        right: (&Bdd, Option<BddVariable>),
+        flip_output: Option<BddVariable>,
+        op_function: T,
+    ) -> Bdd
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        unwrap_unlimited(apply_with_flip_in_context(
+            self,
+            &mut Unlimited,
+            &mut (),
+            left.0,
+            right.0,
+            left.1,
+            right.1,
+            flip_output,
+            op_function,
+        ))
+    }
+
+    /// Same as `Bdd::ternary_op`, but using this context. The results are never memoised.
+    pub fn ternary_op<T>(&mut self, a: &Bdd, b: &Bdd, c: &Bdd, op_function: T) -> Bdd
+    where
+        T: Fn(Option<bool>, Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        unwrap_unlimited(ternary_apply_in_context(
+            self,
+            (a, b, c),
+            (None, None, None),
+            None,
+            op_function,
+            &mut Unlimited,
+            &mut (),
+        ))
+    }
+
+    /// Same as `Bdd::binary_op_nested`, but using this context. The results are never memoised.
+    pub fn binary_op_nested<F1, F2, Trigger>(
+        &mut self,
+        left: &Bdd,
+        right: &Bdd,
+        trigger: Trigger,
+        outer_op: F1,
+        inner_op: F2,
+    ) -> Bdd
+    where
+        F1: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+        F2: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+        Trigger: Fn(BddVariable) -> bool,
+    {
+        unwrap_unlimited(nested_apply_in_context(
+            self,
+            left,
+            right,
+            trigger,
+            outer_op,
+            inner_op,
+            &mut Unlimited,
+            &mut (),
+        ))
+    }
+
+    /// Same as `Bdd::binary_op_with_exists`, but using this context. The results are never
+    /// memoised.
+    pub fn binary_op_with_exists<F>(
+        &mut self,
+        left: &Bdd,
+        right: &Bdd,
+        op: F,
+        variables: &[BddVariable],
+    ) -> Bdd
+    where
+        F: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        let set: HashSet<BddVariable, FxBuildHasher> =
+            HashSet::from_iter(variables.iter().cloned());
+        let trigger = |var: BddVariable| set.contains(&var);
+        self.binary_op_nested(left, right, trigger, op, op_function::or)
+    }
+
+    /// Same as `Bdd::binary_op_with_for_all`, but using this context. The results are never
+    /// memoised.
+    pub fn binary_op_with_for_all<F>(
+        &mut self,
+        left: &Bdd,
+        right: &Bdd,
+        op: F,
+        variables: &[BddVariable],
+    ) -> Bdd
+    where
+        F: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        let set: HashSet<BddVariable, FxBuildHasher> =
+            HashSet::from_iter(variables.iter().cloned());
+        let trigger = |var: BddVariable| set.contains(&var);
+        self.binary_op_nested(left, right, trigger, op, op_function::and)
+    }
+
+    /// Same as `Bdd::exists`, but using this context.
+    pub fn exists(&mut self, bdd: &Bdd, variables: &[BddVariable]) -> Bdd {
+        self.binary_op_with_exists(bdd, bdd, op_function::and, variables)
+    }
+
+    /// Same as `Bdd::for_all`, but using this context.
+    pub fn for_all(&mut self, bdd: &Bdd, variables: &[BddVariable]) -> Bdd {
+        self.binary_op_with_for_all(bdd, bdd, op_function::and, variables)
+    }
+
+    /// **(internal)** Apply one of the basic operations (identified by `op_id`), using the
+    /// memoised result if possible.
+    fn memoised_apply<T>(&mut self, op_id: u8, left: &Bdd, right: &Bdd, op_function: T) -> Bdd
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        if self.memoisation_limit == 0 {
+            return self.binary_op(left, right, op_function);
+        }
+        if let (Some(l), Some(r)) = (self.find(left), self.find(right)) {
+            if let Some(result) = self.memoised.get(&(op_id, l, r)) {
+                return self.interned[*result].clone();
+            }
+        }
+        let result = self.binary_op(left, right, op_function);
+        let operands = [left, right, &result];
+        if self.interned_nodes + self.missing_nodes(&operands) > self.memoisation_limit {
+            self.clear_memoised();
+            if self.missing_nodes(&operands) > self.memoisation_limit {
+                // The result cannot be memoised within the limit.
+                return result;
+            }
+        }
+        let key = (op_id, self.intern(left), self.intern(right));
+        let id = self.intern(&result);
+        self.memoised.insert(key, id);
+        result
+    }
+
+    /// **(internal)** The number of nodes that need to be added to the context in order to
+    /// intern all the given `Bdd`s.
+    fn missing_nodes(&self, bdds: &[&Bdd]) -> usize {
+        let mut missing = 0;
+        for (i, bdd) in bdds.iter().enumerate() {
+            if !bdds[..i].contains(bdd) && self.find(bdd).is_none() {
+                missing += bdd.size();
+            }
+        }
+        missing
+    }
+
+    /// **(internal)** Find the id of an interned `Bdd` equal to `bdd`.
+    fn find(&self, bdd: &Bdd) -> Option<usize> {
+        self.fingerprints
+            .get(&fingerprint(bdd))?
+            .iter()
+            .copied()
+            .find(|id| self.interned[*id] == *bdd)
+    }
+
+    /// **(internal)** Find the id of an interned `Bdd` equal to `bdd`, or intern a copy
+    /// of `bdd` if there is none.
+    fn intern(&mut self, bdd: &Bdd) -> usize {
+        if let Some(id) = self.find(bdd) {
+            return id;
+        }
+        let id = self.interned.len();
+        self.interned.push(bdd.clone());
+        self.interned_nodes += bdd.size();
+        self.fingerprints
+            .entry(fingerprint(bdd))
+            .or_default()
+            .push(id);
+        id
+    }
+}
+
+impl Bdd {
+    /// Same as `Bdd::binary_op`, but the working memory is reused from the given `context`.
+    /// See `ApplyContext` for details.
+    pub fn binary_op_with_context<T>(
+        context: &mut ApplyContext,
+        left: &Bdd,
+        right: &Bdd,
+        op_function: T,
+    ) -> Bdd
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        context.binary_op(left, right, op_function)
+    }
+}
+
+/// **(internal)** Compute a fingerprint of a `Bdd` which is used to find the interned `Bdd`s.
+///
+/// The fingerprint only depends on the size and a few nodes of the `Bdd`, so it can be computed
+/// in constant time. Equal `Bdd`s always have the same fingerprint.
+fn fingerprint(bdd: &Bdd) -> u64 {
+    let mut hasher = FxHasher::default();
+    bdd.size().hash(&mut hasher);
+    bdd.0[bdd.size() - 1].hash(&mut hasher);
+    bdd.0[bdd.size() / 2].hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn apply_context_matches_apply() {
+        let vars = BddVariableSet::new_anonymous(5);
+        let formulas = [
+            "x_0 & !x_1 | x_4",
+            "(x_1 ^ x_2) => x_3",
+            "x_0 <=> (x_2 | !x_4)",
+            "true",
+            "false",
+        ];
+        let bdds: Vec<Bdd> = formulas
+            .iter()
+            .map(|it| vars.eval_expression_string(it))
+            .collect();
+        for mut context in [ApplyContext::new(), ApplyContext::with_memoisation(40)] {
+            // Run everything twice to also test the memoised results.
+            for _ in 0..2 {
+                for a in &bdds {
+                    for b in &bdds {
+                        assert_eq!(a.and(b), context.and(a, b));
+                        assert_eq!(a.or(b), context.or(a, b));
+                        assert_eq!(a.imp(b), context.imp(a, b));
+                        assert_eq!(a.iff(b), context.iff(a, b));
+                        assert_eq!(a.xor(b), context.xor(a, b));
+                        assert_eq!(a.and_not(b), context.and_not(a, b));
+                        assert_eq!(
+                            Bdd::binary_op(a, b, op_function::iff),
+                            Bdd::binary_op_with_context(&mut context, a, b, op_function::iff)
+                        );
+                        let v = BddVariable(2);
+                        assert_eq!(
+                            Bdd::fused_binary_flip_op(
+                                (a, Some(v)),
+                                (b, None),
+                                Some(v),
+                                op_function::or
+                            ),
+                            context.fused_binary_flip_op(
+                                (a, Some(v)),
+                                (b, None),
+                                Some(v),
+                                op_function::or
+                            )
+                        );
+                    }
+                }
+            }
+            assert!(context.memoised_nodes() <= 40);
+        }
+    }
+
+    #[test]
+    fn apply_context_ternary_and_nested() {
+        let vars = BddVariableSet::new_anonymous(5);
+        let bdds: Vec<Bdd> = [
+            "x_0 & !x_1 | x_4",
+            "(x_1 ^ x_2) => x_3",
+            "x_0 <=> x_3",
+            "false",
+        ]
+        .iter()
+        .map(|it| vars.eval_expression_string(it))
+        .collect();
+        let projected = [BddVariable(0), BddVariable(3)];
+        let mut context = ApplyContext::new();
+        for a in &bdds {
+            assert_eq!(a.exists(&projected), context.exists(a, &projected));
+            assert_eq!(a.for_all(&projected), context.for_all(a, &projected));
+            for b in &bdds {
+                let op = op_function::imp;
+                assert_eq!(
+                    Bdd::binary_op_with_exists(a, b, op, &projected),
+                    context.binary_op_with_exists(a, b, op, &projected)
+                );
+                assert_eq!(
+                    Bdd::binary_op_with_for_all(a, b, op, &projected),
+                    context.binary_op_with_for_all(a, b, op, &projected)
+                );
+                for c in &bdds {
+                    let op = |x: Option<bool>, y: Option<bool>, z: Option<bool>| match (x, y, z) {
+                        (Some(x), Some(y), Some(z)) => Some((x && y) || z),
+                        _ => None,
+                    };
+                    assert_eq!(
+                        Bdd::ternary_op(a, b, c, op),
+                        context.ternary_op(a, b, c, op)
+                    );
+                    // Ternary and binary operations can be mixed in one context.
+                    assert_eq!(a.and(b), context.and(a, b));
+                }
+            }
+        }
+        context.clear();
+        assert!(context.ternary_finished.capacity() > 0);
+    }
+
+    #[test]
+    fn apply_context_memoisation() {
+        let vars = BddVariableSet::new_anonymous(3);
+        let a = vars.eval_expression_string("x_0 | x_1");
+        let b = vars.eval_expression_string("x_1 & x_2");
+        let mut context = ApplyContext::new();
+        context.and(&a, &b);
+        assert_eq!(0, context.memoised_results());
+
+        // Here, `a & b == b` and `a | b == a`, both `a` and `b` have 4 nodes.
+        let mut context = ApplyContext::with_memoisation(10);
+        assert_eq!(a.and(&b), context.and(&a, &b));
+        assert_eq!(a.and(&b), context.and(&a, &b));
+        assert_eq!(1, context.memoised_results());
+        assert_eq!(8, context.memoised_nodes());
+        // Different operations and operand orders are different results.
+        assert_eq!(a.or(&b), context.or(&a, &b));
+        assert_eq!(2, context.memoised_results());
+        assert_eq!(8, context.memoised_nodes());
+        // A result which does not fit into the limit is not memoised at all.
+        let c = vars.eval_expression_string("x_0 ^ x_2");
+        assert_eq!(a.and(&c), context.and(&a, &c));
+        assert_eq!(0, context.memoised_results());
+        assert_eq!(0, context.memoised_nodes());
+        assert_eq!(b.and_not(&a), context.and_not(&b, &a));
+        assert_eq!(1, context.memoised_results());
+        assert_eq!(9, context.memoised_nodes());
+        // Once the limit is reached, the old results are discarded.
+        let t = vars.mk_true();
+        assert_eq!(b, context.and(&t, &b));
+        assert_eq!(1, context.memoised_results());
+        assert_eq!(6, context.memoised_nodes());
+        context.clear();
+        assert_eq!(0, context.memoised_results());
+        assert_eq!(0, context.memoised_nodes());
+        assert!(context.existing.capacity() > 0);
+        assert_eq!(b.and_not(&a), context.and_not(&b, &a));
+        assert_eq!(1, context.memoised_results());
+    }
+
+    #[test]
+    fn apply_context_interning() {
+        let vars = BddVariableSet::new_anonymous(4);
+        let a = vars.eval_expression_string("x_0 | x_3");
+        let b = vars.eval_expression_string("x_1 & !x_2");
+        let mut context = ApplyContext::with_memoisation(1000);
+        let mut x = context.and(&a, &b);
+        // Each distinct operand and result is stored only once.
+        assert_eq!(3, context.interned.len());
+        assert_eq!(a, context.or(&x, &a));
+        assert_eq!(3, context.interned.len());
+        // Equal copies of the operands also find the memoised result.
+        assert_eq!(x, context.and(&a.clone(), &b.clone()));
+        assert_eq!(2, context.memoised_results());
+        // A fixed-point loop where results are reused as operands.
+        for _ in 0..5 {
+            x = context.or(&x, &b);
+        }
+        assert_eq!(b, x);
+        assert_eq!(4, context.memoised_results());
+        assert_eq!(3, context.interned.len());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{ApplyObserver, ApplyStatistics, BddVariable};
+
+/// The no-op observer which is used by all standard operations.
+impl ApplyObserver for () {}
+
+impl ApplyObserver for ApplyStatistics {
+    fn on_task(&mut self, variable: BddVariable, stack_depth: usize) {
+        self.tasks += 1;
+        self.max_stack_depth = self.max_stack_depth.max(stack_depth);
+        let index = usize::from(variable.0);
+        if self.tasks_per_variable.len() <= index {
+            self.tasks_per_variable.resize(index + 1, 0);
+        }
+        self.tasks_per_variable[index] += 1;
+    }
+
+    fn on_cache_hit(&mut self) {
+        self.cache_hits += 1;
+    }
+
+    fn on_node_created(&mut self, _variable: BddVariable) {
+        self.nodes_created += 1;
+    }
+}
+
+impl ApplyStatistics {
+    /// Create new empty statistics.
+    pub fn new() -> ApplyStatistics {
+        ApplyStatistics::default()
+    }
+
+    /// The number of resolved tasks.
+    pub fn tasks(&self) -> usize {
+        self.tasks
+    }
+
+    /// The number of sub-tasks that were resolved using the task cache.
+    pub fn cache_hits(&self) -> usize {
+        self.cache_hits
+    }
+
+    /// The number of created decision nodes. For nested operations, this can include nodes
+    /// that are not present in the final result.
+    pub fn nodes_created(&self) -> usize {
+        self.nodes_created
+    }
+
+    /// The maximal number of tasks on the stack.
+    pub fn max_stack_depth(&self) -> usize {
+        self.max_stack_depth
+    }
+
+    /// The number of resolved tasks that branch on the given `variable`.
+    pub fn tasks_of_variable(&self, variable: BddVariable) -> usize {
+        self.tasks_per_variable
+            .get(usize::from(variable.0))
+            .cloned()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn apply_statistics() {
+        let vars = BddVariableSet::new_anonymous(6);
+        let v = vars.variables();
+        let a = vars.eval_expression_string("(x_0 <=> x_3) & (x_1 <=> x_4) | x_5");
+        let b = vars.eval_expression_string("(x_2 ^ x_5) | (x_0 & !x_4)");
+        let c = vars.eval_expression_string("x_1 | !x_3");
+
+        let mut stats = ApplyStatistics::new();
+        let and = op_function::and;
+        assert_eq!(
+            a.and(&b),
+            Bdd::binary_op_with_observer(&a, &b, and, &mut stats)
+        );
+        let result = a.and(&b);
+        assert_eq!(result.size() - 2, stats.nodes_created());
+        assert!(stats.tasks() >= stats.nodes_created());
+        assert!(stats.cache_hits() > 0);
+        assert!(stats.max_stack_depth() >= 1 && stats.max_stack_depth() <= stats.tasks());
+        let per_variable: usize = v.iter().map(|it| stats.tasks_of_variable(*it)).sum();
+        assert_eq!(stats.tasks(), per_variable);
+        assert_eq!(1, stats.tasks_of_variable(v[0]));
+
+        // Statistics accumulate across operations.
+        let before = stats.tasks();
+        let flip = Some(v[1]);
+        assert_eq!(
+            Bdd::fused_binary_flip_op((&a, flip), (&b, None), None, and),
+            Bdd::fused_binary_flip_op_with_observer((&a, flip), (&b, None), None, and, &mut stats)
+        );
+        assert!(stats.tasks() > before);
+
+        let mut stats = ApplyStatistics::new();
+        let ite = |a: Option<bool>, b: Option<bool>, c: Option<bool>| match a {
+            Some(true) => b,
+            Some(false) => c,
+            None => None,
+        };
+        assert_eq!(
+            Bdd::if_then_else(&a, &b, &c),
+            Bdd::ternary_op_with_observer(&a, &b, &c, ite, &mut stats)
+        );
+        assert!(stats.tasks() > 0 && stats.nodes_created() > 0);
+
+        let mut stats = ApplyStatistics::new();
+        let trigger = |var: BddVariable| var == v[0] || var == v[3];
+        assert_eq!(
+            Bdd::binary_op_with_exists(&a, &b, and, &[v[0], v[3]]),
+            Bdd::binary_op_nested_with_observer(&a, &b, trigger, and, op_function::or, &mut stats)
+        );
+        assert!(stats.tasks() > 0 && stats.nodes_created() > 0);
+    }
+}
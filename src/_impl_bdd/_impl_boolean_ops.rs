@@ -1,4 +1,5 @@
 use crate::_impl_bdd::Task;
+use crate::_impl_budget::{unwrap_unlimited, Tracker, Unlimited};
 use crate::*;
 use fxhash::FxBuildHasher;
 use std::cmp::{max, min};
@@ -111,6 +112,46 @@ impl Bdd {
         apply_with_flip_and_limit(limit, left, right, None, None, None, op_function)
     }
 
+    /// Same as `binary_op`, but the operation is aborted once the given `budget` is exceeded.
+    pub fn binary_op_with_budget<T>(
+        left: &Bdd,
+        right: &Bdd,
+        op_function: T,
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded>
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        Bdd::fused_binary_flip_op_with_tracker(
+            (left, None),
+            (right, None),
+            None,
+            op_function,
+            &mut budget.start(),
+        )
+    }
+
+    /// Same as `binary_op`, but the progress of the operation is reported to the given
+    /// `observer` (see `ApplyObserver` and `ApplyStatistics`).
+    pub fn binary_op_with_observer<T, O>(
+        left: &Bdd,
+        right: &Bdd,
+        op_function: T,
+        observer: &mut O,
+    ) -> Bdd
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+        O: ApplyObserver,
+    {
+        Bdd::fused_binary_flip_op_with_observer(
+            (left, None),
+            (right, None),
+            None,
+            op_function,
+            observer,
+        )
+    }
+
     /// Apply a general binary operation together with up-to three Bdd variable flips. See also `binary_op`.
     ///
     /// A flip exchanges the edges of all decision nodes with the specified variable `x`.
@@ -154,6 +195,82 @@ impl Bdd {
         )
     }
 
+    /// Same as `Self::fused_binary_flip_op`, but the operation is aborted once the given
+    /// `budget` is exceeded.
+    pub fn fused_binary_flip_op_with_budget<T>(
+        left: (&Bdd, Option<BddVariable>),
+        right: (&Bdd, Option<BddVariable>),
+        flip_output: Option<BddVariable>,
+        op_function: T,
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded>
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        Bdd::fused_binary_flip_op_with_tracker(
+            left,
+            right,
+            flip_output,
+            op_function,
+            &mut budget.start(),
+        )
+    }
+
+    /// **(internal)** Same as `Self::fused_binary_flip_op`, but the resources used by the
+    /// operation are accounted in the given `tracker`. This allows a single `Budget` to be
+    /// shared by all steps of a composite operation.
+    pub(crate) fn fused_binary_flip_op_with_tracker<T, K>(
+        left: (&Bdd, Option<BddVariable>),
+        right: (&Bdd, Option<BddVariable>),
+        flip_output: Option<BddVariable>,
+        op_function: T,
+        tracker: &mut K,
+    ) -> Result<Bdd, BudgetExceeded>
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+        K: Tracker,
+    {
+        let mut context = ApplyContext::with_capacity(max(left.0.size(), right.0.size()));
+        apply_with_flip_in_context(
+            &mut context,
+            tracker,
+            &mut (),
+            left.0,
+            right.0,
+            left.1,
+            right.1,
+            flip_output,
+            op_function,
+        )
+    }
+
+    /// Same as `Self::fused_binary_flip_op`, but the progress of the operation is reported
+    /// to the given `observer` (see `ApplyObserver` and `ApplyStatistics`).
+    pub fn fused_binary_flip_op_with_observer<T, O>(
+        left: (&Bdd, Option<BddVariable>),
+        right: (&Bdd, Option<BddVariable>),
+        flip_output: Option<BddVariable>,
+        op_function: T,
+        observer: &mut O,
+    ) -> Bdd
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+        O: ApplyObserver,
+    {
+        let mut context = ApplyContext::with_capacity(max(left.0.size(), right.0.size()));
+        unwrap_unlimited(apply_with_flip_in_context(
+            &mut context,
+            &mut Unlimited,
+            observer,
+            left.0,
+            right.0,
+            left.1,
+            right.1,
+            flip_output,
+            op_function,
+        ))
+    }
+
     /// Performs a "dry run" of the supplied operation. This computes two useful results:
     ///
     /// 1. A true value indicating that the resulting BDD will *not* be "empty" (i.e.
@@ -208,8 +325,59 @@ impl Bdd {
     }
 }
 
+/// Basic boolean logical operations where the left operand is a byte-encoded `BddRef`.
+///
+/// The result is always a standard (owned) `Bdd`.
+impl BddRef<'_> {
+    /// Same as `Bdd::and`, but the left operand is this `BddRef`.
+    pub fn and(&self, right: &Bdd) -> Bdd {
+        apply(self, right, op_function::and)
+    }
+
+    /// Same as `Bdd::or`, but the left operand is this `BddRef`.
+    pub fn or(&self, right: &Bdd) -> Bdd {
+        apply(self, right, op_function::or)
+    }
+
+    /// Same as `Bdd::imp`, but the left operand is this `BddRef`.
+    pub fn imp(&self, right: &Bdd) -> Bdd {
+        apply(self, right, op_function::imp)
+    }
+
+    /// Same as `Bdd::iff`, but the left operand is this `BddRef`.
+    pub fn iff(&self, right: &Bdd) -> Bdd {
+        apply(self, right, op_function::iff)
+    }
+
+    /// Same as `Bdd::xor`, but the left operand is this `BddRef`.
+    pub fn xor(&self, right: &Bdd) -> Bdd {
+        apply(self, right, op_function::xor)
+    }
+
+    /// Same as `Bdd::and_not`, but the left operand is this `BddRef`.
+    pub fn and_not(&self, right: &Bdd) -> Bdd {
+        apply(self, right, op_function::and_not)
+    }
+
+    /// Same as `Bdd::binary_op`, but the left operand is this `BddRef`.
+    pub fn binary_op<T>(&self, right: &Bdd, op_function: T) -> Bdd
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        apply(self, right, op_function)
+    }
+
+    /// Same as `BddRef::binary_op`, but both operands are byte-encoded `BddRef` objects.
+    pub fn binary_op_with_ref<T>(&self, right: &BddRef, op_function: T) -> Bdd
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        apply(self, right, op_function)
+    }
+}
+
 /// **(internal)** Shorthand for the more advanced apply which includes variable flipping
-fn apply<T>(left: &Bdd, right: &Bdd, terminal_lookup: T) -> Bdd
+fn apply<L: BddNodes, R: BddNodes, T>(left: &L, right: &R, terminal_lookup: T) -> Bdd
 where
     T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
 {
@@ -231,9 +399,9 @@ where
 /// The reason why we allow this behaviour in apply, is that flipping the pointers in a BDD is cheap,
 /// but breaks the DFS order, which may result in unexpected behaviour. Furthermore, since the
 /// function is generic, in most performance intensive paths, it should be optimized anyway.
-fn apply_with_flip<T>(
-    left: &Bdd,
-    right: &Bdd,
+fn apply_with_flip<L: BddNodes, R: BddNodes, T>(
+    left: &L,
+    right: &R,
     flip_left_if: Option<BddVariable>,
     flip_right_if: Option<BddVariable>,
     flip_out_if: Option<BddVariable>,
@@ -241,6 +409,43 @@ fn apply_with_flip<T>(
 ) -> Bdd
 where
     T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+{
+    let mut context = ApplyContext::with_capacity(max(left.size(), right.size()));
+    unwrap_unlimited(apply_with_flip_in_context(
+        &mut context,
+        &mut Unlimited,
+        &mut (),
+        left,
+        right,
+        flip_left_if,
+        flip_right_if,
+        flip_out_if,
+        terminal_lookup,
+    ))
+}
+
+/// **(internal)** The same as `apply_with_flip`, but the working memory of the algorithm
+/// is taken from the given `context` (and it stays allocated there once the operation
+/// is finished), the computation is aborted once the `tracker` budget is exceeded, and the progress
+/// is reported to the `observer`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn apply_with_flip_in_context<L, R, T, O, K>(
+    context: &mut ApplyContext,
+    tracker: &mut K,
+    observer: &mut O,
+    left: &L,
+    right: &R,
+    flip_left_if: Option<BddVariable>,
+    flip_right_if: Option<BddVariable>,
+    flip_out_if: Option<BddVariable>,
+    terminal_lookup: T,
+) -> Result<Bdd, BudgetExceeded>
+where
+    L: BddNodes,
+    R: BddNodes,
+    T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    O: ApplyObserver,
+    K: Tracker,
 {
     let num_vars = left.num_vars();
     if right.num_vars() != num_vars {
@@ -259,15 +464,24 @@ where
     let mut result: Bdd = Bdd::mk_true(num_vars);
     let mut is_not_empty = false;
 
+    // All working memory is borrowed from the context and cleared first (this keeps
+    // the allocated capacity from previous operations).
+    let ApplyContext {
+        existing,
+        stack,
+        finished,
+        ..
+    } = context;
+    existing.clear();
+    stack.clear();
+    finished.clear();
+
     // Every node in `result` is inserted into `existing` - this ensures we have no duplicates.
-    let mut existing: HashMap<BddNode, BddPointer, FxBuildHasher> =
-        HashMap::with_capacity_and_hasher(max(left.size(), right.size()), FxBuildHasher::default());
     existing.insert(BddNode::mk_zero(num_vars), BddPointer::zero());
     existing.insert(BddNode::mk_one(num_vars), BddPointer::one());
 
     // `stack` is used to explore the two BDDs "side by side" in DFS-like manner. Each task
     // on the stack is a pair of nodes that needs to be fully processed before we are finished.
-    let mut stack: Vec<Task> = Vec::with_capacity(max(left.size(), right.size()));
     stack.push(Task {
         left: left.root_pointer(),
         right: right.root_pointer(),
@@ -275,10 +489,9 @@ where
 
     // `finished` is a memoization cache of tasks which are already completed, since the same
     // combination of nodes can be often explored multiple times.
-    let mut finished: HashMap<Task, BddPointer, FxBuildHasher> =
-        HashMap::with_capacity_and_hasher(max(left.size(), right.size()), FxBuildHasher::default());
 
     while let Some(on_stack) = stack.last() {
+        tracker.step()?;
         if finished.contains_key(on_stack) {
             stack.pop();
         } else {
@@ -317,15 +530,24 @@ where
             };
 
             // Try to solve the tasks using terminal lookup table or from cache.
-            let new_low = terminal_lookup(l_low.as_bool(), r_low.as_bool())
+            let low_lookup = terminal_lookup(l_low.as_bool(), r_low.as_bool());
+            let high_lookup = terminal_lookup(l_high.as_bool(), r_high.as_bool());
+            let new_low = low_lookup
                 .map(BddPointer::from_bool)
                 .or_else(|| finished.get(&comp_low).cloned());
-            let new_high = terminal_lookup(l_high.as_bool(), r_high.as_bool())
+            let new_high = high_lookup
                 .map(BddPointer::from_bool)
                 .or_else(|| finished.get(&comp_high).cloned());
+            if low_lookup.is_none() && new_low.is_some() {
+                observer.on_cache_hit();
+            }
+            if high_lookup.is_none() && new_high.is_some() {
+                observer.on_cache_hit();
+            }
 
             // If both values are computed, mark this task as resolved.
             if let (Some(new_low), Some(new_high)) = (new_low, new_high) {
+                observer.on_task(decision_var, stack.len());
                 if new_low.is_one() || new_high.is_one() {
                     is_not_empty = true
                 }
@@ -346,6 +568,8 @@ where
                     } else {
                         // Node does not exist, it needs to be pushed to result.
                         result.push_node(node);
+                        tracker.check_nodes(result.size())?;
+                        observer.on_node_created(decision_var);
                         existing.insert(node, result.root_pointer());
                         finished.insert(*on_stack, result.root_pointer());
                     }
@@ -373,11 +597,11 @@ where
         }
     }
 
-    if is_not_empty {
+    Ok(if is_not_empty {
         result
     } else {
         Bdd::mk_false(num_vars)
-    }
+    })
 }
 
 /// **(internal)** A simple utility method for checking bounds of a flip variable.
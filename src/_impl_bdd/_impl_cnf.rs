@@ -1,4 +1,5 @@
-use crate::{Bdd, BddNode, BddPartialValuation, BddPointer, BddVariable};
+use crate::_impl_budget::{unwrap_unlimited, Tracker, Unlimited};
+use crate::{Bdd, BddNode, BddPartialValuation, BddPointer, BddVariable, BudgetExceeded};
 use fxhash::FxBuildHasher;
 use std::collections::HashMap;
 
@@ -9,26 +10,38 @@ impl Bdd {
     /// number of clauses low, this could be slightly slower due to all the recursion. However,
     /// it definitely needs to be tested at some point.
     pub(crate) fn mk_cnf(num_vars: u16, cnf: &[BddPartialValuation]) -> Bdd {
+        unwrap_unlimited(Bdd::mk_cnf_with_tracker(num_vars, cnf, &mut Unlimited))
+    }
+
+    /// **(internal)** Same as `Bdd::mk_cnf`, but the construction is aborted once the given
+    /// `tracker` reports that the budget is exceeded.
+    pub(crate) fn mk_cnf_with_tracker<K: Tracker>(
+        num_vars: u16,
+        cnf: &[BddPartialValuation],
+        tracker: &mut K,
+    ) -> Result<Bdd, BudgetExceeded> {
         // This is essentially a "dual" algorithm to the DNF implementation. Relevant explanation
         // can be found there.
 
         if cnf.is_empty() {
-            return Bdd::mk_true(num_vars);
+            return Ok(Bdd::mk_true(num_vars));
         }
 
-        fn build_recursive(
+        fn build_recursive<K: Tracker>(
             num_vars: u16,
             mut variable: u16,
             cnf: &[&BddPartialValuation],
             result: &mut Bdd,
             node_cache: &mut HashMap<BddNode, BddPointer, FxBuildHasher>,
-        ) -> BddPointer {
+            tracker: &mut K,
+        ) -> Result<BddPointer, BudgetExceeded> {
+            tracker.step()?;
             loop {
                 if variable == num_vars {
-                    return BddPointer::from_bool(cnf.is_empty());
+                    return Ok(BddPointer::from_bool(cnf.is_empty()));
                 }
                 if cnf.is_empty() {
-                    return BddPointer::one();
+                    return Ok(BddPointer::one());
                 }
 
                 let var = BddVariable(variable);
@@ -55,20 +68,35 @@ impl Bdd {
                     }
                 }
 
-                let high = build_recursive(num_vars, variable + 1, &var_true, result, node_cache);
-                let low = build_recursive(num_vars, variable + 1, &var_false, result, node_cache);
+                let high = build_recursive(
+                    num_vars,
+                    variable + 1,
+                    &var_true,
+                    result,
+                    node_cache,
+                    tracker,
+                )?;
+                let low = build_recursive(
+                    num_vars,
+                    variable + 1,
+                    &var_false,
+                    result,
+                    node_cache,
+                    tracker,
+                )?;
 
                 if high == low {
-                    return high;
+                    return Ok(high);
                 }
 
                 let node = BddNode::mk_node(var, low, high);
                 return if let Some(id) = node_cache.get(&node) {
-                    *id
+                    Ok(*id)
                 } else {
                     result.push_node(node);
+                    tracker.check_nodes(result.size())?;
                     node_cache.insert(node, result.root_pointer());
-                    result.root_pointer()
+                    Ok(result.root_pointer())
                 };
             }
         }
@@ -79,12 +107,13 @@ impl Bdd {
         node_cache.insert(BddNode::mk_one(num_vars), BddPointer::one());
 
         let cnf = Vec::from_iter(cnf.iter());
-        let result_pointer = build_recursive(num_vars, 0, &cnf, &mut result, &mut node_cache);
-        if result_pointer.is_zero() {
+        let result_pointer =
+            build_recursive(num_vars, 0, &cnf, &mut result, &mut node_cache, tracker)?;
+        Ok(if result_pointer.is_zero() {
             Bdd::mk_false(num_vars)
         } else {
             result
-        }
+        })
     }
 
     /// Construct a CNF representation of this BDD.
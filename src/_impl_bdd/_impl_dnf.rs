@@ -1,4 +1,5 @@
-use crate::{Bdd, BddNode, BddPartialValuation, BddPointer, BddVariable};
+use crate::_impl_budget::{unwrap_unlimited, Tracker, Unlimited};
+use crate::{Bdd, BddNode, BddPartialValuation, BddPointer, BddVariable, BudgetExceeded};
 use fxhash::FxBuildHasher;
 use std::collections::HashMap;
 
@@ -9,20 +10,32 @@ impl Bdd {
     /// number of clauses low, this could be slightly slower due to all the recursion. However,
     /// it definitely needs to be tested at some point.
     pub(crate) fn mk_dnf(num_vars: u16, dnf: &[BddPartialValuation]) -> Bdd {
+        unwrap_unlimited(Bdd::mk_dnf_with_tracker(num_vars, dnf, &mut Unlimited))
+    }
+
+    /// **(internal)** Same as `Bdd::mk_dnf`, but the construction is aborted once the given
+    /// `tracker` reports that the budget is exceeded.
+    pub(crate) fn mk_dnf_with_tracker<K: Tracker>(
+        num_vars: u16,
+        dnf: &[BddPartialValuation],
+        tracker: &mut K,
+    ) -> Result<Bdd, BudgetExceeded> {
         if dnf.is_empty() {
-            return Bdd::mk_false(num_vars);
+            return Ok(Bdd::mk_false(num_vars));
         }
 
         // TODO:
         //  Can we turn the algorithm into a normal loop to prevent stack overflow in
         //  extreme cases?
-        fn build_recursive(
+        fn build_recursive<K: Tracker>(
             num_vars: u16,
             mut variable: u16,
             dnf: &[&BddPartialValuation],
             result: &mut Bdd,
             node_cache: &mut HashMap<BddNode, BddPointer, FxBuildHasher>,
-        ) -> BddPointer {
+            tracker: &mut K,
+        ) -> Result<BddPointer, BudgetExceeded> {
+            tracker.step()?;
             // The loop will automatically skip variables that are not relevant for the validity
             // of the provided DNF. This should significantly decrease the risk of stack overflow,
             // since we only run recursion when it is reasonably likely that we actually need to
@@ -30,10 +43,10 @@ impl Bdd {
             // we would get `low == high` anyway.
             loop {
                 if variable == num_vars {
-                    return BddPointer::from_bool(!dnf.is_empty());
+                    return Ok(BddPointer::from_bool(!dnf.is_empty()));
                 }
                 if dnf.is_empty() {
-                    return BddPointer::zero();
+                    return Ok(BddPointer::zero());
                 }
 
                 let var = BddVariable(variable);
@@ -56,20 +69,35 @@ impl Bdd {
                     }
                 }
 
-                let high = build_recursive(num_vars, variable + 1, &var_true, result, node_cache);
-                let low = build_recursive(num_vars, variable + 1, &var_false, result, node_cache);
+                let high = build_recursive(
+                    num_vars,
+                    variable + 1,
+                    &var_true,
+                    result,
+                    node_cache,
+                    tracker,
+                )?;
+                let low = build_recursive(
+                    num_vars,
+                    variable + 1,
+                    &var_false,
+                    result,
+                    node_cache,
+                    tracker,
+                )?;
 
                 if high == low {
-                    return high;
+                    return Ok(high);
                 }
 
                 let node = BddNode::mk_node(var, low, high);
                 return if let Some(id) = node_cache.get(&node) {
-                    *id
+                    Ok(*id)
                 } else {
                     result.push_node(node);
+                    tracker.check_nodes(result.size())?;
                     node_cache.insert(node, result.root_pointer());
-                    result.root_pointer()
+                    Ok(result.root_pointer())
                 };
             }
         }
@@ -80,9 +108,9 @@ impl Bdd {
         node_cache.insert(BddNode::mk_one(num_vars), BddPointer::one());
 
         let dnf = Vec::from_iter(dnf.iter());
-        build_recursive(num_vars, 0, &dnf, &mut result, &mut node_cache);
+        build_recursive(num_vars, 0, &dnf, &mut result, &mut node_cache, tracker)?;
 
-        result
+        Ok(result)
     }
 
     /// Construct a DNF representation of this BDD. This is equivalent to collecting all results
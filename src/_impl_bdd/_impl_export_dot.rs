@@ -1,4 +1,5 @@
 use crate::*;
+use fxhash::FxBuildHasher;
 use std::io::Write;
 
 /// `.dot` export procedure for `Bdd`s.
@@ -15,7 +16,8 @@ impl Bdd {
         variables: &BddVariableSet,
         zero_pruned: bool,
     ) -> Result<(), std::io::Error> {
-        write_bdd_as_dot(output, self, &variables.var_names, zero_pruned)
+        let options = DotExportOptions::new().zero_pruned(zero_pruned);
+        self.write_as_dot_string_with_options(output, variables, &options)
     }
 
     /// Convert this `Bdd` to a `.dot` string.
@@ -25,79 +27,349 @@ impl Bdd {
     /// If `zero_pruned` is true, edges leading to `zero` are not shown. This can greatly
     /// simplify the graph without losing information.
     pub fn to_dot_string(&self, variables: &BddVariableSet, zero_pruned: bool) -> String {
-        bdd_to_dot_string(self, &variables.var_names, zero_pruned)
+        let options = DotExportOptions::new().zero_pruned(zero_pruned);
+        self.to_dot_string_with_options(variables, &options)
     }
+
+    /// Output this `Bdd` as a `.dot` string into the given `output` writer, using the
+    /// given `DotExportOptions`.
+    pub fn write_as_dot_string_with_options(
+        &self,
+        output: &mut dyn Write,
+        variables: &BddVariableSet,
+        options: &DotExportOptions,
+    ) -> Result<(), std::io::Error> {
+        write_bdds_as_dot(output, &[(None, self)], &variables.var_names, options)
+    }
+
+    /// Convert this `Bdd` to a `.dot` string, using the given `DotExportOptions`.
+    pub fn to_dot_string_with_options(
+        &self,
+        variables: &BddVariableSet,
+        options: &DotExportOptions,
+    ) -> String {
+        bdds_to_dot_string(&[(None, self)], &variables.var_names, options)
+    }
+
+    /// Output several `Bdd`s as a single shared `.dot` graph into the given `output` writer.
+    ///
+    /// Nodes which are shared by several `Bdd`s only appear in the graph once. Each root
+    /// is marked by a node with the given label.
+    pub fn write_as_shared_dot_string(
+        output: &mut dyn Write,
+        variables: &BddVariableSet,
+        roots: &[(&str, &Bdd)],
+        options: &DotExportOptions,
+    ) -> Result<(), std::io::Error> {
+        let roots = labelled_roots(roots);
+        write_bdds_as_dot(output, &roots, &variables.var_names, options)
+    }
+
+    /// Convert several `Bdd`s to a single shared `.dot` graph.
+    ///
+    /// See also `Bdd::write_as_shared_dot_string`.
+    pub fn to_shared_dot_string(
+        variables: &BddVariableSet,
+        roots: &[(&str, &Bdd)],
+        options: &DotExportOptions,
+    ) -> String {
+        bdds_to_dot_string(&labelled_roots(roots), &variables.var_names, options)
+    }
+}
+
+impl DotExportOptions {
+    /// Create the default export options.
+    pub fn new() -> DotExportOptions {
+        DotExportOptions {
+            zero_pruned: false,
+            complement: false,
+            rank_by_level: false,
+            node_style: None,
+            terminal_style: None,
+            high_edge_style: "style=filled".to_string(),
+            low_edge_style: "style=dotted".to_string(),
+            highlight: None,
+            highlight_style: "color=red, penwidth=2".to_string(),
+        }
+    }
+
+    /// If true, edges leading to `zero` (and the `zero` node itself) are not shown.
+    pub fn zero_pruned(mut self, zero_pruned: bool) -> DotExportOptions {
+        self.zero_pruned = zero_pruned;
+        self
+    }
+
+    /// If true, the labels of the terminal nodes are swapped, i.e. the graph displays the
+    /// complement of the exported `Bdd`s. Pruning then removes the edges leading to
+    /// the (displayed) `zero` node.
+    pub fn complement(mut self, complement: bool) -> DotExportOptions {
+        self.complement = complement;
+        self
+    }
+
+    /// If true, nodes conditioning on the same variable are placed on the same rank.
+    pub fn rank_by_level(mut self, rank_by_level: bool) -> DotExportOptions {
+        self.rank_by_level = rank_by_level;
+        self
+    }
+
+    /// Extra `.dot` attributes for decision nodes, e.g. `shape=circle, fontsize=10`.
+    pub fn node_style(mut self, style: &str) -> DotExportOptions {
+        self.node_style = Some(style.to_string());
+        self
+    }
+
+    /// `.dot` attributes for terminal nodes, replacing the default box style.
+    pub fn terminal_style(mut self, style: &str) -> DotExportOptions {
+        self.terminal_style = Some(style.to_string());
+        self
+    }
+
+    /// `.dot` attributes for edges leading to the `high` successor (default `style=filled`).
+    pub fn high_edge_style(mut self, style: &str) -> DotExportOptions {
+        self.high_edge_style = style.to_string();
+        self
+    }
+
+    /// `.dot` attributes for edges leading to the `low` successor (default `style=dotted`).
+    pub fn low_edge_style(mut self, style: &str) -> DotExportOptions {
+        self.low_edge_style = style.to_string();
+        self
+    }
+
+    /// Highlight the path which is followed by the given (partial) valuation, starting
+    /// in each root. The path ends once it reaches a terminal node or a variable that
+    /// is not fixed in the valuation. This works well with clauses returned
+    /// by `Bdd::sat_clauses`.
+    pub fn highlight_path(mut self, path: BddPartialValuation) -> DotExportOptions {
+        self.highlight = Some(path);
+        self
+    }
+
+    /// Highlight the path which is followed by the given valuation, starting in each root.
+    pub fn highlight_valuation(self, valuation: BddValuation) -> DotExportOptions {
+        self.highlight_path(BddPartialValuation::from(valuation))
+    }
+
+    /// `.dot` attributes added to highlighted nodes and edges (default `color=red, penwidth=2`).
+    pub fn highlight_style(mut self, style: &str) -> DotExportOptions {
+        self.highlight_style = style.to_string();
+        self
+    }
+}
+
+impl Default for DotExportOptions {
+    fn default() -> Self {
+        DotExportOptions::new()
+    }
+}
+
+/// **(internal)** Convert the user-facing list of labelled roots into the internal format.
+fn labelled_roots<'a>(roots: &[(&'a str, &'a Bdd)]) -> Vec<(Option<&'a str>, &'a Bdd)> {
+    roots.iter().map(|(l, bdd)| (Some(*l), *bdd)).collect()
+}
+
+/// **(internal)** Escape a string such that it can appear inside a quoted `.dot` label.
+fn escape_label(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
 }
 
-/// Write given `Bdd` into the output buffer as `.dot` graph. Use `var_names` to specify
-/// custom names for individual variables. If `pruned` is true, the output will only
-/// contain edges leading to the `1` terminal node (this is often much easier to read
-/// than the full graph while preserving all the information).
-fn write_bdd_as_dot(
+/// Write given `Bdd`s into the output buffer as a single `.dot` graph. Use `var_names` to
+/// specify custom names for individual variables.
+///
+/// The `Bdd`s are first merged into a single node table, such that shared nodes appear only
+/// once. For a single `Bdd`, the node table is exactly the original `Bdd`, so node
+/// identifiers correspond to `BddPointer` values. A root without a label is marked using
+/// an invisible initial node.
+fn write_bdds_as_dot(
     output: &mut dyn Write,
-    bdd: &Bdd,
+    roots: &[(Option<&str>, &Bdd)],
     var_names: &[String],
-    zero_pruned: bool,
+    options: &DotExportOptions,
 ) -> Result<(), std::io::Error> {
-    if var_names.len() != (bdd.num_vars() as usize) {
-        panic!(
-            "Bdd is incompatible with the variable set ({} vs. {} variables)",
-            bdd.num_vars(),
-            var_names.len()
-        );
+    let num_vars = var_names.len() as u16;
+    let mut nodes = vec![BddNode::mk_zero(num_vars), BddNode::mk_one(num_vars)];
+    let mut existing: HashMap<BddNode, BddPointer, FxBuildHasher> =
+        HashMap::with_capacity_and_hasher(roots.len(), FxBuildHasher::default());
+    let mut root_pointers = Vec::with_capacity(roots.len());
+    for (_, bdd) in roots {
+        if bdd.num_vars() != num_vars {
+            panic!(
+                "Bdd is incompatible with the variable set ({} vs. {} variables)",
+                bdd.num_vars(),
+                var_names.len()
+            );
+        }
+        let mut translation = vec![BddPointer::zero(); bdd.size()];
+        if bdd.size() > 1 {
+            translation[1] = BddPointer::one();
+        }
+        for node_pointer in bdd.pointers().skip(2) {
+            let node = BddNode::mk_node(
+                bdd.var_of(node_pointer),
+                translation[bdd.low_link_of(node_pointer).to_index()],
+                translation[bdd.high_link_of(node_pointer).to_index()],
+            );
+            let id = *existing.entry(node).or_insert_with(|| {
+                nodes.push(node);
+                BddPointer::from_index(nodes.len() - 1)
+            });
+            translation[node_pointer.to_index()] = id;
+        }
+        root_pointers.push(translation[bdd.root_pointer().to_index()]);
     }
+
+    // Collect the highlighted nodes and edges (as pairs of source node and edge value).
+    let mut highlight_nodes: HashSet<BddPointer> = HashSet::new();
+    let mut highlight_edges: HashSet<(BddPointer, bool)> = HashSet::new();
+    if let Some(path) = &options.highlight {
+        for root in &root_pointers {
+            let mut node = *root;
+            highlight_nodes.insert(node);
+            while !node.is_terminal() {
+                let Some(value) = path.get_value(nodes[node.to_index()].var) else {
+                    break;
+                };
+                highlight_edges.insert((node, value));
+                let node_data = &nodes[node.to_index()];
+                node = if value {
+                    node_data.high_link
+                } else {
+                    node_data.low_link
+                };
+                highlight_nodes.insert(node);
+            }
+        }
+    }
+    let highlight_suffix = |highlight: bool| {
+        if highlight {
+            format!(", {}", options.highlight_style)
+        } else {
+            String::new()
+        }
+    };
+
+    // If the terminals are complemented, the pruned terminal is the `one` node.
+    let pruned_terminal = if !options.zero_pruned {
+        None
+    } else if options.complement {
+        Some(BddPointer::one())
+    } else {
+        Some(BddPointer::zero())
+    };
+    // The pruned terminal is still declared when it is one of the roots.
+    let is_declared = |terminal: &BddPointer| {
+        Some(*terminal) != pruned_terminal || root_pointers.contains(terminal)
+    };
+
     writeln!(output, "digraph G {{")?;
-    writeln!(
-        output,
-        "init__ [label=\"\", style=invis, height=0, width=0];"
-    )?;
-    writeln!(output, "init__ -> {};", bdd.root_pointer())?;
+    for (i, ((label, _), root)) in roots.iter().zip(&root_pointers).enumerate() {
+        if let Some(label) = label {
+            writeln!(
+                output,
+                "root_{} [label=\"{}\", shape=plaintext];",
+                i,
+                escape_label(label)
+            )?;
+            writeln!(output, "root_{} -> {};", i, root)?;
+        } else {
+            writeln!(
+                output,
+                "init__ [label=\"\", style=invis, height=0, width=0];"
+            )?;
+            writeln!(output, "init__ -> {};", root)?;
+        }
+    }
 
     /*
        Fortunately, it seem that .dot does not care about ordering of graph elements,
        so we can just go through the BDD and print it as is.
-
-       Note that for BDD slices, this can output unused nodes, but we don't support slices yet anyway.
     */
 
     // terminal nodes
-    if !zero_pruned {
+    for terminal in [BddPointer::zero(), BddPointer::one()] {
+        if !is_declared(&terminal) {
+            continue;
+        }
+        let value = terminal.is_one() != options.complement;
+        let label = if value { "1" } else { "0" };
+        let suffix = highlight_suffix(highlight_nodes.contains(&terminal));
+        if let Some(style) = &options.terminal_style {
+            writeln!(
+                output,
+                "{} [label=\"{}\", {}{}];",
+                terminal, label, style, suffix
+            )?;
+        } else {
+            writeln!(
+                output,
+                "{} [shape=box, label=\"{}\", style=filled, shape=box, height=0.3, width=0.3{}];",
+                terminal, label, suffix
+            )?;
+        }
+    }
+
+    // decision nodes
+    for (index, node) in nodes.iter().enumerate().skip(2) {
+        let node_pointer = BddPointer::from_index(index);
+        let var_name = escape_label(&var_names[node.var.0 as usize]);
+        let style = options
+            .node_style
+            .as_ref()
+            .map(|it| format!(", {}", it))
+            .unwrap_or_default();
+        let suffix = highlight_suffix(highlight_nodes.contains(&node_pointer));
         writeln!(
             output,
-            "0 [shape=box, label=\"0\", style=filled, shape=box, height=0.3, width=0.3];"
+            "{}[label=\"{}\"{}{}];",
+            node_pointer, var_name, style, suffix
         )?;
+        if Some(node.high_link) != pruned_terminal {
+            let suffix = highlight_suffix(highlight_edges.contains(&(node_pointer, true)));
+            writeln!(
+                output,
+                "{} -> {} [{}{}];",
+                node_pointer, node.high_link, options.high_edge_style, suffix
+            )?;
+        }
+        if Some(node.low_link) != pruned_terminal {
+            let suffix = highlight_suffix(highlight_edges.contains(&(node_pointer, false)));
+            writeln!(
+                output,
+                "{} -> {} [{}{}];",
+                node_pointer, node.low_link, options.low_edge_style, suffix
+            )?;
+        }
     }
-    writeln!(
-        output,
-        "1 [shape=box, label=\"1\", style=filled, shape=box, height=0.3, width=0.3];"
-    )?;
 
-    // decision nodes
-    for node_pointer in bdd.pointers().skip(2) {
-        // write the node itself
-        let var_name = &var_names[bdd.var_of(node_pointer).0 as usize];
-        writeln!(output, "{}[label=\"{}\"];", node_pointer, var_name)?;
-        let high_link = bdd.high_link_of(node_pointer);
-        if !zero_pruned || !high_link.is_zero() {
-            // write "high" link
-            writeln!(output, "{} -> {} [style=filled];", node_pointer, high_link)?;
+    if options.rank_by_level {
+        let mut levels: Vec<Vec<BddPointer>> = vec![Vec::new(); var_names.len()];
+        for (index, node) in nodes.iter().enumerate().skip(2) {
+            levels[node.var.0 as usize].push(BddPointer::from_index(index));
         }
-        let low_link = bdd.low_link_of(node_pointer);
-        if !zero_pruned || !low_link.is_zero() {
-            // write "low" link
-            writeln!(output, "{} -> {} [style=dotted];", node_pointer, low_link)?;
+        let mut terminals = vec![BddPointer::zero(), BddPointer::one()];
+        terminals.retain(is_declared);
+        levels.push(terminals);
+        for level in levels.into_iter().filter(|it| !it.is_empty()) {
+            let ids: Vec<String> = level.iter().map(|it| it.to_string()).collect();
+            writeln!(output, "{{rank=same; {};}}", ids.join("; "))?;
         }
     }
+
     writeln!(output, "}}")?;
     Ok(())
 }
 
-/// Converts the given BDD to a .dot graph string using given variable names.
+/// Converts the given BDDs to a .dot graph string using given variable names.
 ///
-/// See also: [bdd_as_dot](fn.bdd_as_dot.html)
-fn bdd_to_dot_string(bdd: &Bdd, var_names: &[String], zero_pruned: bool) -> String {
+/// See also: `write_bdds_as_dot`.
+fn bdds_to_dot_string(
+    roots: &[(Option<&str>, &Bdd)],
+    var_names: &[String],
+    options: &DotExportOptions,
+) -> String {
     let mut buffer: Vec<u8> = Vec::new();
-    write_bdd_as_dot(&mut buffer, bdd, var_names, zero_pruned)
+    write_bdds_as_dot(&mut buffer, roots, var_names, options)
         .expect("Cannot write BDD to .dot string.");
     String::from_utf8(buffer).expect("Invalid UTF formatting in .dot string.")
 }
@@ -138,4 +410,106 @@ mod tests {
         let dot = bdd.to_dot_string(&variables, true);
         assert_eq!(load_expected_results("bdd_to_dot_pruned.dot"), dot);
     }
+
+    #[test]
+    fn bdd_to_dot_default_options() {
+        let variables = BddVariableSet::new(&["a", "b", "c", "d", "e"]);
+        let bdd = mk_small_test_bdd();
+        for pruned in [false, true] {
+            let options = DotExportOptions::new().zero_pruned(pruned);
+            assert_eq!(
+                bdd.to_dot_string(&variables, pruned),
+                bdd.to_dot_string_with_options(&variables, &options)
+            );
+        }
+    }
+
+    #[test]
+    fn bdd_to_dot_styled() {
+        let variables = BddVariableSet::new(&["a", "b", "c", "d", "e"]);
+        let bdd = mk_small_test_bdd();
+        let options = DotExportOptions::new()
+            .rank_by_level(true)
+            .node_style("shape=circle")
+            .terminal_style("shape=square")
+            .high_edge_style("color=blue")
+            .low_edge_style("style=dashed");
+        let dot = bdd.to_dot_string_with_options(&variables, &options);
+        assert!(dot.contains("3[label=\"c\", shape=circle];"));
+        assert!(dot.contains("0 [label=\"0\", shape=square];"));
+        assert!(dot.contains("3 -> 2 [color=blue];"));
+        assert!(dot.contains("3 -> 0 [style=dashed];"));
+        assert!(dot.contains("{rank=same; 2;}"));
+        assert!(dot.contains("{rank=same; 0; 1;}"));
+    }
+
+    #[test]
+    fn bdd_to_dot_complement() {
+        let variables = BddVariableSet::new(&["a", "b", "c", "d", "e"]);
+        let bdd = mk_small_test_bdd();
+        let options = DotExportOptions::new().complement(true).zero_pruned(true);
+        let dot = bdd.to_dot_string_with_options(&variables, &options);
+        assert!(dot.contains("0 [shape=box, label=\"1\""));
+        assert!(!dot.contains("1 [shape=box"));
+        assert!(!dot.contains("2 -> 1"));
+        assert!(dot.contains("2 -> 0"));
+    }
+
+    #[test]
+    fn bdd_to_dot_highlight() {
+        let variables = BddVariableSet::new(&["a", "b", "c", "d", "e"]);
+        let bdd = mk_small_test_bdd();
+        let path = bdd.sat_clauses().next().unwrap();
+        let options = DotExportOptions::new()
+            .highlight_path(path)
+            .highlight_style("color=red");
+        let dot = bdd.to_dot_string_with_options(&variables, &options);
+        assert!(dot.contains("3[label=\"c\", color=red];"));
+        assert!(dot.contains("3 -> 2 [style=filled, color=red];"));
+        assert!(dot.contains("2 -> 1 [style=dotted, color=red];"));
+        assert!(dot.contains("3 -> 0 [style=dotted];"));
+        assert!(dot.contains("1 [shape=box, label=\"1\", style=filled, shape=box, height=0.3, width=0.3, color=red];"));
+
+        // A valuation that leads to `zero` through the low edge of `c`.
+        let valuation = BddValuation::new(vec![false; 5]);
+        let options = DotExportOptions::new()
+            .highlight_valuation(valuation)
+            .highlight_style("color=red");
+        let dot = bdd.to_dot_string_with_options(&variables, &options);
+        assert!(dot.contains("3 -> 0 [style=dotted, color=red];"));
+        assert!(dot.contains("2[label=\"d\"];"));
+    }
+
+    #[test]
+    fn bdd_to_shared_dot() {
+        let variables = BddVariableSet::new(&["a", "b", "c", "d", "e"]);
+        let f = variables.eval_expression_string("c & !d");
+        let g = variables.eval_expression_string("!d");
+        let h = variables.mk_false();
+        let roots = [("f", &f), ("g", &g), ("h \"quoted\"", &h)];
+        let dot = Bdd::to_shared_dot_string(&variables, &roots, &DotExportOptions::new());
+        // `!d` is shared by both graphs, so there are only two decision nodes.
+        assert_eq!(1, dot.matches("[label=\"d\"]").count());
+        assert_eq!(1, dot.matches("[label=\"c\"]").count());
+        assert!(dot.contains("root_0 [label=\"f\", shape=plaintext];"));
+        assert!(dot.contains("root_0 -> 3;"));
+        assert!(dot.contains("root_1 -> 2;"));
+        assert!(dot.contains("root_2 [label=\"h \\\"quoted\\\"\", shape=plaintext];"));
+        assert!(dot.contains("root_2 -> 0;"));
+        assert!(!dot.contains("init__"));
+        // The pruned terminal is still declared when it is a root.
+        let pruned = DotExportOptions::new().zero_pruned(true);
+        let dot =
+            Bdd::to_shared_dot_string(&variables, &roots, &pruned.clone().rank_by_level(true));
+        assert!(dot.contains("root_2 -> 0;"));
+        assert!(dot.contains("0 [shape=box, label=\"0\""));
+        assert!(dot.contains("{rank=same; 0; 1;}"));
+        let dot = Bdd::to_shared_dot_string(&variables, &roots[..2], &pruned);
+        assert!(!dot.contains("label=\"0\""));
+        let complement = pruned.complement(true);
+        let dot =
+            Bdd::to_shared_dot_string(&variables, &[("t", &variables.mk_true())], &complement);
+        assert!(dot.contains("root_0 -> 1;"));
+        assert!(dot.contains("1 [shape=box, label=\"0\""));
+    }
 }
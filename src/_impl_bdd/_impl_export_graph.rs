@@ -0,0 +1,378 @@
+use crate::*;
+use std::io::Write;
+
+/// Export procedures for `Bdd`s into graph formats other than `.dot`: Mermaid, GraphML
+/// and a JSON node-link structure.
+///
+/// In all formats, node identifiers correspond to the `BddPointer` values of the exported
+/// `Bdd`, terminal nodes are labelled `0` and `1`, and decision nodes are labelled by the
+/// name of their conditioning variable. When edges leading to `zero` are pruned, the `zero`
+/// node is still emitted for the `false` Bdd, since it is the root.
+impl Bdd {
+    /// Output this `Bdd` as a Mermaid flowchart into the given `output` writer.
+    ///
+    /// High edges use a solid arrow, low edges use a dotted arrow. If `zero_pruned` is true,
+    /// edges leading to `zero` are not shown.
+    pub fn write_as_mermaid_string(
+        &self,
+        output: &mut dyn Write,
+        variables: &BddVariableSet,
+        zero_pruned: bool,
+    ) -> Result<(), std::io::Error> {
+        write_bdd_as_mermaid(output, self, &variables.var_names, zero_pruned)
+    }
+
+    /// Convert this `Bdd` to a Mermaid flowchart string.
+    ///
+    /// See also `Bdd::write_as_mermaid_string`.
+    pub fn to_mermaid_string(&self, variables: &BddVariableSet, zero_pruned: bool) -> String {
+        write_to_string(|out| self.write_as_mermaid_string(out, variables, zero_pruned))
+    }
+
+    /// Output this `Bdd` as a GraphML document into the given `output` writer.
+    ///
+    /// Nodes have a `label` attribute (and decision nodes also a `variable` attribute with
+    /// the variable index), edges have a `kind` attribute which is either `high` or `low`.
+    /// The graph has a `root` attribute with the identifier of the root node. If `zero_pruned`
+    /// is true, edges leading to `zero` are not shown.
+    pub fn write_as_graphml_string(
+        &self,
+        output: &mut dyn Write,
+        variables: &BddVariableSet,
+        zero_pruned: bool,
+    ) -> Result<(), std::io::Error> {
+        write_bdd_as_graphml(output, self, &variables.var_names, zero_pruned)
+    }
+
+    /// Convert this `Bdd` to a GraphML string.
+    ///
+    /// See also `Bdd::write_as_graphml_string`.
+    pub fn to_graphml_string(&self, variables: &BddVariableSet, zero_pruned: bool) -> String {
+        write_to_string(|out| self.write_as_graphml_string(out, variables, zero_pruned))
+    }
+
+    /// Output this `Bdd` as a JSON node-link object into the given `output` writer.
+    ///
+    /// The object has the form `{"directed": true, "root": 3, "nodes": [...], "links": [...]}`,
+    /// where each node has an `id` and a `label` (and decision nodes also a `variable` index),
+    /// and each link has a `source`, a `target` and a `kind` (`high` or `low`). This is
+    /// the same structure as used by `networkx` or `d3`. If `zero_pruned` is true, edges
+    /// leading to `zero` are not shown.
+    pub fn write_as_json_string(
+        &self,
+        output: &mut dyn Write,
+        variables: &BddVariableSet,
+        zero_pruned: bool,
+    ) -> Result<(), std::io::Error> {
+        write_bdd_as_json(output, self, &variables.var_names, zero_pruned)
+    }
+
+    /// Convert this `Bdd` to a JSON node-link string.
+    ///
+    /// See also `Bdd::write_as_json_string`.
+    pub fn to_json_string(&self, variables: &BddVariableSet, zero_pruned: bool) -> String {
+        write_to_string(|out| self.write_as_json_string(out, variables, zero_pruned))
+    }
+}
+
+/// **(internal)** Panic if the `Bdd` does not match the given variable names.
+pub(super) fn check_var_names(bdd: &Bdd, var_names: &[String]) {
+    if var_names.len() != (bdd.num_vars() as usize) {
+        panic!(
+            "Bdd is incompatible with the variable set ({} vs. {} variables)",
+            bdd.num_vars(),
+            var_names.len()
+        );
+    }
+}
+
+/// **(internal)** Iterate over the displayed nodes of a `Bdd` together with their labels.
+///
+/// The root node is always displayed, even if it is a pruned `zero`.
+fn labelled_nodes<'a>(
+    bdd: &'a Bdd,
+    var_names: &'a [String],
+    zero_pruned: bool,
+) -> impl Iterator<Item = (BddPointer, &'a str)> + 'a {
+    let zero_pruned = zero_pruned && !bdd.is_false();
+    bdd.pointers()
+        .filter(move |it| !(zero_pruned && it.is_zero()))
+        .map(move |it| {
+            let label = if it.is_zero() {
+                "0"
+            } else if it.is_one() {
+                "1"
+            } else {
+                var_names[bdd.var_of(it).0 as usize].as_str()
+            };
+            (it, label)
+        })
+}
+
+/// **(internal)** Iterate over the displayed edges of a `Bdd` as `(source, target, is_high)`.
+fn edges(
+    bdd: &Bdd,
+    zero_pruned: bool,
+) -> impl Iterator<Item = (BddPointer, BddPointer, bool)> + '_ {
+    bdd.pointers().skip(2).flat_map(move |node| {
+        [
+            (node, bdd.high_link_of(node), true),
+            (node, bdd.low_link_of(node), false),
+        ]
+        .into_iter()
+        .filter(move |(_, target, _)| !(zero_pruned && target.is_zero()))
+    })
+}
+
+fn write_bdd_as_mermaid(
+    output: &mut dyn Write,
+    bdd: &Bdd,
+    var_names: &[String],
+    zero_pruned: bool,
+) -> Result<(), std::io::Error> {
+    check_var_names(bdd, var_names);
+    writeln!(output, "graph TD")?;
+    for (node, label) in labelled_nodes(bdd, var_names, zero_pruned) {
+        // Mermaid uses HTML-like entity codes to escape special characters in labels.
+        let label = label.replace('#', "#35;").replace('"', "#quot;");
+        if node.is_terminal() {
+            writeln!(output, "    n{}[\"{}\"]", node, label)?;
+        } else {
+            writeln!(output, "    n{}((\"{}\"))", node, label)?;
+        }
+    }
+    for (source, target, is_high) in edges(bdd, zero_pruned) {
+        if is_high {
+            writeln!(output, "    n{} -->|high| n{}", source, target)?;
+        } else {
+            writeln!(output, "    n{} -.->|low| n{}", source, target)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_bdd_as_graphml(
+    output: &mut dyn Write,
+    bdd: &Bdd,
+    var_names: &[String],
+    zero_pruned: bool,
+) -> Result<(), std::io::Error> {
+    check_var_names(bdd, var_names);
+    writeln!(output, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        output,
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+    )?;
+    writeln!(
+        output,
+        "  <key id=\"root\" for=\"graph\" attr.name=\"root\" attr.type=\"string\"/>"
+    )?;
+    writeln!(
+        output,
+        "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>"
+    )?;
+    writeln!(
+        output,
+        "  <key id=\"variable\" for=\"node\" attr.name=\"variable\" attr.type=\"int\"/>"
+    )?;
+    writeln!(
+        output,
+        "  <key id=\"kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>"
+    )?;
+    writeln!(output, "  <graph id=\"G\" edgedefault=\"directed\">")?;
+    writeln!(
+        output,
+        "    <data key=\"root\">n{}</data>",
+        bdd.root_pointer()
+    )?;
+    for (node, label) in labelled_nodes(bdd, var_names, zero_pruned) {
+        write!(
+            output,
+            "    <node id=\"n{}\"><data key=\"label\">{}</data>",
+            node,
+            escape_xml(label)
+        )?;
+        if !node.is_terminal() {
+            write!(
+                output,
+                "<data key=\"variable\">{}</data>",
+                bdd.var_of(node).0
+            )?;
+        }
+        writeln!(output, "</node>")?;
+    }
+    for (source, target, is_high) in edges(bdd, zero_pruned) {
+        let kind = if is_high { "high" } else { "low" };
+        writeln!(
+            output,
+            "    <edge source=\"n{}\" target=\"n{}\"><data key=\"kind\">{}</data></edge>",
+            source, target, kind
+        )?;
+    }
+    writeln!(output, "  </graph>")?;
+    writeln!(output, "</graphml>")?;
+    Ok(())
+}
+
+fn write_bdd_as_json(
+    output: &mut dyn Write,
+    bdd: &Bdd,
+    var_names: &[String],
+    zero_pruned: bool,
+) -> Result<(), std::io::Error> {
+    check_var_names(bdd, var_names);
+    write!(
+        output,
+        "{{\"directed\":true,\"root\":{},\"nodes\":[",
+        bdd.root_pointer()
+    )?;
+    for (i, (node, label)) in labelled_nodes(bdd, var_names, zero_pruned).enumerate() {
+        if i > 0 {
+            write!(output, ",")?;
+        }
+        write!(output, "{{\"id\":{},\"label\":{}", node, escape_json(label))?;
+        if !node.is_terminal() {
+            write!(output, ",\"variable\":{}", bdd.var_of(node).0)?;
+        }
+        write!(output, "}}")?;
+    }
+    write!(output, "],\"links\":[")?;
+    for (i, (source, target, is_high)) in edges(bdd, zero_pruned).enumerate() {
+        if i > 0 {
+            write!(output, ",")?;
+        }
+        let kind = if is_high { "high" } else { "low" };
+        write!(
+            output,
+            "{{\"source\":{},\"target\":{},\"kind\":\"{}\"}}",
+            source, target, kind
+        )?;
+    }
+    writeln!(output, "]}}")?;
+    Ok(())
+}
+
+/// **(internal)** Escape special XML characters in the given string.
+fn escape_xml(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// **(internal)** Convert the given string into a quoted JSON string literal.
+fn escape_json(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            _ => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// **(internal)** Run the given writer function on a buffer and convert it into a `String`.
+pub(super) fn write_to_string<F>(write: F) -> String
+where
+    F: FnOnce(&mut dyn Write) -> Result<(), std::io::Error>,
+{
+    let mut buffer: Vec<u8> = Vec::new();
+    write(&mut buffer).expect("Cannot write BDD to string.");
+    String::from_utf8(buffer).expect("Invalid UTF formatting in BDD string.")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_small_test_bdd;
+    use crate::*;
+
+    #[test]
+    fn bdd_to_mermaid() {
+        let variables = BddVariableSet::new(&["a", "b", "c", "d", "e"]);
+        let bdd = mk_small_test_bdd();
+        let expected = "graph TD
+    n0[\"0\"]
+    n1[\"1\"]
+    n2((\"d\"))
+    n3((\"c\"))
+    n2 -->|high| n0
+    n2 -.->|low| n1
+    n3 -->|high| n2
+    n3 -.->|low| n0
+";
+        assert_eq!(expected, bdd.to_mermaid_string(&variables, false));
+        let pruned = bdd.to_mermaid_string(&variables, true);
+        assert!(!pruned.contains("n0"));
+        assert!(pruned.contains("n3 -->|high| n2"));
+    }
+
+    #[test]
+    fn bdd_to_graphml() {
+        let variables = BddVariableSet::new(&["a", "b", "c", "d'", "e"]);
+        let bdd = mk_small_test_bdd();
+        let graphml = bdd.to_graphml_string(&variables, true);
+        assert!(graphml.contains("<data key=\"root\">n3</data>"));
+        assert!(graphml.contains(
+            "<node id=\"n2\"><data key=\"label\">d&apos;</data><data key=\"variable\">3</data></node>"
+        ));
+        assert!(graphml.contains("<node id=\"n1\"><data key=\"label\">1</data></node>"));
+        assert!(!graphml.contains("<node id=\"n0\">"));
+        assert!(graphml
+            .contains("<edge source=\"n3\" target=\"n2\"><data key=\"kind\">high</data></edge>"));
+        assert!(!graphml.contains("target=\"n0\""));
+        assert!(graphml.ends_with("</graphml>\n"));
+    }
+
+    #[test]
+    fn bdd_to_json() {
+        let variables = BddVariableSet::new(&["a", "b", "c", "d\"", "e"]);
+        let bdd = mk_small_test_bdd();
+        let expected = "{\"directed\":true,\"root\":3,\"nodes\":[\
+            {\"id\":0,\"label\":\"0\"},\
+            {\"id\":1,\"label\":\"1\"},\
+            {\"id\":2,\"label\":\"d\\\"\",\"variable\":3},\
+            {\"id\":3,\"label\":\"c\",\"variable\":2}],\"links\":[\
+            {\"source\":2,\"target\":0,\"kind\":\"high\"},\
+            {\"source\":2,\"target\":1,\"kind\":\"low\"},\
+            {\"source\":3,\"target\":2,\"kind\":\"high\"},\
+            {\"source\":3,\"target\":0,\"kind\":\"low\"}]}\n";
+        assert_eq!(expected, bdd.to_json_string(&variables, false));
+    }
+
+    #[test]
+    fn constant_to_json() {
+        let variables = BddVariableSet::new_anonymous(2);
+        let expected =
+            "{\"directed\":true,\"root\":0,\"nodes\":[{\"id\":0,\"label\":\"0\"}],\"links\":[]}\n";
+        assert_eq!(
+            expected,
+            variables.mk_false().to_json_string(&variables, false)
+        );
+        // The root is emitted even when `zero` is pruned.
+        assert_eq!(
+            expected,
+            variables.mk_false().to_json_string(&variables, true)
+        );
+        let graphml = variables.mk_false().to_graphml_string(&variables, true);
+        assert!(graphml.contains("<data key=\"root\">n0</data>"));
+        assert!(graphml.contains("<node id=\"n0\"><data key=\"label\">0</data></node>"));
+        let mermaid = variables.mk_false().to_mermaid_string(&variables, true);
+        assert_eq!("graph TD\n    n0[\"0\"]\n", mermaid);
+    }
+}
@@ -0,0 +1,552 @@
+use super::_impl_export_graph::{check_var_names, write_to_string};
+use crate::*;
+use std::io::Write;
+
+/// Export procedures which generate source code that evaluates a `Bdd` without depending
+/// on this crate.
+///
+/// The Rust and C functions store the `Bdd` as a constant table of `(variable, low, high)`
+/// triples (indexed by `BddPointer`) and walk it from the root, so the size of the code is
+/// linear in the size of the `Bdd`. The function takes an array of `bool` values indexed by
+/// variable. The Verilog module is a netlist of multiplexers, one for each decision node.
+///
+/// Variable names only appear in comments, so they do not have to be valid identifiers.
+/// However, the `name` of the generated function (or module) must be a valid identifier
+/// which is not a keyword of the target language.
+impl Bdd {
+    /// Output this `Bdd` as a Rust function `pub fn name(values: &[bool; N]) -> bool`
+    /// into the given `output` writer.
+    pub fn write_as_rust_source(
+        &self,
+        output: &mut dyn Write,
+        variables: &BddVariableSet,
+        name: &str,
+    ) -> Result<(), std::io::Error> {
+        write_bdd_as_rust(output, self, &variables.var_names, name)
+    }
+
+    /// Convert this `Bdd` to the source code of a Rust function.
+    ///
+    /// See also `Bdd::write_as_rust_source`.
+    pub fn to_rust_source(&self, variables: &BddVariableSet, name: &str) -> String {
+        write_to_string(|out| self.write_as_rust_source(out, variables, name))
+    }
+
+    /// Output this `Bdd` as a C function `bool name(const bool *values)` into the given
+    /// `output` writer. The code requires `stdbool.h` (or C23). Since identifiers starting
+    /// with `_` are reserved in C, the `name` cannot start with `_`.
+    pub fn write_as_c_source(
+        &self,
+        output: &mut dyn Write,
+        variables: &BddVariableSet,
+        name: &str,
+    ) -> Result<(), std::io::Error> {
+        write_bdd_as_c(output, self, &variables.var_names, name)
+    }
+
+    /// Convert this `Bdd` to the source code of a C function.
+    ///
+    /// See also `Bdd::write_as_c_source`.
+    pub fn to_c_source(&self, variables: &BddVariableSet, name: &str) -> String {
+        write_to_string(|out| self.write_as_c_source(out, variables, name))
+    }
+
+    /// Output this `Bdd` as a Verilog module `name` with an input vector `x` (one bit for
+    /// each variable) and a single output `y` into the given `output` writer.
+    pub fn write_as_verilog(
+        &self,
+        output: &mut dyn Write,
+        variables: &BddVariableSet,
+        name: &str,
+    ) -> Result<(), std::io::Error> {
+        write_bdd_as_verilog(output, self, &variables.var_names, name)
+    }
+
+    /// Convert this `Bdd` to a Verilog module.
+    ///
+    /// See also `Bdd::write_as_verilog`.
+    pub fn to_verilog(&self, variables: &BddVariableSet, name: &str) -> String {
+        write_to_string(|out| self.write_as_verilog(out, variables, name))
+    }
+}
+
+/// **(internal)** Keywords (including reserved and weak keywords) which cannot be used
+/// as a function name in Rust.
+const RUST_KEYWORDS: &[&str] = &[
+    "_", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+    "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// **(internal)** Keywords of C (up to C23, including the `stdbool.h` macros) which cannot be
+/// used as a function name.
+const C_KEYWORDS: &[&str] = &[
+    "alignas",
+    "alignof",
+    "auto",
+    "bool",
+    "break",
+    "case",
+    "char",
+    "const",
+    "constexpr",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extern",
+    "false",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "nullptr",
+    "register",
+    "restrict",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "static_assert",
+    "struct",
+    "switch",
+    "thread_local",
+    "true",
+    "typedef",
+    "typeof",
+    "typeof_unqual",
+    "union",
+    "unsigned",
+    "void",
+    "volatile",
+    "while",
+];
+
+/// **(internal)** Keywords of Verilog (IEEE 1364-2005) which cannot be used as a module name.
+const VERILOG_KEYWORDS: &[&str] = &[
+    "always",
+    "and",
+    "assign",
+    "automatic",
+    "begin",
+    "buf",
+    "bufif0",
+    "bufif1",
+    "case",
+    "casex",
+    "casez",
+    "cell",
+    "cmos",
+    "config",
+    "deassign",
+    "default",
+    "defparam",
+    "design",
+    "disable",
+    "edge",
+    "else",
+    "end",
+    "endcase",
+    "endconfig",
+    "endfunction",
+    "endgenerate",
+    "endmodule",
+    "endprimitive",
+    "endspecify",
+    "endtable",
+    "endtask",
+    "event",
+    "for",
+    "force",
+    "forever",
+    "fork",
+    "function",
+    "generate",
+    "genvar",
+    "highz0",
+    "highz1",
+    "if",
+    "ifnone",
+    "incdir",
+    "include",
+    "initial",
+    "inout",
+    "input",
+    "instance",
+    "integer",
+    "join",
+    "large",
+    "liblist",
+    "library",
+    "localparam",
+    "macromodule",
+    "medium",
+    "module",
+    "nand",
+    "negedge",
+    "nmos",
+    "nor",
+    "noshowcancelled",
+    "not",
+    "notif0",
+    "notif1",
+    "or",
+    "output",
+    "parameter",
+    "pmos",
+    "posedge",
+    "primitive",
+    "pull0",
+    "pull1",
+    "pulldown",
+    "pullup",
+    "pulsestyle_ondetect",
+    "pulsestyle_onevent",
+    "rcmos",
+    "real",
+    "realtime",
+    "reg",
+    "release",
+    "repeat",
+    "rnmos",
+    "rpmos",
+    "rtran",
+    "rtranif0",
+    "rtranif1",
+    "scalared",
+    "showcancelled",
+    "signed",
+    "small",
+    "specify",
+    "specparam",
+    "strong0",
+    "strong1",
+    "supply0",
+    "supply1",
+    "table",
+    "task",
+    "time",
+    "tran",
+    "tranif0",
+    "tranif1",
+    "tri",
+    "tri0",
+    "tri1",
+    "triand",
+    "trior",
+    "trireg",
+    "unsigned",
+    "use",
+    "uwire",
+    "vectored",
+    "wait",
+    "wand",
+    "weak0",
+    "weak1",
+    "while",
+    "wire",
+    "wor",
+    "xnor",
+    "xor",
+];
+
+/// **(internal)** Panic if the `name` is not a valid identifier in the generated code, i.e.
+/// it has an invalid character or it is one of the `keywords` of the target language.
+fn check_identifier(name: &str, keywords: &[&str]) {
+    let valid = name
+        .chars()
+        .enumerate()
+        .all(|(i, c)| c == '_' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()));
+    if name.is_empty() || !valid {
+        panic!("`{}` is not a valid identifier.", name);
+    }
+    if keywords.contains(&name) {
+        panic!("`{}` is a reserved word.", name);
+    }
+}
+
+/// **(internal)** A variable name which can be safely used inside a comment.
+fn comment_safe(name: &str) -> String {
+    format!("{:?}", name).replace("*/", "* /")
+}
+
+/// **(internal)** The `(variable, low, high)` triples of all nodes (terminals are padded
+/// with zeros, but the first two entries are never accessed).
+fn node_table(bdd: &Bdd) -> impl Iterator<Item = (BddPointer, usize, usize, usize)> + '_ {
+    bdd.pointers().map(move |it| {
+        if it.is_terminal() {
+            (it, 0, 0, 0)
+        } else {
+            (
+                it,
+                bdd.var_of(it).to_index(),
+                bdd.low_link_of(it).to_index(),
+                bdd.high_link_of(it).to_index(),
+            )
+        }
+    })
+}
+
+/// **(internal)** A comment line listing the variable indices and names.
+fn variables_comment(var_names: &[String]) -> String {
+    let names: Vec<String> = var_names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| format!("{} = {}", i, comment_safe(name)))
+        .collect();
+    format!("Variables: {}", names.join(", "))
+}
+
+fn write_bdd_as_rust(
+    output: &mut dyn Write,
+    bdd: &Bdd,
+    var_names: &[String],
+    name: &str,
+) -> Result<(), std::io::Error> {
+    check_var_names(bdd, var_names);
+    check_identifier(name, RUST_KEYWORDS);
+    writeln!(output, "/// {}", variables_comment(var_names))?;
+    writeln!(
+        output,
+        "pub fn {}(values: &[bool; {}]) -> bool {{",
+        name,
+        var_names.len()
+    )?;
+    writeln!(
+        output,
+        "    const NODES: [(usize, usize, usize); {}] = [",
+        bdd.size()
+    )?;
+    for (node, var, low, high) in node_table(bdd) {
+        write!(output, "        ({}, {}, {}),", var, low, high)?;
+        if node.is_terminal() {
+            writeln!(output, " // {}", node)?;
+        } else {
+            writeln!(output, " // {}", comment_safe(&var_names[var]))?;
+        }
+    }
+    writeln!(output, "    ];")?;
+    writeln!(output, "    let mut node = {};", bdd.root_pointer())?;
+    writeln!(output, "    while node > 1 {{")?;
+    writeln!(output, "        let (var, low, high) = NODES[node];")?;
+    writeln!(
+        output,
+        "        node = if values[var] {{ high }} else {{ low }};"
+    )?;
+    writeln!(output, "    }}")?;
+    writeln!(output, "    node == 1")?;
+    writeln!(output, "}}")?;
+    Ok(())
+}
+
+fn write_bdd_as_c(
+    output: &mut dyn Write,
+    bdd: &Bdd,
+    var_names: &[String],
+    name: &str,
+) -> Result<(), std::io::Error> {
+    check_var_names(bdd, var_names);
+    check_identifier(name, C_KEYWORDS);
+    // All file-scope identifiers starting with `_` are reserved in C.
+    if name.starts_with('_') {
+        panic!("`{}` is a reserved identifier.", name);
+    }
+    writeln!(output, "#include <stdbool.h>")?;
+    writeln!(output)?;
+    writeln!(output, "/* {} */", variables_comment(var_names))?;
+    writeln!(output, "bool {}(const bool *values) {{", name)?;
+    writeln!(
+        output,
+        "    static const unsigned int NODES[{}][3] = {{",
+        bdd.size()
+    )?;
+    for (node, var, low, high) in node_table(bdd) {
+        write!(output, "        {{{}, {}, {}}},", var, low, high)?;
+        if node.is_terminal() {
+            writeln!(output, " /* {} */", node)?;
+        } else {
+            writeln!(output, " /* {} */", comment_safe(&var_names[var]))?;
+        }
+    }
+    writeln!(output, "    }};")?;
+    writeln!(output, "    unsigned int node = {};", bdd.root_pointer())?;
+    writeln!(output, "    while (node > 1) {{")?;
+    writeln!(
+        output,
+        "        node = values[NODES[node][0]] ? NODES[node][2] : NODES[node][1];"
+    )?;
+    writeln!(output, "    }}")?;
+    writeln!(output, "    return node == 1;")?;
+    writeln!(output, "}}")?;
+    Ok(())
+}
+
+fn write_bdd_as_verilog(
+    output: &mut dyn Write,
+    bdd: &Bdd,
+    var_names: &[String],
+    name: &str,
+) -> Result<(), std::io::Error> {
+    check_var_names(bdd, var_names);
+    check_identifier(name, VERILOG_KEYWORDS);
+    let signal = |node: BddPointer| -> String {
+        if node.is_zero() {
+            "1'b0".to_string()
+        } else if node.is_one() {
+            "1'b1".to_string()
+        } else {
+            format!("n{}", node)
+        }
+    };
+    writeln!(output, "// {}", variables_comment(var_names))?;
+    if var_names.is_empty() {
+        writeln!(output, "module {}(output wire y);", name)?;
+    } else {
+        writeln!(
+            output,
+            "module {}(input wire [{}:0] x, output wire y);",
+            name,
+            var_names.len() - 1
+        )?;
+    }
+    for node in bdd.pointers().skip(2) {
+        let var = bdd.var_of(node).to_index();
+        writeln!(
+            output,
+            "    wire {} = x[{}] ? {} : {}; // {}",
+            signal(node),
+            var,
+            signal(bdd.high_link_of(node)),
+            signal(bdd.low_link_of(node)),
+            comment_safe(&var_names[var])
+        )?;
+    }
+    writeln!(output, "    assign y = {};", signal(bdd.root_pointer()))?;
+    writeln!(output, "endmodule")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::{load_expected_results, mk_small_test_bdd};
+    use crate::*;
+
+    #[test]
+    fn bdd_to_rust_source() {
+        let variables = BddVariableSet::new(&["a", "b", "c", "d", "e"]);
+        let bdd = mk_small_test_bdd();
+        let expected = "/// Variables: 0 = \"a\", 1 = \"b\", 2 = \"c\", 3 = \"d\", 4 = \"e\"
+pub fn eval_c(values: &[bool; 5]) -> bool {
+    const NODES: [(usize, usize, usize); 4] = [
+        (0, 0, 0), // 0
+        (0, 0, 0), // 1
+        (3, 1, 0), // \"d\"
+        (2, 0, 2), // \"c\"
+    ];
+    let mut node = 3;
+    while node > 1 {
+        let (var, low, high) = NODES[node];
+        node = if values[var] { high } else { low };
+    }
+    node == 1
+}
+";
+        assert_eq!(expected, bdd.to_rust_source(&variables, "eval_c"));
+    }
+
+    #[test]
+    fn bdd_to_c_source() {
+        let variables = BddVariableSet::new(&["a", "b", "c", "d*/", "e"]);
+        let bdd = mk_small_test_bdd();
+        let source = bdd.to_c_source(&variables, "eval_c");
+        assert!(source.starts_with("#include <stdbool.h>\n"));
+        assert!(source.contains("bool eval_c(const bool *values) {\n"));
+        assert!(source.contains("static const unsigned int NODES[4][3] = {\n"));
+        assert!(source.contains("        {3, 1, 0}, /* \"d* /\" */\n"));
+        assert!(source.contains("    unsigned int node = 3;\n"));
+    }
+
+    #[test]
+    fn bdd_to_verilog() {
+        let variables = BddVariableSet::new(&["a", "b", "c", "d", "e"]);
+        let bdd = mk_small_test_bdd();
+        let expected = "// Variables: 0 = \"a\", 1 = \"b\", 2 = \"c\", 3 = \"d\", 4 = \"e\"
+module eval_c(input wire [4:0] x, output wire y);
+    wire n2 = x[3] ? 1'b0 : 1'b1; // \"d\"
+    wire n3 = x[2] ? n2 : 1'b0; // \"c\"
+    assign y = n3;
+endmodule
+";
+        assert_eq!(expected, bdd.to_verilog(&variables, "eval_c"));
+
+        let empty = BddVariableSet::new_anonymous(0);
+        let verilog = empty.mk_true().to_verilog(&empty, "t");
+        assert!(verilog.contains("module t(output wire y);\n    assign y = 1'b1;\n"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_function_name() {
+        let variables = BddVariableSet::new_anonymous(2);
+        variables.mk_true().to_rust_source(&variables, "1st");
+    }
+
+    #[test]
+    fn reserved_function_names() {
+        let variables = BddVariableSet::new_anonymous(2);
+        let bdd = variables.mk_var_by_name("x_1");
+        let accepts = |export: fn(&Bdd, &BddVariableSet, &str) -> String, name: &str| {
+            std::panic::catch_unwind(|| export(&bdd, &variables, name)).is_ok()
+        };
+        let (rust, c, verilog) = (Bdd::to_rust_source, Bdd::to_c_source, Bdd::to_verilog);
+        assert!(!accepts(rust, "fn") && !accepts(rust, "_") && !accepts(rust, "Self"));
+        assert!(accepts(rust, "int") && accepts(rust, "module"));
+        assert!(!accepts(c, "int") && !accepts(c, "bool") && !accepts(c, "__f"));
+        assert!(!accepts(c, "_F") && !accepts(c, "_f") && accepts(c, "fn"));
+        assert!(!accepts(verilog, "module") && !accepts(verilog, "wire"));
+        assert!(accepts(verilog, "fn") && accepts(verilog, "int"));
+    }
+
+    /// **(internal)** The functions generated by `Bdd::to_rust_source` for the formulas
+    /// in `rust_source_matches_eval` (checked in as a fixture so that they are compiled).
+    mod generated {
+        include!("../../res/test_results/bdd_to_rust_source.rs");
+    }
+
+    #[test]
+    fn rust_source_matches_eval() {
+        let variables = BddVariableSet::new_anonymous(5);
+        let formulas = [
+            "true",
+            "false",
+            "x_0 & !x_1 | (x_2 <=> x_4)",
+            "(x_0 ^ x_1 ^ x_2 ^ x_3) => x_4",
+        ];
+        let functions = [
+            generated::eval_0,
+            generated::eval_1,
+            generated::eval_2,
+            generated::eval_3,
+        ];
+        let mut sources = Vec::new();
+        for (i, (formula, function)) in formulas.iter().zip(functions).enumerate() {
+            let bdd = variables.eval_expression_string(formula);
+            sources.push(bdd.to_rust_source(&variables, &format!("eval_{}", i)));
+            for valuation in ValuationsOfClauseIterator::new_unconstrained(5) {
+                let values: [bool; 5] = valuation.clone().vector().try_into().unwrap();
+                assert_eq!(bdd.eval_in(&valuation), function(&values));
+            }
+        }
+        // The fixture must be exactly the code generated by the current implementation.
+        let expected = load_expected_results("bdd_to_rust_source.rs");
+        assert_eq!(expected, sources.join("\n"));
+    }
+}
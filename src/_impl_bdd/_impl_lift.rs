@@ -0,0 +1,348 @@
+use crate::_impl_bdd::_impl_nested_ops::{fix_bdd_alignment, inner_apply};
+use crate::_impl_budget::{unwrap_unlimited, Unlimited};
+use crate::*;
+use fxhash::FxBuildHasher;
+use std::fmt::{Display, Formatter};
+
+/// Checked variants of binary operations, which return an `IncompatibleBdds` error instead
+/// of panicking when the two `Bdd`s do not have the same number of variables.
+impl Bdd {
+    /// Same as `Bdd::and`, but fails if the `Bdd`s are not compatible.
+    pub fn try_and(&self, right: &Bdd) -> Result<Bdd, IncompatibleBdds> {
+        Bdd::try_binary_op(self, right, op_function::and)
+    }
+
+    /// Same as `Bdd::or`, but fails if the `Bdd`s are not compatible.
+    pub fn try_or(&self, right: &Bdd) -> Result<Bdd, IncompatibleBdds> {
+        Bdd::try_binary_op(self, right, op_function::or)
+    }
+
+    /// Same as `Bdd::imp`, but fails if the `Bdd`s are not compatible.
+    pub fn try_imp(&self, right: &Bdd) -> Result<Bdd, IncompatibleBdds> {
+        Bdd::try_binary_op(self, right, op_function::imp)
+    }
+
+    /// Same as `Bdd::iff`, but fails if the `Bdd`s are not compatible.
+    pub fn try_iff(&self, right: &Bdd) -> Result<Bdd, IncompatibleBdds> {
+        Bdd::try_binary_op(self, right, op_function::iff)
+    }
+
+    /// Same as `Bdd::xor`, but fails if the `Bdd`s are not compatible.
+    pub fn try_xor(&self, right: &Bdd) -> Result<Bdd, IncompatibleBdds> {
+        Bdd::try_binary_op(self, right, op_function::xor)
+    }
+
+    /// Same as `Bdd::and_not`, but fails if the `Bdd`s are not compatible.
+    pub fn try_and_not(&self, right: &Bdd) -> Result<Bdd, IncompatibleBdds> {
+        Bdd::try_binary_op(self, right, op_function::and_not)
+    }
+
+    /// Same as `Bdd::binary_op`, but fails if the `Bdd`s are not compatible.
+    pub fn try_binary_op<T>(
+        left: &Bdd,
+        right: &Bdd,
+        op_function: T,
+    ) -> Result<Bdd, IncompatibleBdds>
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        IncompatibleBdds::check(left, right)?;
+        Ok(Bdd::binary_op(left, right, op_function))
+    }
+
+    /// Move this `Bdd` from the `from` variable set into the `to` variable set.
+    ///
+    /// Each variable of `from` is translated using the given `map`. Variables that are not
+    /// in the `map` are matched by name. Only the variables that actually appear in this `Bdd`
+    /// need to be translated. The map does not have to preserve the variable ordering (or even
+    /// be injective): in such case, the result is recomputed node by node, which is more
+    /// expensive than a simple renaming.
+    ///
+    /// Returns an error if this `Bdd` does not belong to `from`, or if some variable cannot be
+    /// translated into a valid variable of `to`.
+    pub fn lift(
+        &self,
+        from: &BddVariableSet,
+        to: &BddVariableSet,
+        map: &HashMap<BddVariable, BddVariable>,
+    ) -> Result<Bdd, LiftError> {
+        if self.num_vars() != from.num_vars() {
+            return Err(LiftError::Incompatible(IncompatibleBdds {
+                left: self.num_vars(),
+                right: from.num_vars(),
+            }));
+        }
+
+        let mut support = self.support_set().into_iter().collect::<Vec<_>>();
+        support.sort();
+        let mut translation = HashMap::new();
+        for var in support.iter() {
+            let new_var = match map.get(var) {
+                Some(new_var) => *new_var,
+                None => {
+                    let name = from.name_of(*var);
+                    let Some(new_var) = to.var_by_name(name.as_str()) else {
+                        return Err(LiftError::UnknownVariable(name));
+                    };
+                    new_var
+                }
+            };
+            if new_var.0 >= to.num_vars() {
+                return Err(LiftError::InvalidVariable(from.name_of(*var), new_var));
+            }
+            translation.insert(*var, new_var);
+        }
+
+        let order_preserving = support
+            .windows(2)
+            .all(|pair| translation[&pair[0]] < translation[&pair[1]]);
+
+        if order_preserving {
+            // The structure of the `Bdd` does not change, we only rename the variables.
+            let mut result = self.clone();
+            for node in result.0.iter_mut() {
+                node.var = if node.var.0 == self.num_vars() {
+                    BddVariable(to.num_vars())
+                } else {
+                    translation[&node.var]
+                };
+            }
+            Ok(result)
+        } else {
+            Ok(self.lift_reordered(to.num_vars(), &translation))
+        }
+    }
+
+    /// **(internal)** Rebuild this `Bdd` with variables translated using `translation`, where
+    /// the translation does not preserve the variable ordering.
+    ///
+    /// All intermediate results are created in one shared `Bdd` (similar to the inner tasks
+    /// of `Bdd::binary_op_nested`), so every source node only adds the nodes that are not
+    /// present yet. Each source node `(x, low, high)` is translated as
+    /// `(x' & high') | (low' & !x')`.
+    fn lift_reordered(
+        &self,
+        num_vars: u16,
+        translation: &HashMap<BddVariable, BddVariable>,
+    ) -> Bdd {
+        let mut result = Bdd::mk_true(num_vars);
+        let mut node_cache: HashMap<BddNode, BddPointer, FxBuildHasher> =
+            HashMap::with_capacity_and_hasher(self.size(), FxBuildHasher::default());
+        node_cache.insert(BddNode::mk_zero(num_vars), BddPointer::zero());
+        node_cache.insert(BddNode::mk_one(num_vars), BddPointer::one());
+        // One task cache per operation; they are shared by all source nodes.
+        let mut and_cache = HashMap::with_hasher(FxBuildHasher::default());
+        let mut and_not_cache = HashMap::with_hasher(FxBuildHasher::default());
+        let mut or_cache = HashMap::with_hasher(FxBuildHasher::default());
+
+        // Children always precede their parents, so every source node can be translated
+        // once both of its children are translated.
+        let mut pointers: Vec<BddPointer> = Vec::with_capacity(self.size());
+        for node in self.pointers() {
+            if node.is_terminal() {
+                pointers.push(node);
+                continue;
+            }
+            let literal = BddNode::mk_node(
+                translation[&self.var_of(node)],
+                BddPointer::zero(),
+                BddPointer::one(),
+            );
+            let literal = *node_cache.entry(literal).or_insert_with(|| {
+                result.push_node(literal);
+                result.root_pointer()
+            });
+            let low = pointers[self.low_link_of(node).to_index()];
+            let high = pointers[self.high_link_of(node).to_index()];
+            let mut inner = |left, right, cache, op: fn(_, _) -> _| {
+                unwrap_unlimited(inner_apply(
+                    &mut result,
+                    left,
+                    right,
+                    &mut node_cache,
+                    cache,
+                    op,
+                    &mut Unlimited,
+                    &mut (),
+                ))
+            };
+            let high = inner(literal, high, &mut and_cache, op_function::and);
+            let low = inner(low, literal, &mut and_not_cache, op_function::and_not);
+            pointers.push(inner(high, low, &mut or_cache, op_function::or));
+        }
+
+        fix_bdd_alignment(&result, *pointers.last().unwrap())
+    }
+}
+
+impl IncompatibleBdds {
+    /// **(internal)** Fail if the two `Bdd`s do not have the same number of variables.
+    pub(crate) fn check(left: &Bdd, right: &Bdd) -> Result<(), IncompatibleBdds> {
+        if left.num_vars() == right.num_vars() {
+            Ok(())
+        } else {
+            Err(IncompatibleBdds {
+                left: left.num_vars(),
+                right: right.num_vars(),
+            })
+        }
+    }
+
+    /// The number of variables of the left operand.
+    pub fn left_num_vars(&self) -> u16 {
+        self.left
+    }
+
+    /// The number of variables of the right operand.
+    pub fn right_num_vars(&self) -> u16 {
+        self.right
+    }
+}
+
+impl Display for IncompatibleBdds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Var count mismatch: BDDs are not compatible. {} != {}",
+            self.left, self.right
+        )
+    }
+}
+
+impl std::error::Error for IncompatibleBdds {}
+
+impl Display for LiftError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LiftError::Incompatible(error) => write!(
+                f,
+                "Bdd is incompatible with the variable set ({} vs. {} variables).",
+                error.left, error.right
+            ),
+            LiftError::UnknownVariable(name) => {
+                write!(f, "Variable `{}` cannot be translated.", name)
+            }
+            LiftError::InvalidVariable(name, var) => write!(
+                f,
+                "Variable `{}` is mapped to an invalid variable {}.",
+                name, var
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LiftError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn checked_operations() {
+        let small = BddVariableSet::new_anonymous(2);
+        let large = BddVariableSet::new_anonymous(3);
+        let a = small.mk_var_by_name("x_0");
+        let b = small.mk_var_by_name("x_1");
+        let c = large.mk_var_by_name("x_0");
+
+        assert_eq!(a.and(&b), a.try_and(&b).unwrap());
+        assert_eq!(a.or(&b), a.try_or(&b).unwrap());
+        assert_eq!(a.imp(&b), a.try_imp(&b).unwrap());
+        assert_eq!(a.iff(&b), a.try_iff(&b).unwrap());
+        assert_eq!(a.xor(&b), a.try_xor(&b).unwrap());
+        assert_eq!(a.and_not(&b), a.try_and_not(&b).unwrap());
+
+        let error = a.try_and(&c).unwrap_err();
+        assert_eq!(2, error.left_num_vars());
+        assert_eq!(3, error.right_num_vars());
+        assert_eq!(
+            "Var count mismatch: BDDs are not compatible. 2 != 3",
+            error.to_string()
+        );
+        assert!(c.try_xor(&a).is_err());
+    }
+
+    #[test]
+    fn lift_preserving_order() {
+        let from = BddVariableSet::new(&["a", "b", "c"]);
+        let to = BddVariableSet::new(&["x", "a", "y", "b'", "c", "z"]);
+        let f = from.eval_expression_string("(a & !b) | c");
+        let mut map = HashMap::new();
+        map.insert(
+            from.var_by_name("b").unwrap(),
+            to.var_by_name("b'").unwrap(),
+        );
+        let lifted = f.lift(&from, &to, &map).unwrap();
+        assert_eq!(to.eval_expression_string("(a & !b') | c"), lifted);
+        assert_eq!(f.size(), lifted.size());
+
+        assert_eq!(to.mk_true(), from.mk_true().lift(&from, &to, &map).unwrap());
+        assert_eq!(
+            to.mk_false(),
+            from.mk_false().lift(&from, &to, &map).unwrap()
+        );
+    }
+
+    #[test]
+    fn lift_reordering() {
+        let from = BddVariableSet::new(&["a", "b", "c"]);
+        let to = BddVariableSet::new(&["c", "b", "a", "d"]);
+        let f = from.eval_expression_string("(a & !b) | (c <=> a)");
+        let lifted = f.lift(&from, &to, &HashMap::new()).unwrap();
+        assert_eq!(to.eval_expression_string("(a & !b) | (c <=> a)"), lifted);
+
+        // A non-injective map is a substitution.
+        let mut map = HashMap::new();
+        map.insert(from.var_by_name("c").unwrap(), to.var_by_name("a").unwrap());
+        let lifted = f.lift(&from, &to, &map).unwrap();
+        assert_eq!(to.eval_expression_string("(a & !b) | (a <=> a)"), lifted);
+    }
+
+    #[test]
+    fn lift_errors() {
+        let from = BddVariableSet::new(&["a", "b"]);
+        let to = BddVariableSet::new(&["a", "c"]);
+        let f = from.eval_expression_string("a & b");
+        let error = f.lift(&from, &to, &HashMap::new()).unwrap_err();
+        assert_eq!(LiftError::UnknownVariable("b".to_string()), error);
+        assert_eq!("Variable `b` cannot be translated.", error.to_string());
+        let g = from.eval_expression_string("!a");
+        assert!(g.lift(&from, &to, &HashMap::new()).is_ok());
+        let error = to
+            .mk_true()
+            .lift(&BddVariableSet::new_anonymous(3), &to, &HashMap::new())
+            .unwrap_err();
+        let LiftError::Incompatible(error) = error else {
+            panic!("Expected incompatible error, got {:?}.", error);
+        };
+        assert_eq!((2, 3), (error.left_num_vars(), error.right_num_vars()));
+
+        let mut map = HashMap::new();
+        map.insert(from.var_by_name("b").unwrap(), BddVariable::from_index(5));
+        assert_eq!(
+            Err(LiftError::InvalidVariable(
+                "b".to_string(),
+                BddVariable::from_index(5)
+            )),
+            f.lift(&from, &to, &map)
+        );
+        map.insert(from.var_by_name("b").unwrap(), to.var_by_name("c").unwrap());
+        assert_eq!(
+            to.eval_expression_string("a & c"),
+            f.lift(&from, &to, &map).unwrap()
+        );
+    }
+
+    #[test]
+    fn lift_reversed_order() {
+        let names: Vec<String> = (0..8).map(|i| format!("v{}", i)).collect();
+        let reversed: Vec<String> = names.iter().rev().cloned().collect();
+        let from = BddVariableSet::new(&names.iter().map(|it| it.as_str()).collect::<Vec<_>>());
+        let to = BddVariableSet::new(&reversed.iter().map(|it| it.as_str()).collect::<Vec<_>>());
+        let formula = "(v0 <=> v4) & (v1 ^ v5) & ((v2 & !v6) | (v3 => v7))";
+        let f = from.eval_expression_string(formula);
+        let lifted = f.lift(&from, &to, &HashMap::new()).unwrap();
+        assert_eq!(to.eval_expression_string(formula), lifted);
+        assert_eq!(f, lifted.lift(&to, &from, &HashMap::new()).unwrap());
+    }
+}
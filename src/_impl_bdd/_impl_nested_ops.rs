@@ -1,5 +1,8 @@
 use crate::_impl_bdd::Task;
-use crate::{Bdd, BddNode, BddPointer, BddVariable};
+use crate::_impl_budget::{unwrap_unlimited, Tracker, Unlimited};
+use crate::{
+    ApplyContext, ApplyObserver, Bdd, BddNode, BddPointer, BddVariable, Budget, BudgetExceeded,
+};
 use fxhash::FxBuildHasher;
 use std::cmp::{max, min};
 use std::collections::{HashMap, HashSet};
@@ -63,7 +66,137 @@ impl Bdd {
         F2: Fn(Option<bool>, Option<bool>) -> Option<bool>,
         Trigger: Fn(BddVariable) -> bool,
     {
-        nested_apply(left, right, trigger, outer_op, inner_op)
+        unwrap_unlimited(nested_apply(
+            left,
+            right,
+            trigger,
+            outer_op,
+            inner_op,
+            &mut Unlimited,
+            &mut (),
+        ))
+    }
+
+    /// Same as `Bdd::binary_op_nested`, but the progress of the operation is reported to the
+    /// given `observer` (see `ApplyObserver` and `ApplyStatistics`).
+    pub fn binary_op_nested_with_observer<F1, F2, Trigger, O>(
+        left: &Bdd,
+        right: &Bdd,
+        trigger: Trigger,
+        outer_op: F1,
+        inner_op: F2,
+        observer: &mut O,
+    ) -> Bdd
+    where
+        F1: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+        F2: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+        Trigger: Fn(BddVariable) -> bool,
+        O: ApplyObserver,
+    {
+        unwrap_unlimited(nested_apply(
+            left,
+            right,
+            trigger,
+            outer_op,
+            inner_op,
+            &mut Unlimited,
+            observer,
+        ))
+    }
+
+    /// Same as `Bdd::binary_op_with_for_all`, but the operation is aborted once the given
+    /// `budget` is exceeded.
+    pub fn binary_op_with_for_all_with_budget<F>(
+        left: &Bdd,
+        right: &Bdd,
+        op: F,
+        variables: &[BddVariable],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded>
+    where
+        F: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        let set: HashSet<BddVariable, FxBuildHasher> =
+            HashSet::from_iter(variables.iter().cloned());
+        let trigger = |var: BddVariable| set.contains(&var);
+
+        nested_apply(
+            left,
+            right,
+            trigger,
+            op,
+            crate::op_function::and,
+            &mut budget.start(),
+            &mut (),
+        )
+    }
+
+    /// Same as `Bdd::binary_op_with_exists`, but the operation is aborted once the given
+    /// `budget` is exceeded.
+    pub fn binary_op_with_exists_with_budget<F>(
+        left: &Bdd,
+        right: &Bdd,
+        op: F,
+        variables: &[BddVariable],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded>
+    where
+        F: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        Bdd::binary_op_with_exists_with_tracker(left, right, op, variables, &mut budget.start())
+    }
+
+    /// **(internal)** Same as `Bdd::binary_op_with_exists`, but the resources used by the
+    /// operation are accounted in the given `tracker`.
+    pub(crate) fn binary_op_with_exists_with_tracker<F, K: Tracker>(
+        left: &Bdd,
+        right: &Bdd,
+        op: F,
+        variables: &[BddVariable],
+        tracker: &mut K,
+    ) -> Result<Bdd, BudgetExceeded>
+    where
+        F: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        let set: HashSet<BddVariable, FxBuildHasher> =
+            HashSet::from_iter(variables.iter().cloned());
+        let trigger = |var: BddVariable| set.contains(&var);
+
+        nested_apply(
+            left,
+            right,
+            trigger,
+            op,
+            crate::op_function::or,
+            tracker,
+            &mut (),
+        )
+    }
+
+    /// Same as `Bdd::binary_op_nested`, but the operation is aborted once the given `budget`
+    /// is exceeded.
+    pub fn binary_op_nested_with_budget<F1, F2, Trigger>(
+        left: &Bdd,
+        right: &Bdd,
+        trigger: Trigger,
+        outer_op: F1,
+        inner_op: F2,
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded>
+    where
+        F1: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+        F2: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+        Trigger: Fn(BddVariable) -> bool,
+    {
+        nested_apply(
+            left,
+            right,
+            trigger,
+            outer_op,
+            inner_op,
+            &mut budget.start(),
+            &mut (),
+        )
     }
 }
 
@@ -73,14 +206,17 @@ impl Bdd {
 /// Note that using this algorithm leaves the BDD in a "misaligned" state where the root pointer
 /// is not necessarily the last node. As such, you have to "re-align" the BDD before returning
 /// it to the user.
-fn inner_apply<F>(
+#[allow(clippy::too_many_arguments)]
+pub(super) fn inner_apply<F, K: Tracker, O: ApplyObserver>(
     bdd: &mut Bdd,
     left: BddPointer,
     right: BddPointer,
     node_cache: &mut HashMap<BddNode, BddPointer, FxBuildHasher>,
     task_cache: &mut HashMap<Task, BddPointer, FxBuildHasher>,
     op: F,
-) -> BddPointer
+    tracker: &mut K,
+    observer: &mut O,
+) -> Result<BddPointer, BudgetExceeded>
 where
     F: Fn(Option<bool>, Option<bool>) -> Option<bool>,
 {
@@ -91,6 +227,7 @@ where
     stack.push(Task { left, right });
 
     while let Some(on_stack) = stack.last() {
+        tracker.step()?;
         if let Some(saved) = task_cache.get(on_stack) {
             output = *saved;
             stack.pop();
@@ -125,15 +262,24 @@ where
             };
 
             // Try to solve the tasks using terminal lookup table or from cache.
-            let new_low = op(l_low.as_bool(), r_low.as_bool())
+            let low_lookup = op(l_low.as_bool(), r_low.as_bool());
+            let high_lookup = op(l_high.as_bool(), r_high.as_bool());
+            let new_low = low_lookup
                 .map(BddPointer::from_bool)
                 .or_else(|| task_cache.get(&comp_low).cloned());
-            let new_high = op(l_high.as_bool(), r_high.as_bool())
+            let new_high = high_lookup
                 .map(BddPointer::from_bool)
                 .or_else(|| task_cache.get(&comp_high).cloned());
+            if low_lookup.is_none() && new_low.is_some() {
+                observer.on_cache_hit();
+            }
+            if high_lookup.is_none() && new_high.is_some() {
+                observer.on_cache_hit();
+            }
 
             // If both values are computed, mark this task as resolved.
             if let (Some(new_low), Some(new_high)) = (new_low, new_high) {
+                observer.on_task(decision_var, stack.len());
                 output = if new_low == new_high {
                     // There is no decision, just skip this node and point to either child.
                     task_cache.insert(*on_stack, new_low);
@@ -148,6 +294,8 @@ where
                     } else {
                         // Node does not exist, it needs to be created.
                         bdd.push_node(node);
+                        tracker.check_nodes(bdd.size())?;
+                        observer.on_node_created(decision_var);
                         let id = bdd.root_pointer();
                         node_cache.insert(node, id);
                         task_cache.insert(*on_stack, id);
@@ -166,7 +314,7 @@ where
         }
     }
 
-    output
+    Ok(output)
 }
 
 /// **(internal)** Takes a misaligned BDD and the desired root pointer and re-creates the BDD in
@@ -174,7 +322,7 @@ where
 ///
 /// The method assumes that the BDD is already "reduced". It only fixes the node post-order
 /// and filters away any nodes that are not reachable from the new root.
-fn fix_bdd_alignment(bdd: &Bdd, root: BddPointer) -> Bdd {
+pub(super) fn fix_bdd_alignment(bdd: &Bdd, root: BddPointer) -> Bdd {
     if root.is_zero() {
         return Bdd::mk_false(bdd.num_vars());
     }
@@ -231,13 +379,16 @@ fn fix_bdd_alignment(bdd: &Bdd, root: BddPointer) -> Bdd {
 }
 
 /// **(internal)** See `Bdd::nested_apply`
-fn nested_apply<F1, F2, Trigger>(
+#[allow(clippy::too_many_arguments)]
+fn nested_apply<F1, F2, Trigger, K: Tracker, O: ApplyObserver>(
     left: &Bdd,
     right: &Bdd,
     trigger: Trigger,
     outer_op: F1,
     inner_op: F2,
-) -> Bdd
+    tracker: &mut K,
+    observer: &mut O,
+) -> Result<Bdd, BudgetExceeded>
 where
     F1: Fn(Option<bool>, Option<bool>) -> Option<bool>,
     F2: Fn(Option<bool>, Option<bool>) -> Option<bool>,
@@ -246,6 +397,40 @@ where
     //  the user some other info about the BDD node. Now we can't because we don't have a notion
     //  of "BDD slice" and BDD pointers are private (and probably should stay that way).
     Trigger: Fn(BddVariable) -> bool,
+{
+    let expected_capacity = max(left.size(), right.size());
+    let mut context = ApplyContext::with_capacity(expected_capacity);
+    context.inner_finished.reserve(expected_capacity);
+    nested_apply_in_context(
+        &mut context,
+        left,
+        right,
+        trigger,
+        outer_op,
+        inner_op,
+        tracker,
+        observer,
+    )
+}
+
+/// **(internal)** The same as `nested_apply`, but the working memory of the algorithm
+/// is taken from the given `context` (and it stays allocated there once the operation
+/// is finished).
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn nested_apply_in_context<F1, F2, Trigger, K: Tracker, O: ApplyObserver>(
+    context: &mut ApplyContext,
+    left: &Bdd,
+    right: &Bdd,
+    trigger: Trigger,
+    outer_op: F1,
+    inner_op: F2,
+    tracker: &mut K,
+    observer: &mut O,
+) -> Result<Bdd, BudgetExceeded>
+where
+    F1: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    F2: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    Trigger: Fn(BddVariable) -> bool,
 {
     let num_vars = left.num_vars();
     if right.num_vars() != num_vars {
@@ -261,30 +446,38 @@ where
     // Every tasks saves its result here. The last task will thus give us the proper "result".
     let mut output: BddPointer = BddPointer::zero();
 
-    // Every node in `result` is inserted into `node_cache` - this ensures we have no duplicates.
-    let mut node_cache: HashMap<BddNode, BddPointer, FxBuildHasher> =
-        HashMap::with_capacity_and_hasher(max(left.size(), right.size()), FxBuildHasher::default());
-    node_cache.insert(BddNode::mk_zero(num_vars), BddPointer::zero());
-    node_cache.insert(BddNode::mk_one(num_vars), BddPointer::one());
-
+    // All working memory is borrowed from the context and cleared first (this keeps
+    // the allocated capacity from previous operations).
+    //
     // Outer cache tracks the task results for `op_outer` operating on the input BDDs.
     // Inner cache tracks the task results for `op_inner` operating on the result BDD.
     // Inner cache is shared across all invocations of the inner tasks because they all implement
     // the same BDD operation.
-    let mut outer_cache: HashMap<Task, BddPointer, FxBuildHasher> =
-        HashMap::with_capacity_and_hasher(max(left.size(), right.size()), FxBuildHasher::default());
-    let mut inner_cache: HashMap<Task, BddPointer, FxBuildHasher> =
-        HashMap::with_capacity_and_hasher(max(left.size(), right.size()), FxBuildHasher::default());
+    let ApplyContext {
+        existing: node_cache,
+        finished: outer_cache,
+        inner_finished: inner_cache,
+        stack: outer_stack,
+        ..
+    } = context;
+    node_cache.clear();
+    outer_cache.clear();
+    inner_cache.clear();
+    outer_stack.clear();
+
+    // Every node in `result` is inserted into `node_cache` - this ensures we have no duplicates.
+    node_cache.insert(BddNode::mk_zero(num_vars), BddPointer::zero());
+    node_cache.insert(BddNode::mk_one(num_vars), BddPointer::one());
 
     // `stack` is used to explore the two BDDs "side by side" in DFS-like manner. Each task
     // on the stack is a pair of nodes that needs to be fully processed before we are finished.
-    let mut outer_stack: Vec<Task> = Vec::with_capacity(max(left.size(), right.size()));
     outer_stack.push(Task {
         left: left.root_pointer(),
         right: right.root_pointer(),
     });
 
     while let Some(on_stack) = outer_stack.last() {
+        tracker.step()?;
         if let Some(saved) = outer_cache.get(on_stack) {
             output = *saved;
             outer_stack.pop();
@@ -319,15 +512,24 @@ where
             };
 
             // Try to solve the tasks using terminal lookup table or from cache.
-            let new_low = outer_op(l_low.as_bool(), r_low.as_bool())
+            let low_lookup = outer_op(l_low.as_bool(), r_low.as_bool());
+            let high_lookup = outer_op(l_high.as_bool(), r_high.as_bool());
+            let new_low = low_lookup
                 .map(BddPointer::from_bool)
                 .or_else(|| outer_cache.get(&comp_low).cloned());
-            let new_high = outer_op(l_high.as_bool(), r_high.as_bool())
+            let new_high = high_lookup
                 .map(BddPointer::from_bool)
                 .or_else(|| outer_cache.get(&comp_high).cloned());
+            if low_lookup.is_none() && new_low.is_some() {
+                observer.on_cache_hit();
+            }
+            if high_lookup.is_none() && new_high.is_some() {
+                observer.on_cache_hit();
+            }
 
             // If both values are computed, mark this task as resolved.
             if let (Some(new_low), Some(new_high)) = (new_low, new_high) {
+                observer.on_task(decision_var, outer_stack.len());
                 output = if new_low == new_high {
                     // There is no decision, just skip this node and point to either child.
                     outer_cache.insert(*on_stack, new_low);
@@ -341,10 +543,12 @@ where
                             &mut result,
                             new_low,
                             new_high,
-                            &mut node_cache,
-                            &mut inner_cache,
+                            node_cache,
+                            inner_cache,
                             &inner_op,
-                        );
+                            tracker,
+                            observer,
+                        )?;
                         outer_cache.insert(*on_stack, inner_result);
                         inner_result
                     } else {
@@ -356,6 +560,8 @@ where
                         } else {
                             // Node does not exist, it needs to be pushed to result.
                             result.push_node(node);
+                            tracker.check_nodes(result.size())?;
+                            observer.on_node_created(decision_var);
                             let id = result.root_pointer();
                             node_cache.insert(node, id);
                             outer_cache.insert(*on_stack, id);
@@ -376,12 +582,13 @@ where
     }
 
     // Finally, clean up the BDD.
-    fix_bdd_alignment(&result, output)
+    Ok(fix_bdd_alignment(&result, output))
 }
 
 #[cfg(test)]
 mod tests {
     use crate::_impl_bdd::_impl_nested_ops::{fix_bdd_alignment, inner_apply};
+    use crate::_impl_budget::Unlimited;
     use crate::{bdd, Bdd, BddNode, BddPointer, BddVariable, BddVariableSet};
     use fxhash::FxBuildHasher;
     use std::collections::HashMap;
@@ -454,7 +661,10 @@ mod tests {
             &mut node_cache,
             &mut task_cache,
             crate::op_function::or,
-        );
+            &mut Unlimited,
+            &mut (),
+        )
+        .unwrap();
         // But we need to re-align the BDD afterwards.
         let test_bdd = fix_bdd_alignment(&test_bdd, new_root);
 
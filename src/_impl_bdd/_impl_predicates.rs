@@ -0,0 +1,130 @@
+use crate::{op_function, Bdd, BddPointer};
+use fxhash::FxBuildHasher;
+use std::collections::HashSet;
+
+/// Set-like predicates on `Bdd`s which are evaluated without building the result of the
+/// underlying logical operation. The exploration stops at the first counterexample.
+impl Bdd {
+    /// True if every valuation of this `Bdd` also satisfies `other`
+    /// (i.e. $\phi \Rightarrow \psi$ is a tautology).
+    pub fn is_subset(&self, other: &Bdd) -> bool {
+        !has_witness([self, other], |[l, r]| op_function::and_not(l, r))
+    }
+
+    /// True if there is no valuation which satisfies both this `Bdd` and `other`.
+    pub fn is_disjoint(&self, other: &Bdd) -> bool {
+        !self.intersects(other)
+    }
+
+    /// True if there is a valuation which satisfies both this `Bdd` and `other`.
+    pub fn intersects(&self, other: &Bdd) -> bool {
+        has_witness([self, other], |[l, r]| op_function::and(l, r))
+    }
+
+    /// True if every valuation of this `Bdd` that is also in `care` satisfies `other`
+    /// (i.e. $\gamma \land \phi \Rightarrow \psi$ is a tautology, where $\gamma$ is `care`).
+    pub fn implies_under(&self, care: &Bdd, other: &Bdd) -> bool {
+        !has_witness([care, self, other], |[c, l, r]| {
+            op_function::and(c, op_function::and_not(l, r))
+        })
+    }
+}
+
+/// **(internal)** Check if the result of `op` applied to the given `Bdd`s is satisfiable,
+/// without constructing the result.
+///
+/// This is a simplified version of the apply algorithm which explores all tuples of nodes
+/// in DFS order and returns once a tuple evaluates to `true`. Since the search stops at the
+/// first satisfying tuple, every visited tuple is known to be unsatisfiable once it is
+/// visited again, so the cache only needs to remember visited tuples.
+fn has_witness<const N: usize, F>(bdds: [&Bdd; N], op: F) -> bool
+where
+    F: Fn([Option<bool>; N]) -> Option<bool>,
+{
+    let num_vars = bdds[0].num_vars();
+    for bdd in &bdds {
+        if bdd.num_vars() != num_vars {
+            panic!(
+                "Var count mismatch: BDDs are not compatible. {} != {}",
+                num_vars,
+                bdd.num_vars()
+            );
+        }
+    }
+
+    let mut visited: HashSet<[BddPointer; N], FxBuildHasher> = HashSet::default();
+    let mut stack: Vec<[BddPointer; N]> = vec![bdds.map(|it| it.root_pointer())];
+    while let Some(task) = stack.pop() {
+        let values = std::array::from_fn(|i| task[i].as_bool());
+        match op(values) {
+            Some(true) => return true,
+            Some(false) => continue,
+            None => {}
+        }
+        if !visited.insert(task) {
+            continue;
+        }
+
+        let decision_var = (0..N).map(|i| bdds[i].var_of(task[i])).min().unwrap();
+        let mut low = task;
+        let mut high = task;
+        for i in 0..N {
+            if bdds[i].var_of(task[i]) == decision_var {
+                low[i] = bdds[i].low_link_of(task[i]);
+                high[i] = bdds[i].high_link_of(task[i]);
+            }
+        }
+        stack.push(low);
+        stack.push(high);
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::BddVariableSet;
+
+    #[test]
+    fn set_predicates() {
+        let vars = BddVariableSet::new_anonymous(5);
+        let formulas = [
+            "true",
+            "false",
+            "x_0 & x_1",
+            "x_0",
+            "x_0 | x_2",
+            "!x_0 & x_3",
+            "(x_1 <=> x_4) & x_2",
+            "x_1 ^ x_3 ^ x_4",
+        ];
+        let bdds: Vec<_> = formulas
+            .iter()
+            .map(|it| vars.eval_expression_string(it))
+            .collect();
+        for a in &bdds {
+            for b in &bdds {
+                assert_eq!(a.and_not(b).is_false(), a.is_subset(b));
+                assert_eq!(a.and(b).is_false(), a.is_disjoint(b));
+                assert_eq!(!a.and(b).is_false(), a.intersects(b));
+                for care in &bdds {
+                    let expected = care.and(a).and_not(b).is_false();
+                    assert_eq!(expected, a.implies_under(care, b));
+                }
+            }
+        }
+        let a = vars.eval_expression_string("x_0 & x_1");
+        let b = vars.eval_expression_string("x_0");
+        assert!(a.is_subset(&b));
+        assert!(!b.is_subset(&a));
+        let care = vars.eval_expression_string("x_1");
+        assert!(b.implies_under(&care, &a));
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_predicates_incompatible() {
+        let a = BddVariableSet::new_anonymous(3).mk_true();
+        let b = BddVariableSet::new_anonymous(4).mk_true();
+        a.is_subset(&b);
+    }
+}
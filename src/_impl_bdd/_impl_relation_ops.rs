@@ -1,4 +1,6 @@
-use crate::{Bdd, BddPartialValuation, BddVariable};
+use crate::_impl_budget::{unwrap_unlimited, Tracker, Unlimited};
+use crate::op_function;
+use crate::{Bdd, BddPartialValuation, BddVariable, Budget, BudgetExceeded};
 use rand::Rng;
 
 /// Advanced relation-like operations for `Bdd`s.
@@ -63,6 +65,27 @@ impl Bdd {
         Bdd::binary_op_with_for_all(self, self, crate::op_function::and, variables)
     }
 
+    /// Same as `Bdd::exists`, but the operation is aborted once the given `budget` is exceeded.
+    pub fn exists_with_budget(
+        &self,
+        variables: &[BddVariable],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        let and = crate::op_function::and;
+        Bdd::binary_op_with_exists_with_budget(self, self, and, variables, budget)
+    }
+
+    /// Same as `Bdd::for_all`, but the operation is aborted once the given `budget` is
+    /// exceeded.
+    pub fn for_all_with_budget(
+        &self,
+        variables: &[BddVariable],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        let and = crate::op_function::and;
+        Bdd::binary_op_with_for_all_with_budget(self, self, and, variables, budget)
+    }
+
     /// Picks one valuation for the given `BddVariable`.
     ///
     /// Essentially, what this means is that
@@ -107,16 +130,63 @@ impl Bdd {
     /// This can be used to implement non-trivial element picking on relations (for example,
     /// for $A \times B$, picking one $b \in B$ for every $a \in A$).
     pub fn pick(&self, variables: &[BddVariable]) -> Bdd {
-        fn r_pick(set: &Bdd, variables: &[BddVariable]) -> Bdd {
+        unwrap_unlimited(self.pick_with_tracker(variables, &mut Unlimited))
+    }
+
+    /// Same as `Bdd::pick`, but the operation is aborted once the given `budget` is exceeded.
+    ///
+    /// The `budget` is shared by all the intermediate operations.
+    pub fn pick_with_budget(
+        &self,
+        variables: &[BddVariable],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        self.pick_with_tracker(variables, &mut budget.start())
+    }
+
+    /// **(internal)** Implementation of `Bdd::pick` which uses the given `tracker`.
+    fn pick_with_tracker<K: Tracker>(
+        &self,
+        variables: &[BddVariable],
+        tracker: &mut K,
+    ) -> Result<Bdd, BudgetExceeded> {
+        fn r_pick<K: Tracker>(
+            set: &Bdd,
+            variables: &[BddVariable],
+            tracker: &mut K,
+        ) -> Result<Bdd, BudgetExceeded> {
             if let Some((last_var, rest)) = variables.split_last() {
-                let picked = r_pick(&set.var_exists(*last_var), rest);
-                picked.and(&set.var_pick(*last_var))
+                let var = Some(*last_var);
+                let exists = Bdd::fused_binary_flip_op_with_tracker(
+                    (set, None),
+                    (set, var),
+                    None,
+                    op_function::or,
+                    tracker,
+                )?;
+                let picked = r_pick(&exists, rest, tracker)?;
+                // Same as `set.var_pick(last_var)`.
+                let selected = set.select_with_tracker(&[(*last_var, false)], tracker)?;
+                let var_picked = Bdd::fused_binary_flip_op_with_tracker(
+                    (set, None),
+                    (&selected, var),
+                    None,
+                    op_function::and_not,
+                    tracker,
+                )?;
+                Bdd::fused_binary_flip_op_with_tracker(
+                    (&picked, None),
+                    (&var_picked, None),
+                    None,
+                    op_function::and,
+                    tracker,
+                )
             } else {
-                set.clone()
+                Ok(set.clone())
             }
         }
 
-        r_pick(self, &sorted(variables))
+        r_pick(self, &sorted(variables), tracker)
     }
 
     /// Same as `bdd.pick`, but the preferred value for each variable is picked randomly using
@@ -149,6 +219,32 @@ impl Bdd {
         self.and(&valuation_bdd)
     }
 
+    /// Same as `Bdd::select`, but the operation is aborted once the given `budget` is exceeded.
+    pub fn select_with_budget(
+        &self,
+        variables: &[(BddVariable, bool)],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        self.select_with_tracker(variables, &mut budget.start())
+    }
+
+    /// **(internal)** Implementation of `Bdd::select` which uses the given `tracker`.
+    fn select_with_tracker<K: Tracker>(
+        &self,
+        variables: &[(BddVariable, bool)],
+        tracker: &mut K,
+    ) -> Result<Bdd, BudgetExceeded> {
+        let valuation = BddPartialValuation::from_values(variables);
+        let valuation_bdd = Bdd::mk_partial_valuation(self.num_vars(), &valuation);
+        Bdd::fused_binary_flip_op_with_tracker(
+            (self, None),
+            (&valuation_bdd, None),
+            None,
+            op_function::and,
+            tracker,
+        )
+    }
+
     /// Fixes a `variable` to the given `value`, and then eliminates said variable using
     /// existential projection.
     ///
@@ -169,6 +265,25 @@ impl Bdd {
         let variables: Vec<BddVariable> = Vec::from_iter(variables.iter().map(|(x, _)| *x));
         Bdd::binary_op_with_exists(self, &valuation_bdd, crate::op_function::and, &variables)
     }
+
+    /// Same as `Bdd::restrict`, but the operation is aborted once the given `budget` is
+    /// exceeded.
+    pub fn restrict_with_budget(
+        &self,
+        variables: &[(BddVariable, bool)],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        let valuation = BddPartialValuation::from_values(variables);
+        let valuation_bdd = Bdd::mk_partial_valuation(self.num_vars(), &valuation);
+        let variables: Vec<BddVariable> = Vec::from_iter(variables.iter().map(|(x, _)| *x));
+        Bdd::binary_op_with_exists_with_budget(
+            self,
+            &valuation_bdd,
+            op_function::and,
+            &variables,
+            budget,
+        )
+    }
 }
 
 /// **(internal)** Helper function for sorting variable list arguments.
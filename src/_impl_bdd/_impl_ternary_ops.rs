@@ -1,7 +1,9 @@
-use crate::{Bdd, BddNode, BddPointer, BddVariable};
-use fxhash::FxBuildHasher;
+use crate::_impl_bdd::TernaryTask;
+use crate::_impl_budget::{unwrap_unlimited, Tracker, Unlimited};
+use crate::{
+    ApplyContext, ApplyObserver, Bdd, BddNode, BddPointer, BddVariable, Budget, BudgetExceeded,
+};
 use std::cmp::{max, min};
-use std::collections::HashMap;
 
 impl Bdd {
     /// A ternary logical operation on three `Bdd` objects. Works the same as `Bdd::binary_op`,
@@ -14,7 +16,62 @@ impl Bdd {
     where
         T: Fn(Option<bool>, Option<bool>, Option<bool>) -> Option<bool>,
     {
-        ternary_apply((a, b, c), (None, None, None), None, op_function)
+        let result = ternary_apply(
+            (a, b, c),
+            (None, None, None),
+            None,
+            op_function,
+            &mut Unlimited,
+            &mut (),
+        );
+        unwrap_unlimited(result)
+    }
+
+    /// Same as `Bdd::ternary_op`, but the operation is aborted once the given `budget`
+    /// is exceeded.
+    pub fn ternary_op_with_budget<T>(
+        a: &Bdd,
+        b: &Bdd,
+        c: &Bdd,
+        op_function: T,
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded>
+    where
+        T: Fn(Option<bool>, Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        ternary_apply(
+            (a, b, c),
+            (None, None, None),
+            None,
+            op_function,
+            &mut budget.start(),
+            &mut (),
+        )
+    }
+
+    /// Same as `Bdd::ternary_op`, but the progress of the operation is reported to the given
+    /// `observer` (see `ApplyObserver` and `ApplyStatistics`).
+    pub fn ternary_op_with_observer<T, O>(
+        a: &Bdd,
+        b: &Bdd,
+        c: &Bdd,
+        op_function: T,
+        observer: &mut O,
+    ) -> Bdd
+    where
+        T: Fn(Option<bool>, Option<bool>, Option<bool>) -> Option<bool>,
+        O: ApplyObserver,
+    {
+        let flips = (None, None, None);
+        let result = ternary_apply(
+            (a, b, c),
+            flips,
+            None,
+            op_function,
+            &mut Unlimited,
+            observer,
+        );
+        unwrap_unlimited(result)
     }
 
     /// A ternary version of `Bdd::fused_binary_flip_op` that makes it possible to flip
@@ -33,11 +90,80 @@ impl Bdd {
     where
         T: Fn(Option<bool>, Option<bool>, Option<bool>) -> Option<bool>,
     {
-        ternary_apply((a.0, b.0, c.0), (a.1, b.1, c.1), flip_output, op_function)
+        let flips = (a.1, b.1, c.1);
+        let result = ternary_apply(
+            (a.0, b.0, c.0),
+            flips,
+            flip_output,
+            op_function,
+            &mut Unlimited,
+            &mut (),
+        );
+        unwrap_unlimited(result)
+    }
+
+    /// Same as `Bdd::fused_ternary_flip_op`, but the operation is aborted once the given
+    /// `budget` is exceeded.
+    pub fn fused_ternary_flip_op_with_budget<T>(
+        a: (&Bdd, Option<BddVariable>),
+        b: (&Bdd, Option<BddVariable>),
+        c: (&Bdd, Option<BddVariable>),
+        flip_output: Option<BddVariable>,
+        op_function: T,
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded>
+    where
+        T: Fn(Option<bool>, Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        let flips = (a.1, b.1, c.1);
+        ternary_apply(
+            (a.0, b.0, c.0),
+            flips,
+            flip_output,
+            op_function,
+            &mut budget.start(),
+            &mut (),
+        )
     }
 }
 
-fn ternary_apply<T>(
+fn ternary_apply<T, K: Tracker, O: ApplyObserver>(
+    args: (&Bdd, &Bdd, &Bdd),
+    flips: (
+        Option<BddVariable>,
+        Option<BddVariable>,
+        Option<BddVariable>,
+    ),
+    flip_out_if: Option<BddVariable>,
+    terminal_lookup: T,
+    tracker: &mut K,
+    observer: &mut O,
+) -> Result<Bdd, BudgetExceeded>
+where
+    T: Fn(Option<bool>, Option<bool>, Option<bool>) -> Option<bool>,
+{
+    let (a, b, c) = args;
+    let expected_capacity = max(a.size(), max(b.size(), c.size()));
+    let mut context = ApplyContext::new();
+    context.existing.reserve(expected_capacity);
+    context.ternary_finished.reserve(expected_capacity);
+    context.ternary_stack.reserve(2 * usize::from(a.num_vars()));
+    ternary_apply_in_context(
+        &mut context,
+        args,
+        flips,
+        flip_out_if,
+        terminal_lookup,
+        tracker,
+        observer,
+    )
+}
+
+/// **(internal)** The same as `ternary_apply`, but the working memory of the algorithm
+/// is taken from the given `context` (and it stays allocated there once the operation
+/// is finished).
+pub(crate) fn ternary_apply_in_context<T, K: Tracker, O: ApplyObserver>(
+    context: &mut ApplyContext,
     args: (&Bdd, &Bdd, &Bdd),
     flips: (
         Option<BddVariable>,
@@ -46,7 +172,9 @@ fn ternary_apply<T>(
     ),
     flip_out_if: Option<BddVariable>,
     terminal_lookup: T,
-) -> Bdd
+    tracker: &mut K,
+    observer: &mut O,
+) -> Result<Bdd, BudgetExceeded>
 where
     T: Fn(Option<bool>, Option<bool>, Option<bool>) -> Option<bool>,
 {
@@ -73,25 +201,26 @@ where
     let mut result: Bdd = Bdd::mk_true(num_vars);
     let mut is_not_empty = false;
 
+    // All working memory is borrowed from the context and cleared first (this keeps
+    // the allocated capacity from previous operations).
+    let ApplyContext {
+        existing,
+        ternary_stack: stack,
+        ternary_finished: finished,
+        ..
+    } = context;
+    existing.clear();
+    stack.clear();
+    finished.clear();
+
     // Every node in `result` is inserted into `existing` - this ensures we have no duplicates.
-    let expected_capacity = max(a.size(), max(b.size(), c.size()));
-    let mut existing: HashMap<BddNode, BddPointer, FxBuildHasher> =
-        HashMap::with_capacity_and_hasher(expected_capacity, FxBuildHasher::default());
     existing.insert(BddNode::mk_zero(num_vars), BddPointer::zero());
     existing.insert(BddNode::mk_one(num_vars), BddPointer::one());
 
-    // Task is a triple of pointers into the `a`, `b`, and `c` BDDs.
-    #[derive(Eq, PartialEq, Hash, Copy, Clone)]
-    struct Task {
-        a: BddPointer,
-        b: BddPointer,
-        c: BddPointer,
-    }
-
     // `stack` is used to explore the BDDs "side by side" in DFS-like manner. Each task
-    // on the stack is a triple of nodes that needs to be fully processed before we are finished.
-    let mut stack: Vec<Task> = Vec::with_capacity(2 * usize::from(num_vars));
-    stack.push(Task {
+    // on the stack is a triple of pointers into the `a`, `b`, and `c` BDDs that needs to be
+    // fully processed before we are finished.
+    stack.push(TernaryTask {
         a: a.root_pointer(),
         b: b.root_pointer(),
         c: c.root_pointer(),
@@ -99,10 +228,9 @@ where
 
     // `finished` is a memoization cache of tasks that are already completed, since the same
     // combination of nodes can be often explored multiple times.
-    let mut finished: HashMap<Task, BddPointer, FxBuildHasher> =
-        HashMap::with_capacity_and_hasher(expected_capacity, FxBuildHasher::default());
 
     while let Some(on_stack) = stack.last() {
+        tracker.step()?;
         if finished.contains_key(on_stack) {
             // skip finished tasks
             stack.pop();
@@ -140,27 +268,36 @@ where
             };
 
             // Two tasks which correspond to the two recursive sub-problems we need to solve.
-            let comp_low = Task {
+            let comp_low = TernaryTask {
                 a: low_a,
                 b: low_b,
                 c: low_c,
             };
-            let comp_high = Task {
+            let comp_high = TernaryTask {
                 a: high_a,
                 b: high_b,
                 c: high_c,
             };
 
             // Try to solve the tasks using terminal lookup table or from cache.
-            let new_low = terminal_lookup(low_a.as_bool(), low_b.as_bool(), low_c.as_bool())
+            let low_lookup = terminal_lookup(low_a.as_bool(), low_b.as_bool(), low_c.as_bool());
+            let high_lookup = terminal_lookup(high_a.as_bool(), high_b.as_bool(), high_c.as_bool());
+            let new_low = low_lookup
                 .map(BddPointer::from_bool)
                 .or_else(|| finished.get(&comp_low).cloned());
-            let new_high = terminal_lookup(high_a.as_bool(), high_b.as_bool(), high_c.as_bool())
+            let new_high = high_lookup
                 .map(BddPointer::from_bool)
                 .or_else(|| finished.get(&comp_high).cloned());
+            if low_lookup.is_none() && new_low.is_some() {
+                observer.on_cache_hit();
+            }
+            if high_lookup.is_none() && new_high.is_some() {
+                observer.on_cache_hit();
+            }
 
             // If both values are computed, mark this task as resolved.
             if let (Some(new_low), Some(new_high)) = (new_low, new_high) {
+                observer.on_task(decision_var, stack.len());
                 if new_low.is_one() || new_high.is_one() {
                     is_not_empty = true
                 }
@@ -181,6 +318,8 @@ where
                     } else {
                         // Node does not exist, it needs to be pushed to result.
                         result.push_node(node);
+                        tracker.check_nodes(result.size())?;
+                        observer.on_node_created(decision_var);
                         existing.insert(node, result.root_pointer());
                         finished.insert(*on_stack, result.root_pointer());
                     }
@@ -208,11 +347,11 @@ where
         }
     }
 
-    if is_not_empty {
+    Ok(if is_not_empty {
         result
     } else {
         Bdd::mk_false(num_vars)
-    }
+    })
 }
 
 /// **(internal)** A simple utility method for checking bounds of a flip variable.
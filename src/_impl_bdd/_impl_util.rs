@@ -99,91 +99,13 @@ impl Bdd {
     }
 
     pub fn exact_cardinality(&self) -> BigInt {
-        let zero = BigInt::from(0);
-        let one = BigInt::from(1);
-        if self.is_false() {
-            return zero;
-        }
-        let mut cache = vec![None; self.0.len()];
-        cache[0] = Some(zero);
-        cache[1] = Some(one.clone());
-        let mut stack: Vec<BddPointer> = vec![self.root_pointer()];
-        while let Some(node) = stack.last() {
-            if cache[node.0 as usize].is_some() {
-                stack.pop();
-            } else {
-                let low = self.low_link_of(*node);
-                let high = self.high_link_of(*node);
-                let low_var = self.var_of(low).0;
-                let high_var = self.var_of(high).0;
-                let node_var = self.var_of(*node).0;
-                let low = low.0 as usize;
-                let high = high.0 as usize;
-
-                if let (Some(cache_low), Some(cache_high)) = (&cache[low], &cache[high]) {
-                    let low_card = cache_low * (one.clone() << (low_var - node_var - 1));
-                    let high_card = cache_high * (one.clone() << (high_var - node_var - 1));
-                    cache[node.0 as usize] = Some(low_card + high_card);
-                    stack.pop();
-                } else {
-                    if cache[low].is_none() {
-                        stack.push(BddPointer(low as u32));
-                    }
-                    if cache[high].is_none() {
-                        stack.push(BddPointer(high as u32));
-                    }
-                }
-            }
-        }
-        let last_var = self.0.last().unwrap().var.0;
-        cache.last().cloned().flatten().unwrap() * (one << last_var)
+        exact_cardinality(self)
     }
 
     /// Approximately computes the number of valuations satisfying the formula given
     /// by this `Bdd`.
     pub fn cardinality(&self) -> f64 {
-        if self.is_false() {
-            return 0.0;
-        }
-        let mut cache = vec![None; self.0.len()];
-        cache[0] = Some(0.0);
-        cache[1] = Some(1.0);
-        let mut stack: Vec<BddPointer> = vec![self.root_pointer()];
-        while let Some(node) = stack.last() {
-            if cache[node.0 as usize].is_some() {
-                stack.pop();
-            } else {
-                let low = self.low_link_of(*node);
-                let high = self.high_link_of(*node);
-                let low_var = self.var_of(low).0;
-                let high_var = self.var_of(high).0;
-                let node_var = self.var_of(*node).0;
-                let low = low.0 as usize;
-                let high = high.0 as usize;
-
-                if cache[low].is_some() && cache[high].is_some() {
-                    let low_cardinality =
-                        cache[low].unwrap() * 2.0_f64.powi((low_var - node_var - 1) as i32);
-                    let high_cardinality =
-                        cache[high].unwrap() * 2.0_f64.powi((high_var - node_var - 1) as i32);
-                    cache[node.0 as usize] = Some(low_cardinality + high_cardinality);
-                    stack.pop();
-                } else {
-                    if cache[low].is_none() {
-                        stack.push(BddPointer(low as u32));
-                    }
-                    if cache[high].is_none() {
-                        stack.push(BddPointer(high as u32));
-                    }
-                }
-            }
-        }
-        let r = cache.last().unwrap().unwrap() * 2.0_f64.powi(self.0.last().unwrap().var.0 as i32);
-        if r.is_nan() {
-            f64::INFINITY
-        } else {
-            r
-        }
+        cardinality(self)
     }
 
     /// If the `Bdd` is satisfiable, return some `BddValuation` that satisfies the `Bdd`.
@@ -372,7 +294,7 @@ impl Bdd {
     }
 
     /// **(internal)** Create an iterator over all nodes of the `Bdd` (including terminals).
-    pub(crate) fn nodes(&self) -> Iter<BddNode> {
+    pub(crate) fn nodes(&self) -> Iter<'_, BddNode> {
         self.0.iter()
     }
 
@@ -457,13 +379,7 @@ impl Bdd {
 
     /// Return the set of all variables that actually appear as decision variables in this BDD.
     pub fn support_set(&self) -> HashSet<BddVariable> {
-        let mut result = HashSet::new();
-
-        for node in self.nodes().skip(2) {
-            result.insert(node.var);
-        }
-
-        result
+        support_set(self)
     }
 
     /// Return the BDD which represents a function where variable `var` was substituted for
@@ -480,6 +396,107 @@ impl Bdd {
     }
 }
 
+/// **(internal)** Shared implementation of `Bdd::exact_cardinality` and
+/// `BddRef::exact_cardinality`.
+pub(crate) fn exact_cardinality<B: BddNodes>(bdd: &B) -> BigInt {
+    let zero = BigInt::from(0);
+    let one = BigInt::from(1);
+    if bdd.size() == 1 {
+        return zero;
+    }
+    let mut cache = vec![None; bdd.size()];
+    cache[0] = Some(zero);
+    cache[1] = Some(one.clone());
+    let mut stack: Vec<BddPointer> = vec![bdd.root_pointer()];
+    while let Some(node) = stack.last() {
+        if cache[node.0 as usize].is_some() {
+            stack.pop();
+        } else {
+            let low = bdd.low_link_of(*node);
+            let high = bdd.high_link_of(*node);
+            let low_var = bdd.var_of(low).0;
+            let high_var = bdd.var_of(high).0;
+            let node_var = bdd.var_of(*node).0;
+            let low = low.0 as usize;
+            let high = high.0 as usize;
+
+            if let (Some(cache_low), Some(cache_high)) = (&cache[low], &cache[high]) {
+                let low_card = cache_low * (one.clone() << (low_var - node_var - 1));
+                let high_card = cache_high * (one.clone() << (high_var - node_var - 1));
+                cache[node.0 as usize] = Some(low_card + high_card);
+                stack.pop();
+            } else {
+                if cache[low].is_none() {
+                    stack.push(BddPointer(low as u32));
+                }
+                if cache[high].is_none() {
+                    stack.push(BddPointer(high as u32));
+                }
+            }
+        }
+    }
+    let last_var = bdd.var_of(bdd.root_pointer()).0;
+    cache.last().cloned().flatten().unwrap() * (one << last_var)
+}
+
+/// **(internal)** Shared implementation of `Bdd::cardinality` and `BddRef::cardinality`.
+pub(crate) fn cardinality<B: BddNodes>(bdd: &B) -> f64 {
+    if bdd.size() == 1 {
+        return 0.0;
+    }
+    let mut cache = vec![None; bdd.size()];
+    cache[0] = Some(0.0);
+    cache[1] = Some(1.0);
+    let mut stack: Vec<BddPointer> = vec![bdd.root_pointer()];
+    while let Some(node) = stack.last() {
+        if cache[node.0 as usize].is_some() {
+            stack.pop();
+        } else {
+            let low = bdd.low_link_of(*node);
+            let high = bdd.high_link_of(*node);
+            let low_var = bdd.var_of(low).0;
+            let high_var = bdd.var_of(high).0;
+            let node_var = bdd.var_of(*node).0;
+            let low = low.0 as usize;
+            let high = high.0 as usize;
+
+            if cache[low].is_some() && cache[high].is_some() {
+                let low_cardinality =
+                    cache[low].unwrap() * 2.0_f64.powi((low_var - node_var - 1) as i32);
+                let high_cardinality =
+                    cache[high].unwrap() * 2.0_f64.powi((high_var - node_var - 1) as i32);
+                cache[node.0 as usize] = Some(low_cardinality + high_cardinality);
+                stack.pop();
+            } else {
+                if cache[low].is_none() {
+                    stack.push(BddPointer(low as u32));
+                }
+                if cache[high].is_none() {
+                    stack.push(BddPointer(high as u32));
+                }
+            }
+        }
+    }
+    let root_var = bdd.var_of(bdd.root_pointer()).0;
+    let r = cache.last().unwrap().unwrap() * 2.0_f64.powi(root_var as i32);
+    if r.is_nan() {
+        f64::INFINITY
+    } else {
+        r
+    }
+}
+
+/// **(internal)** Shared implementation of `Bdd::support_set` and `BddRef::support_set`.
+pub(crate) fn support_set<B: BddNodes>(bdd: &B) -> HashSet<BddVariable> {
+    let mut result = HashSet::new();
+
+    for node in (2..bdd.size()).map(BddPointer::from_index) {
+        result.insert(bdd.var_of(node));
+    }
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use crate::_test_util::mk_small_test_bdd;
@@ -541,7 +558,7 @@ mod tests {
     fn bdd_sat_witness_basic() {
         // v3 & !v4
         let bdd = mk_small_test_bdd();
-        let expected = BddValuation(vec![false, false, true, false, false]);
+        let expected = BddValuation::new(vec![false, false, true, false, false]);
         assert_eq!(bdd.sat_witness().unwrap(), expected);
         assert!(bdd.eval_in(&bdd.sat_witness().unwrap()));
     }
@@ -550,7 +567,7 @@ mod tests {
     fn bdd_sat_witness_advanced() {
         let vars = BddVariableSet::new_anonymous(5);
         let bdd = vars.eval_expression_string("x_0 & (x_1 | x_2) & (x_0 => x_4)");
-        let valuation = BddValuation(vec![true, false, true, false, true]);
+        let valuation = BddValuation::new(vec![true, false, true, false, true]);
         assert_eq!(bdd.sat_witness().unwrap(), valuation);
         assert!(bdd.eval_in(&bdd.sat_witness().unwrap()));
     }
@@ -624,7 +641,7 @@ mod tests {
             bdd.rename_variable(BddVariable(2), BddVariable(0));
             bdd.rename_variable(BddVariable(3), BddVariable(4));
         }
-        let expected = HashSet::from_iter(vec![BddVariable(0), BddVariable(4)].into_iter());
+        let expected = HashSet::from_iter(vec![BddVariable(0), BddVariable(4)]);
         assert_eq!(expected, bdd.support_set());
     }
 
@@ -496,8 +496,8 @@ mod tests {
         let c3 = BddPartialValuation::from_values(&[(v[2], false), (v[4], true)]);
         let bdd = vars.mk_dnf(&[c1.clone(), c2.clone(), c3.clone()]);
 
-        let first_valuation = BddValuation(vec![false, false, false, false, true]);
-        let last_valuation = BddValuation(vec![true, true, true, false, true]);
+        let first_valuation = BddValuation::new(vec![false, false, false, false, true]);
+        let last_valuation = BddValuation::new(vec![true, true, true, false, true]);
 
         assert_eq!(Some(first_valuation), bdd.first_valuation());
         assert_eq!(None, vars.mk_false().first_valuation());
@@ -545,8 +545,8 @@ mod tests {
         let c3 = BddPartialValuation::from_values(&[(v[2], false), (v[4], true)]);
         let bdd = vars.mk_dnf(&[c1.clone(), c2.clone(), c3.clone()]);
 
-        let most_positive_valuation = BddValuation(vec![true, false, true, true, true]);
-        let most_negative_valuation = BddValuation(vec![false, false, false, false, true]);
+        let most_positive_valuation = BddValuation::new(vec![true, false, true, true, true]);
+        let most_negative_valuation = BddValuation::new(vec![false, false, false, false, true]);
 
         assert_eq!(Some(most_positive_valuation), bdd.most_positive_valuation());
         assert_eq!(None, vars.mk_false().most_positive_valuation());
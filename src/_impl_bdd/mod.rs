@@ -19,9 +19,22 @@ pub mod _impl_nested_ops;
 /// (quantification, selection, projection, partial element picking)
 pub mod _impl_relation_ops;
 
+/// **(internal)** Lifting of `Bdd`s into other variable sets and the checked (`try_*`)
+/// variants of binary operations.
+pub mod _impl_lift;
+
+/// **(internal)** Set-like predicates (subset, disjointness) which do not build the result.
+pub mod _impl_predicates;
+
 /// **(internal)** Simple export functions for printing `Bdd`s as `.dot` files.
 pub mod _impl_export_dot;
 
+/// **(internal)** Export functions for printing `Bdd`s as Mermaid, GraphML or JSON graphs.
+pub mod _impl_export_graph;
+
+/// **(internal)** Export of `Bdd`s as standalone Rust, C or Verilog source code.
+pub mod _impl_export_source;
+
 /// **(internal)** Implementation of the string and byte serialisation procedures for `Bdd`s.
 pub mod _impl_serialisation;
 
@@ -44,7 +57,16 @@ pub mod _impl_cnf;
 ///
 /// This is not public because it is just a utility structure for the apply algorithms.
 #[derive(Eq, PartialEq, Hash, Copy, Clone)]
-struct Task {
+pub(crate) struct Task {
     left: BddPointer,
     right: BddPointer,
 }
+
+/// **(internal)** Same as `Task`, but with three BDD pointers (used by the ternary apply
+/// algorithm).
+#[derive(Eq, PartialEq, Hash, Copy, Clone)]
+pub(crate) struct TernaryTask {
+    a: BddPointer,
+    b: BddPointer,
+    c: BddPointer,
+}
@@ -0,0 +1,215 @@
+use crate::{Bdd, BddDisjointCubes, BddMaximalCubes, BddPartialValuation, BddPointer};
+use std::collections::HashSet;
+
+impl Bdd {
+    /// Create an iterator over a set of disjoint conjunctive clauses (cubes) whose disjunction
+    /// is equivalent to this `Bdd`.
+    ///
+    /// Compared to `Bdd::sat_clauses`, the cubes of the two children of each node are
+    /// merged whenever they are the same, such that the decision variable does not appear
+    /// in the merged cube. Note that the cover is computed eagerly once the iterator
+    /// is created.
+    pub fn sat_disjoint_cubes(&self) -> BddDisjointCubes {
+        BddDisjointCubes {
+            cubes: disjoint_cover(self).into_iter(),
+        }
+    }
+
+    /// Create an iterator over maximal cubes of this `Bdd`. Each cube is a conjunctive clause
+    /// which implies this `Bdd`, but no literal can be removed from it without breaking
+    /// this property (i.e. it is a prime implicant).
+    ///
+    /// The cubes are obtained by extending the individual paths of the `Bdd`, so every
+    /// valuation is covered by at least one cube. However, the cubes are typically not
+    /// disjoint, and the result does not necessarily contain all prime implicants.
+    pub fn sat_maximal_cubes(&self) -> BddMaximalCubes<'_> {
+        BddMaximalCubes {
+            bdd: self,
+            paths: self.sat_clauses(),
+            seen: HashSet::new(),
+        }
+    }
+}
+
+impl Iterator for BddDisjointCubes {
+    type Item = BddPartialValuation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cubes.next()
+    }
+}
+
+impl Iterator for BddMaximalCubes<'_> {
+    type Item = BddPartialValuation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for mut cube in &mut self.paths {
+            for (var, value) in cube.to_values() {
+                cube.unset_value(var);
+                if !implies(self.bdd, &cube) {
+                    cube.set_value(var, value);
+                }
+            }
+            if self.seen.insert(cube.clone()) {
+                return Some(cube);
+            }
+        }
+        None
+    }
+}
+
+/// **(internal)** Compute a disjoint cover of the given `bdd`, merging the same cubes
+/// of the two children of each node.
+///
+/// The cover of each node is only kept until all its parents are processed.
+fn disjoint_cover(bdd: &Bdd) -> Vec<BddPartialValuation> {
+    if bdd.is_false() {
+        return Vec::new();
+    }
+    // The number of parents of each node which are not processed yet.
+    let mut waiting_parents: Vec<usize> = vec![0; bdd.size()];
+    for node in bdd.pointers().skip(2) {
+        waiting_parents[bdd.low_link_of(node).to_index()] += 1;
+        waiting_parents[bdd.high_link_of(node).to_index()] += 1;
+    }
+    let mut done: Vec<bool> = vec![false; bdd.size()];
+    let mut covers: Vec<Option<Vec<BddPartialValuation>>> = vec![None; bdd.size()];
+    covers[0] = Some(Vec::new());
+    covers[1] = Some(vec![BddPartialValuation::empty()]);
+    done[0] = true;
+    done[1] = true;
+    let mut stack: Vec<BddPointer> = vec![bdd.root_pointer()];
+    while let Some(node) = stack.last() {
+        if done[node.to_index()] {
+            stack.pop();
+            continue;
+        }
+        let low = bdd.low_link_of(*node);
+        let high = bdd.high_link_of(*node);
+        if let (Some(low_cover), Some(high_cover)) =
+            (&covers[low.to_index()], &covers[high.to_index()])
+        {
+            let var = bdd.var_of(*node);
+            let low_set: HashSet<&BddPartialValuation> = low_cover.iter().collect();
+            let high_set: HashSet<&BddPartialValuation> = high_cover.iter().collect();
+            let mut cover = Vec::with_capacity(low_cover.len() + high_cover.len());
+            for cube in low_cover {
+                let mut cube = cube.clone();
+                if !high_set.contains(&cube) {
+                    cube.set_value(var, false);
+                }
+                cover.push(cube);
+            }
+            for cube in high_cover {
+                if !low_set.contains(cube) {
+                    let mut cube = cube.clone();
+                    cube.set_value(var, true);
+                    cover.push(cube);
+                }
+            }
+            covers[node.to_index()] = Some(cover);
+            done[node.to_index()] = true;
+            stack.pop();
+            // Once all parents of a child are processed, its cover is no longer needed.
+            for child in [low, high] {
+                waiting_parents[child.to_index()] -= 1;
+                if waiting_parents[child.to_index()] == 0 && !child.is_terminal() {
+                    covers[child.to_index()] = None;
+                }
+            }
+        } else {
+            if !done[low.to_index()] {
+                stack.push(low);
+            }
+            if !done[high.to_index()] {
+                stack.push(high);
+            }
+        }
+    }
+    covers[bdd.root_pointer().to_index()].take().unwrap()
+}
+
+/// **(internal)** Check that every valuation of the conjunctive `cube` satisfies the `bdd`,
+/// i.e. no zero terminal is reachable using paths that are compatible with the `cube`.
+fn implies(bdd: &Bdd, cube: &BddPartialValuation) -> bool {
+    let mut visited = vec![false; bdd.size()];
+    let mut stack = vec![bdd.root_pointer()];
+    while let Some(node) = stack.pop() {
+        if node.is_zero() {
+            return false;
+        }
+        if node.is_one() || visited[node.to_index()] {
+            continue;
+        }
+        visited[node.to_index()] = true;
+        match cube.get_value(bdd.var_of(node)) {
+            Some(true) => stack.push(bdd.high_link_of(node)),
+            Some(false) => stack.push(bdd.low_link_of(node)),
+            None => {
+                stack.push(bdd.low_link_of(node));
+                stack.push(bdd.high_link_of(node));
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Bdd, BddPartialValuation, BddVariableSet};
+
+    fn mk_cube(bdd: &Bdd, cube: &BddPartialValuation) -> Bdd {
+        Bdd::mk_partial_valuation(bdd.num_vars(), cube)
+    }
+
+    #[test]
+    fn disjoint_and_maximal_cubes() {
+        let vars = BddVariableSet::new_anonymous(5);
+        let formulas = [
+            "true",
+            "false",
+            "x_2",
+            "x_0 | x_1 | x_2",
+            "(x_0 <=> x_3) & (x_1 ^ x_4)",
+            "(x_0 & x_1) | (x_1 & x_2) | (x_3 & !x_4)",
+            "x_0 ^ x_1 ^ x_2 ^ x_3 ^ x_4",
+        ];
+        for formula in formulas {
+            let bdd = vars.eval_expression_string(formula);
+
+            let cubes: Vec<BddPartialValuation> = bdd.sat_disjoint_cubes().collect();
+            assert!(cubes.len() <= bdd.sat_clauses().count());
+            let mut union = vars.mk_false();
+            for cube in &cubes {
+                let cube = mk_cube(&bdd, cube);
+                assert!(union.and(&cube).is_false());
+                union = union.or(&cube);
+            }
+            assert_eq!(bdd, union);
+
+            let mut union = vars.mk_false();
+            for cube in bdd.sat_maximal_cubes() {
+                assert!(mk_cube(&bdd, &cube).imp(&bdd).is_true());
+                for (var, _) in cube.to_values() {
+                    let mut smaller = cube.clone();
+                    smaller.unset_value(var);
+                    assert!(!mk_cube(&bdd, &smaller).imp(&bdd).is_true());
+                }
+                union = union.or(&mk_cube(&bdd, &cube));
+            }
+            assert_eq!(bdd, union);
+        }
+
+        // A disjunction has 3 paths, but the maximal cubes are just the 3 literals.
+        let bdd = vars.eval_expression_string("x_0 | x_1 | x_2");
+        assert_eq!(3, bdd.sat_maximal_cubes().count());
+        for cube in bdd.sat_maximal_cubes() {
+            assert_eq!(1, cube.cardinality());
+        }
+        // Here, paths `!x_0 & x_1 & x_2` and `x_0 & x_1 & x_2` are merged.
+        let bdd = vars.eval_expression_string("(x_1 & x_2) | (!x_0 & x_3)");
+        assert_eq!(4, bdd.sat_clauses().count());
+        let cubes: Vec<BddPartialValuation> = bdd.sat_disjoint_cubes().collect();
+        assert_eq!(3, cubes.len());
+    }
+}
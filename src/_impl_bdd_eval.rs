@@ -0,0 +1,206 @@
+use crate::{Bdd, BddValuation, BddVariable, CompiledBdd};
+
+impl Bdd {
+    /// Evaluate this `Bdd` in each of the given `valuations`.
+    ///
+    /// The valuations are processed in blocks of 64 using `Bdd::eval_packed`, so this is
+    /// typically much faster than calling `Bdd::eval_in` for each valuation separately.
+    pub fn eval_many(&self, valuations: &[BddValuation]) -> Vec<bool> {
+        eval_many(self.num_vars(), valuations, |columns| {
+            self.eval_packed(columns)
+        })
+    }
+
+    /// Evaluate this `Bdd` in 64 valuations at once.
+    ///
+    /// The valuations are given as `columns` of packed bits: the $k$-th bit of `columns[i]` is
+    /// the value of variable $x_i$ in the $k$-th valuation. The $k$-th bit of the result is
+    /// then the value of this `Bdd` in the $k$-th valuation.
+    ///
+    /// *Panics:* There must be exactly one column for each variable.
+    pub fn eval_packed(&self, columns: &[u64]) -> u64 {
+        check_columns(self.num_vars(), columns);
+        // Nodes are sorted such that children always precede their parents.
+        // (The `false` Bdd only has one node, but both terminals are always allocated.)
+        let mut results = vec![0u64; self.size().max(2)];
+        results[1] = u64::MAX;
+        for (i, node) in self.0.iter().enumerate().skip(2) {
+            let column = columns[usize::from(node.var.0)];
+            let low = results[node.low_link.to_index()];
+            let high = results[node.high_link.to_index()];
+            results[i] = (column & high) | (!column & low);
+        }
+        results[self.root_pointer().to_index()]
+    }
+
+    /// Flatten this `Bdd` into a `CompiledBdd` decision table that is faster to evaluate.
+    pub fn compile(&self) -> CompiledBdd {
+        let mut variables = Vec::with_capacity(self.size());
+        let mut links = Vec::with_capacity(self.size());
+        for node in &self.0 {
+            variables.push(node.var.0);
+            links.push([node.low_link.0, node.high_link.0]);
+        }
+        CompiledBdd {
+            num_vars: self.num_vars(),
+            variables,
+            links,
+        }
+    }
+}
+
+impl CompiledBdd {
+    /// The number of variables of the original `Bdd`.
+    pub fn num_vars(&self) -> u16 {
+        self.num_vars
+    }
+
+    /// The number of nodes of the decision table (including the two terminals).
+    pub fn size(&self) -> usize {
+        self.links.len()
+    }
+
+    /// Evaluate the decision table in the given `valuation`.
+    pub fn eval(&self, valuation: &BddValuation) -> bool {
+        debug_assert!(
+            valuation.num_vars() == self.num_vars,
+            "Incompatible variable count."
+        );
+        self.eval_with(|var| valuation.value(BddVariable(var)))
+    }
+
+    /// Evaluate the decision table in a valuation given as a slice of `bool` values
+    /// (indexed by variable).
+    ///
+    /// *Panics:* The slice must contain a value for each variable.
+    pub fn eval_slice(&self, values: &[bool]) -> bool {
+        assert_eq!(values.len(), usize::from(self.num_vars));
+        self.eval_with(|var| values[usize::from(var)])
+    }
+
+    /// Same as `Bdd::eval_many`, but using the decision table.
+    pub fn eval_many(&self, valuations: &[BddValuation]) -> Vec<bool> {
+        eval_many(self.num_vars, valuations, |columns| {
+            self.eval_packed(columns)
+        })
+    }
+
+    /// Same as `Bdd::eval_packed`, but using the decision table.
+    ///
+    /// *Panics:* There must be exactly one column for each variable.
+    pub fn eval_packed(&self, columns: &[u64]) -> u64 {
+        check_columns(self.num_vars, columns);
+        let mut results = vec![0u64; self.size().max(2)];
+        results[1] = u64::MAX;
+        for i in 2..self.size() {
+            let column = columns[usize::from(self.variables[i])];
+            let [low, high] = self.links[i];
+            results[i] = (column & results[high as usize]) | (!column & results[low as usize]);
+        }
+        results[self.size() - 1]
+    }
+
+    /// **(internal)** Walk the decision table from the root using the given variable values.
+    fn eval_with<F: Fn(u16) -> bool>(&self, value: F) -> bool {
+        let mut node = self.size() - 1;
+        while node > 1 {
+            let branch = usize::from(value(self.variables[node]));
+            node = self.links[node][branch] as usize;
+        }
+        node == 1
+    }
+}
+
+/// **(internal)** Check that the packed `columns` match the number of variables.
+fn check_columns(num_vars: u16, columns: &[u64]) {
+    if columns.len() != usize::from(num_vars) {
+        panic!(
+            "Expected {} columns, but found {}.",
+            num_vars,
+            columns.len()
+        );
+    }
+}
+
+/// **(internal)** Split the `valuations` into blocks of 64, transpose each block into packed
+/// columns and evaluate it using `eval_packed`.
+fn eval_many<F: Fn(&[u64]) -> u64>(
+    num_vars: u16,
+    valuations: &[BddValuation],
+    eval_packed: F,
+) -> Vec<bool> {
+    let mut result = Vec::with_capacity(valuations.len());
+    let mut columns = vec![0u64; usize::from(num_vars)];
+    for block in valuations.chunks(64) {
+        columns.iter_mut().for_each(|it| *it = 0);
+        for (k, valuation) in block.iter().enumerate() {
+            debug_assert!(
+                valuation.num_vars() == num_vars,
+                "Incompatible variable count."
+            );
+            for (i, word) in valuation.as_words().iter().enumerate() {
+                let mut rest = *word;
+                while rest != 0 {
+                    let var = i * 64 + rest.trailing_zeros() as usize;
+                    columns[var] |= 1u64 << k;
+                    rest &= rest - 1;
+                }
+            }
+        }
+        let packed = eval_packed(&columns);
+        result.extend((0..block.len()).map(|k| packed & (1u64 << k) != 0));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BddValuation, BddVariableSet, ValuationsOfClauseIterator};
+
+    #[test]
+    fn eval_many_matches_eval_in() {
+        let vars = BddVariableSet::new_anonymous(7);
+        let formulas = [
+            "true",
+            "false",
+            "x_0",
+            "(x_0 & !x_3) | (x_2 <=> x_6)",
+            "x_1 ^ x_2 ^ x_4 ^ x_5",
+        ];
+        let valuations: Vec<BddValuation> =
+            ValuationsOfClauseIterator::new_unconstrained(7).collect();
+        assert_eq!(128, valuations.len());
+        for formula in formulas {
+            let bdd = vars.eval_expression_string(formula);
+            let expected: Vec<bool> = valuations.iter().map(|it| bdd.eval_in(it)).collect();
+            assert_eq!(expected, bdd.eval_many(&valuations));
+            // Blocks that are not aligned to 64 valuations.
+            assert_eq!(expected[..70], bdd.eval_many(&valuations[..70]));
+
+            let compiled = bdd.compile();
+            assert_eq!(bdd.size(), compiled.size());
+            assert_eq!(expected, compiled.eval_many(&valuations));
+            for (valuation, value) in valuations.iter().zip(&expected) {
+                assert_eq!(*value, compiled.eval(valuation));
+                assert_eq!(*value, compiled.eval_slice(&valuation.clone().vector()));
+            }
+        }
+        assert!(vars.mk_true().eval_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn eval_packed_columns() {
+        let vars = BddVariableSet::new_anonymous(2);
+        let bdd = vars.eval_expression_string("x_0 & !x_1");
+        let columns = [0b1010, 0b1100];
+        assert_eq!(0b0010, bdd.eval_packed(&columns));
+        assert_eq!(0b0010, bdd.compile().eval_packed(&columns));
+    }
+
+    #[test]
+    #[should_panic]
+    fn eval_packed_invalid_columns() {
+        let vars = BddVariableSet::new_anonymous(2);
+        vars.mk_true().eval_packed(&[0]);
+    }
+}
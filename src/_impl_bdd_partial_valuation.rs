@@ -1,31 +1,38 @@
-use crate::{BddPartialValuation, BddValuation, BddVariable};
-use std::cmp::min;
+use crate::_impl_bdd_valuation::bit_of;
+use crate::boolean_expression::BooleanExpression;
+use crate::{BddPartialValuation, BddValuation, BddVariable, BddVariableSet};
 use std::convert::TryFrom;
+use std::fmt::{Debug, Error, Formatter};
 use std::hash::{Hash, Hasher};
-use std::ops::{Index, IndexMut};
+use std::ops::Index;
 
 impl BddPartialValuation {
     /// Creates an empty valuation without any variables set.
     pub fn empty() -> BddPartialValuation {
-        BddPartialValuation(Vec::new())
+        BddPartialValuation {
+            fixed: Vec::new(),
+            values: Vec::new(),
+        }
     }
 
     /// True if the valuation contains no values.
     pub fn is_empty(&self) -> bool {
-        self.0.iter().all(|it| it.is_none())
+        self.fixed.iter().all(|it| *it == 0)
     }
 
     /// Return the number of fixed variables in this valuation.
     pub fn cardinality(&self) -> u16 {
-        u16::try_from(self.0.iter().filter(|it| it.is_some()).count()).unwrap()
+        let count: u32 = self.fixed.iter().map(|it| it.count_ones()).sum();
+        u16::try_from(count).unwrap()
     }
 
     /// Return the identifier of the last fixed variable in this valuation. Returns `None` if
     /// no variable is fixed.
     pub fn last_fixed_variable(&self) -> Option<BddVariable> {
-        for i in (0..self.0.len()).rev() {
-            if self.0[i].is_some() {
-                return Some(BddVariable(i as u16));
+        for (i, word) in self.fixed.iter().enumerate().rev() {
+            if *word != 0 {
+                let index = i * 64 + 63 - word.leading_zeros() as usize;
+                return Some(BddVariable(index as u16));
             }
         }
         None
@@ -46,20 +53,15 @@ impl BddPartialValuation {
 
     /// Consume this valuation and turn it into a vector of values which are stored in it.
     pub fn to_values(&self) -> Vec<(BddVariable, bool)> {
-        self.0
-            .iter()
-            .enumerate()
-            .filter_map(|(i, value)| value.map(|value| (BddVariable(i as u16), value)))
-            .collect()
+        self.iter().collect()
     }
 
     /// Get a value stored for the given variable id, if any.
     pub fn get_value(&self, id: BddVariable) -> Option<bool> {
-        let index = usize::from(id.0);
-        if index < self.0.len() {
-            self.0[index]
-        } else {
-            None
+        let (word, mask) = bit_of(id);
+        match self.fixed.get(word) {
+            Some(fixed) if fixed & mask != 0 => Some(self.values[word] & mask != 0),
+            _ => None,
         }
     }
 
@@ -70,24 +72,28 @@ impl BddPartialValuation {
 
     /// Update value of the given `id` variable.
     pub fn set_value(&mut self, id: BddVariable, value: bool) {
-        let cell = self.mut_cell(id);
-        *cell = Some(value);
+        let (word, mask) = bit_of(id);
+        if self.fixed.len() <= word {
+            self.fixed.resize(word + 1, 0);
+            self.values.resize(word + 1, 0);
+        }
+        self.fixed[word] |= mask;
+        if value {
+            self.values[word] |= mask;
+        } else {
+            self.values[word] &= !mask;
+        }
     }
 
     /// Remove value of a variable from this valuation.
     ///
     /// If the value was not set, this operation has no effect.
     pub fn unset_value(&mut self, id: BddVariable) {
-        let cell = self.mut_cell(id);
-        *cell = None;
-    }
-
-    fn mut_cell(&mut self, id: BddVariable) -> &mut Option<bool> {
-        let index = usize::from(id.0);
-        while self.0.len() <= index {
-            self.0.push(None);
+        let (word, mask) = bit_of(id);
+        if word < self.fixed.len() {
+            self.fixed[word] &= !mask;
+            self.values[word] &= !mask;
         }
-        &mut self.0[index]
     }
 
     /// Returns true if the values set in this partial valuation match the values fixed in the
@@ -95,15 +101,143 @@ impl BddPartialValuation {
     ///
     /// In other words `this >= valuation` in terms of specificity.
     pub fn extends(&self, valuation: &BddPartialValuation) -> bool {
-        for var_id in 0..(valuation.0.len() as u16) {
-            let var = BddVariable(var_id);
-            let expected = valuation.get_value(var);
-            if expected.is_some() && self.get_value(var) != expected {
-                return false;
+        valuation.fixed.iter().enumerate().all(|(i, expected)| {
+            let fixed = self.fixed.get(i).cloned().unwrap_or(0);
+            let values = self.values.get(i).cloned().unwrap_or(0);
+            // All expected variables must be fixed, and to the same value.
+            expected & !fixed == 0 && (values ^ valuation.values[i]) & expected == 0
+        })
+    }
+
+    /// Iterate over all fixed variables and their values (in the variable order).
+    pub fn iter(&self) -> impl Iterator<Item = (BddVariable, bool)> + '_ {
+        self.fixed.iter().enumerate().flat_map(move |(i, fixed)| {
+            let values = self.values[i];
+            let mut rest = *fixed;
+            std::iter::from_fn(move || {
+                if rest == 0 {
+                    return None;
+                }
+                let bit = rest.trailing_zeros();
+                rest &= rest - 1;
+                let var = BddVariable((i * 64) as u16 + bit as u16);
+                Some((var, values & (1u64 << bit) != 0))
+            })
+        })
+    }
+
+    /// Returns true if there is a variable which is fixed to different values in this
+    /// and the `other` valuation.
+    pub fn conflicts_with(&self, other: &BddPartialValuation) -> bool {
+        self.fixed
+            .iter()
+            .zip(&other.fixed)
+            .zip(self.values.iter().zip(&other.values))
+            .any(|((a, b), (x, y))| a & b & (x ^ y) != 0)
+    }
+
+    /// Combine the fixed values of this and the `other` valuation. Returns `None` if the
+    /// two valuations are in conflict.
+    pub fn merge(&self, other: &BddPartialValuation) -> Option<BddPartialValuation> {
+        if self.conflicts_with(other) {
+            return None;
+        }
+        let mut result = self.clone();
+        for (var, value) in other.iter() {
+            result.set_value(var, value);
+        }
+        Some(result)
+    }
+
+    /// A valuation with values that are fixed to the same value in both this and the
+    /// `other` valuation.
+    pub fn intersection(&self, other: &BddPartialValuation) -> BddPartialValuation {
+        let values: Vec<(BddVariable, bool)> = self
+            .iter()
+            .filter(|(var, value)| other.get_value(*var) == Some(*value))
+            .collect();
+        BddPartialValuation::from_values(&values)
+    }
+
+    /// A valuation with values of this valuation that are not fixed to the same value
+    /// in the `other` valuation.
+    pub fn difference(&self, other: &BddPartialValuation) -> BddPartialValuation {
+        let values: Vec<(BddVariable, bool)> = self
+            .iter()
+            .filter(|(var, value)| other.get_value(*var) != Some(*value))
+            .collect();
+        BddPartialValuation::from_values(&values)
+    }
+
+    /// A valuation with only the values of the given `variables` (if they are fixed).
+    pub fn restrict_to(&self, variables: &[BddVariable]) -> BddPartialValuation {
+        let values: Vec<(BddVariable, bool)> = self
+            .iter()
+            .filter(|(var, _)| variables.contains(var))
+            .collect();
+        BddPartialValuation::from_values(&values)
+    }
+
+    /// Convert this valuation to a string of the form `a=1,b=0`, using the variable names
+    /// from the given `BddVariableSet`.
+    pub fn to_string_with_names(&self, variables: &BddVariableSet) -> String {
+        let items: Vec<String> = self
+            .iter()
+            .map(|(var, value)| format!("{}={}", variables.name_of(var), i32::from(value)))
+            .collect();
+        items.join(",")
+    }
+
+    /// Parse a valuation from a string of the form `a=1,b=0` (see also `to_string_with_names`),
+    /// resolving variable names using the given `BddVariableSet`. Values can be written as
+    /// `0`/`1` or `false`/`true` and whitespace is ignored.
+    pub fn from_string_with_names(
+        variables: &BddVariableSet,
+        data: &str,
+    ) -> Result<BddPartialValuation, String> {
+        let mut result = BddPartialValuation::empty();
+        if data.trim().is_empty() {
+            return Ok(result);
+        }
+        for item in data.split(',') {
+            let Some((name, value)) = item.split_once('=') else {
+                return Err(format!(
+                    "Expected `name=value`, but found `{}`.",
+                    item.trim()
+                ));
+            };
+            let (name, value) = (name.trim(), value.trim());
+            let Some(var) = variables.var_by_name(name) else {
+                return Err(format!("Unknown variable `{}`.", name));
+            };
+            let value = match value {
+                "1" | "true" => true,
+                "0" | "false" => false,
+                _ => return Err(format!("Invalid value `{}` of `{}`.", value, name)),
+            };
+            if result.get_value(var) == Some(!value) {
+                return Err(format!("Conflicting values of `{}`.", name));
             }
+            result.set_value(var, value);
         }
+        Ok(result)
+    }
 
-        true
+    /// Convert this valuation to a `BooleanExpression` which is a conjunction of
+    /// the fixed literals (using the variable names from the given `BddVariableSet`).
+    /// An empty valuation is converted to `true`.
+    pub fn to_boolean_expression(&self, variables: &BddVariableSet) -> BooleanExpression {
+        self.iter()
+            .map(|(var, value)| {
+                let literal = BooleanExpression::Variable(variables.name_of(var));
+                if value {
+                    literal
+                } else {
+                    BooleanExpression::Not(Box::new(literal))
+                }
+            })
+            .reduce(|a, b| BooleanExpression::And(Box::new(a), Box::new(b)))
+            .unwrap_or(BooleanExpression::Const(true))
     }
 }
 
@@ -115,7 +249,10 @@ impl Default for BddPartialValuation {
 
 impl From<BddValuation> for BddPartialValuation {
     fn from(value: BddValuation) -> Self {
-        BddPartialValuation(value.0.into_iter().map(Some).collect::<Vec<_>>())
+        BddPartialValuation {
+            fixed: BddValuation::all_true(value.num_vars()).as_words().to_vec(),
+            values: value.as_words().to_vec(),
+        }
     }
 }
 
@@ -123,40 +260,28 @@ impl Index<BddVariable> for BddPartialValuation {
     type Output = Option<bool>;
 
     fn index(&self, index: BddVariable) -> &Self::Output {
-        let index = usize::from(index.0);
-        if index < self.0.len() {
-            &self.0[index]
-        } else {
-            &None
+        match self.get_value(index) {
+            Some(true) => &Some(true),
+            Some(false) => &Some(false),
+            None => &None,
         }
     }
 }
 
-impl IndexMut<BddVariable> for BddPartialValuation {
-    fn index_mut(&mut self, index: BddVariable) -> &mut Self::Output {
-        self.mut_cell(index)
+impl Debug for BddPartialValuation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.debug_map().entries(self.iter()).finish()
     }
 }
 
 impl PartialEq for BddPartialValuation {
     fn eq(&self, other: &Self) -> bool {
-        let min_len = min(self.0.len(), other.0.len());
-        for i in 0..min_len {
-            if self.0[i] != other.0[i] {
-                return false;
-            }
-        }
-        for j in min_len..self.0.len() {
-            if self.0[j].is_some() {
-                return false;
-            }
-        }
-        for j in min_len..other.0.len() {
-            if other.0[j].is_some() {
-                return false;
-            }
-        }
-        true
+        // Trailing zero words are not significant.
+        let len = self.fixed.len().max(other.fixed.len());
+        (0..len).all(|i| {
+            self.fixed.get(i).unwrap_or(&0) == other.fixed.get(i).unwrap_or(&0)
+                && self.values.get(i).unwrap_or(&0) == other.values.get(i).unwrap_or(&0)
+        })
     }
 }
 
@@ -164,18 +289,20 @@ impl Eq for BddPartialValuation {}
 
 impl Hash for BddPartialValuation {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        for (var, value) in self.0.iter().enumerate() {
-            if let Some(value) = value {
-                state.write_usize(var);
-                state.write_u8(u8::from(*value))
-            }
-        }
+        let len = self
+            .fixed
+            .iter()
+            .rposition(|it| *it != 0)
+            .map(|it| it + 1)
+            .unwrap_or(0);
+        self.fixed[..len].hash(state);
+        self.values[..len].hash(state);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{BddPartialValuation, BddValuation, BddVariable};
+    use crate::{BddPartialValuation, BddValuation, BddVariable, BddVariableSet};
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
@@ -220,8 +347,8 @@ mod tests {
     #[test]
     fn valuation_conversions() {
         let mut val = BddValuation::all_false(24);
-        val[BddVariable(14)] = true;
-        val[BddVariable(21)] = true;
+        val.set(BddVariable(14));
+        val.set(BddVariable(21));
 
         assert_eq!(
             BddPartialValuation::from(val.clone()),
@@ -229,9 +356,86 @@ mod tests {
         );
 
         let mut partial = BddPartialValuation::from(val.clone());
-        partial[BddVariable(13)] = Some(true);
-        val[BddVariable(13)] = true;
+        partial.set_value(BddVariable(13), true);
+        val.set(BddVariable(13));
 
-        assert_eq!(val, BddValuation::try_from(partial).unwrap());
+        assert_eq!(val, BddValuation::try_from(partial.clone()).unwrap());
+
+        // Unset trailing variables are ignored, but unset variables before the last
+        // fixed variable are not.
+        partial.unset_value(BddVariable(23));
+        assert_eq!(
+            23,
+            BddValuation::try_from(partial.clone()).unwrap().num_vars()
+        );
+        partial.unset_value(BddVariable(0));
+        assert!(BddValuation::try_from(partial).is_err());
+    }
+
+    #[test]
+    fn partial_valuation_algebra() {
+        let vars = BddVariableSet::new(&["a", "b", "c", "d"]);
+        let [a, b, c, d] = <[BddVariable; 4]>::try_from(vars.variables()).unwrap();
+        let x = BddPartialValuation::from_values(&[(a, true), (b, false)]);
+        let y = BddPartialValuation::from_values(&[(b, false), (c, true)]);
+        let z = BddPartialValuation::from_values(&[(a, false), (d, true)]);
+
+        assert_eq!(vec![(a, true), (b, false)], x.iter().collect::<Vec<_>>());
+        assert!(!x.conflicts_with(&y));
+        assert!(x.conflicts_with(&z));
+        assert!(z.conflicts_with(&x));
+        assert_eq!(None, x.merge(&z));
+        let merged = BddPartialValuation::from_values(&[(a, true), (b, false), (c, true)]);
+        assert_eq!(Some(merged.clone()), x.merge(&y));
+        assert!(merged.extends(&x) && merged.extends(&y));
+
+        assert_eq!(
+            BddPartialValuation::from_values(&[(b, false)]),
+            x.intersection(&y)
+        );
+        assert!(x.intersection(&z).is_empty());
+        assert_eq!(
+            BddPartialValuation::from_values(&[(a, true)]),
+            x.difference(&y)
+        );
+        assert_eq!(x, x.difference(&z));
+        assert_eq!(
+            BddPartialValuation::from_values(&[(c, true)]),
+            merged.restrict_to(&[c, d])
+        );
+    }
+
+    #[test]
+    fn partial_valuation_names() {
+        let vars = BddVariableSet::new(&["a", "b", "c"]);
+        let [a, b, c] = <[BddVariable; 3]>::try_from(vars.variables()).unwrap();
+        let x = BddPartialValuation::from_values(&[(a, true), (c, false)]);
+        assert_eq!("a=1,c=0", x.to_string_with_names(&vars));
+        assert_eq!(
+            x,
+            BddPartialValuation::from_string_with_names(&vars, "a=1,c=0").unwrap()
+        );
+        assert_eq!(
+            x,
+            BddPartialValuation::from_string_with_names(&vars, " c = false , a=true").unwrap()
+        );
+        let empty = BddPartialValuation::empty();
+        assert_eq!("", empty.to_string_with_names(&vars));
+        assert_eq!(
+            empty,
+            BddPartialValuation::from_string_with_names(&vars, " ").unwrap()
+        );
+        for invalid in ["a", "x=1", "a=2", "a=1,a=0", "a=1,"] {
+            assert!(BddPartialValuation::from_string_with_names(&vars, invalid).is_err());
+        }
+
+        assert_eq!("(a & !c)", x.to_boolean_expression(&vars).to_string());
+        let y = BddPartialValuation::from_values(&[(b, false)]);
+        assert_eq!("!b", y.to_boolean_expression(&vars).to_string());
+        assert_eq!("true", empty.to_boolean_expression(&vars).to_string());
+        assert_eq!(
+            vars.mk_conjunctive_clause(&x),
+            vars.eval_expression(&x.to_boolean_expression(&vars))
+        );
     }
 }
@@ -1,15 +1,20 @@
-use crate::{Bdd, BddPartialValuation, BddPathIterator, BddPointer};
+use crate::{Bdd, BddNodes, BddPartialValuation, BddPathIterator, BddPointer, BddSource};
 
 impl BddPathIterator<'_> {
-    pub fn new(bdd: &Bdd) -> BddPathIterator {
-        if bdd.is_false() {
+    pub fn new(bdd: &Bdd) -> BddPathIterator<'_> {
+        BddPathIterator::from_source(BddSource::Owned(bdd))
+    }
+
+    /// **(internal)** Create a path iterator for any supported `Bdd` representation.
+    pub(crate) fn from_source(bdd: BddSource<'_>) -> BddPathIterator<'_> {
+        if bdd.size() == 1 {
             BddPathIterator {
                 bdd,
                 stack: Vec::new(),
             }
         } else {
             let mut stack = vec![bdd.root_pointer()];
-            continue_path(bdd, &mut stack); // Compute the first valid path.
+            continue_path(&bdd, &mut stack); // Compute the first valid path.
             BddPathIterator { bdd, stack }
         }
     }
@@ -22,7 +27,7 @@ impl Iterator for BddPathIterator<'_> {
         if self.stack.is_empty() {
             None
         } else {
-            let item = make_clause(self.bdd, &self.stack);
+            let item = make_clause(&self.bdd, &self.stack);
 
             // Now, we need to pop the path until we find a node with a valid successor,
             // and then extend the path using `continue_path`.
@@ -45,7 +50,7 @@ impl Iterator for BddPathIterator<'_> {
 
                         let new_entry = self.bdd.high_link_of(*top);
                         self.stack.push(new_entry);
-                        continue_path(self.bdd, &mut self.stack);
+                        continue_path(&self.bdd, &mut self.stack);
                         break;
                     }
                 } else if self.bdd.high_link_of(*top) == last_child {
@@ -71,7 +76,7 @@ impl Iterator for BddPathIterator<'_> {
 /// the low link unless it leads to a false leaf.
 ///
 /// The input path must be non-empty.
-fn continue_path(bdd: &Bdd, path: &mut Vec<BddPointer>) {
+fn continue_path<B: BddNodes>(bdd: &B, path: &mut Vec<BddPointer>) {
     assert!(!path.is_empty());
     loop {
         let top = *path.last().unwrap();
@@ -92,7 +97,7 @@ fn continue_path(bdd: &Bdd, path: &mut Vec<BddPointer>) {
 /// **(internal)** Convert a path in a `Bdd` saved as a stack into a clause.
 ///
 /// The path must end with a pointer to the one-terminal node.
-fn make_clause(bdd: &Bdd, path: &[BddPointer]) -> BddPartialValuation {
+fn make_clause<B: BddNodes>(bdd: &B, path: &[BddPointer]) -> BddPartialValuation {
     let mut result = BddPartialValuation::empty();
     for i in 0..(path.len() - 1) {
         let this_node = path[i];
@@ -104,7 +109,7 @@ fn make_clause(bdd: &Bdd, path: &[BddPointer]) -> BddPartialValuation {
         } else if bdd.high_link_of(this_node) == next_node {
             result.set_value(var, true);
         } else {
-            panic!("Path {:?} is not valid in BDD {:?}", path, bdd);
+            panic!("Path {:?} is not valid in the BDD.", path);
         }
     }
 
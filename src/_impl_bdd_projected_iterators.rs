@@ -0,0 +1,296 @@
+use crate::{
+    Bdd, BddPartialValuation, BddPointer, BddProjectedClauses, BddProjectedValuations, BddVariable,
+};
+use fxhash::FxBuildHasher;
+use num_bigint::BigInt;
+use std::collections::HashMap;
+
+impl Bdd {
+    /// Create an iterator over disjoint conjunctive clauses that use only the given
+    /// `variables` and together describe the projection of this `Bdd` onto these variables
+    /// (i.e. the result of `Bdd::exists` applied to all other variables).
+    ///
+    /// The projected `Bdd` is never constructed: the iterator explores the sets of nodes
+    /// which are reachable using the already fixed `variables`.
+    pub fn sat_clauses_over(&self, variables: &[BddVariable]) -> BddProjectedClauses<'_> {
+        BddProjectedClauses::new(self, variables)
+    }
+
+    /// Create an iterator over all distinct projections of the satisfying valuations of this
+    /// `Bdd` onto the given `variables`. Each item is a `BddPartialValuation` where exactly
+    /// the `variables` are fixed.
+    pub fn sat_valuations_over(&self, variables: &[BddVariable]) -> BddProjectedValuations<'_> {
+        let clauses = self.sat_clauses_over(variables);
+        BddProjectedValuations {
+            variables: clauses.variables.clone(),
+            clauses,
+            current: None,
+        }
+    }
+
+    /// Compute the number of distinct projections of the satisfying valuations of this `Bdd`
+    /// onto the given `variables`.
+    ///
+    /// Same as with `Bdd::sat_clauses_over`, the projected `Bdd` is never constructed. Instead,
+    /// the projections are counted using a memoised traversal of the sets of reachable nodes.
+    pub fn exact_cardinality_over(&self, variables: &[BddVariable]) -> BigInt {
+        let clauses = BddProjectedClauses::new(self, variables);
+        let root = clauses.normalize(vec![self.root_pointer()], 0);
+        let mut cache = HashMap::with_hasher(FxBuildHasher::default());
+        clauses.count_projections(0, root, &mut cache)
+    }
+}
+
+/// **(internal)** Sort and deduplicate `variables`, checking they are valid in the `bdd`.
+fn normalize_variables(bdd: &Bdd, variables: &[BddVariable]) -> Vec<BddVariable> {
+    let mut variables = variables.to_vec();
+    variables.sort();
+    variables.dedup();
+    if let Some(last) = variables.last() {
+        if last.0 >= bdd.num_vars() {
+            panic!("Variable {} is not in the Bdd.", last);
+        }
+    }
+    variables
+}
+
+impl BddProjectedClauses<'_> {
+    /// **(internal)** Create a new iterator over the projection onto `variables`.
+    fn new<'a>(bdd: &'a Bdd, variables: &[BddVariable]) -> BddProjectedClauses<'a> {
+        let variables = normalize_variables(bdd, variables);
+        let mut iterator = BddProjectedClauses {
+            bdd,
+            variables,
+            stack: Vec::new(),
+        };
+        let root = iterator.normalize(vec![bdd.root_pointer()], 0);
+        if !root.is_empty() {
+            iterator.stack.push((0, root, BddPartialValuation::empty()));
+        }
+        iterator
+    }
+
+    /// **(internal)** Normalize a set of nodes before branching on the variable at the given
+    /// `level`: Nodes conditioning on variables that are not projected (and thus come before
+    /// the variable at `level`) are replaced by their children. Zero nodes are removed, and if
+    /// the set contains the one node, it is replaced by a set with just the one node.
+    fn normalize(&self, nodes: Vec<BddPointer>, level: usize) -> Vec<BddPointer> {
+        let bound = self.variables.get(level).cloned();
+        let mut result = Vec::new();
+        let mut stack = nodes;
+        while let Some(node) = stack.pop() {
+            if node.is_zero() {
+                continue;
+            }
+            if node.is_one() {
+                return vec![BddPointer::one()];
+            }
+            match bound {
+                Some(bound) if self.bdd.var_of(node) < bound => {
+                    stack.push(self.bdd.low_link_of(node));
+                    stack.push(self.bdd.high_link_of(node));
+                }
+                _ => result.push(node),
+            }
+        }
+        result.sort();
+        result.dedup();
+        result
+    }
+
+    /// **(internal)** Count the valuations of the projected variables from the given `level`
+    /// onwards, for which the disjunction of the (normalized) `nodes` is satisfiable.
+    fn count_projections(
+        &self,
+        level: usize,
+        nodes: Vec<BddPointer>,
+        cache: &mut HashMap<(usize, Vec<BddPointer>), BigInt, FxBuildHasher>,
+    ) -> BigInt {
+        if nodes.is_empty() {
+            // All the nodes were zero.
+            return BigInt::from(0);
+        }
+        if nodes[0].is_one() {
+            return BigInt::from(1) << (self.variables.len() - level);
+        }
+        if level == self.variables.len() {
+            // Every non-zero node has some satisfying valuation.
+            return BigInt::from(1);
+        }
+        let key = (level, nodes);
+        if let Some(count) = cache.get(&key) {
+            return count.clone();
+        }
+        let variable = self.variables[level];
+        let low = self.normalize(self.cofactor(&key.1, variable, false), level + 1);
+        let high = self.normalize(self.cofactor(&key.1, variable, true), level + 1);
+        let count = self.count_projections(level + 1, low, cache)
+            + self.count_projections(level + 1, high, cache);
+        cache.insert(key, count.clone());
+        count
+    }
+
+    /// **(internal)** Restrict the disjunction of `nodes` to the given `value` of `variable`.
+    fn cofactor(
+        &self,
+        nodes: &[BddPointer],
+        variable: BddVariable,
+        value: bool,
+    ) -> Vec<BddPointer> {
+        nodes
+            .iter()
+            .map(|node| {
+                if self.bdd.var_of(*node) != variable {
+                    *node
+                } else if value {
+                    self.bdd.high_link_of(*node)
+                } else {
+                    self.bdd.low_link_of(*node)
+                }
+            })
+            .collect()
+    }
+}
+
+impl Iterator for BddProjectedClauses<'_> {
+    type Item = BddPartialValuation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((level, nodes, clause)) = self.stack.pop() {
+            // Every non-zero node is satisfiable, so the remaining function is `true`
+            // on the remaining variables.
+            if level == self.variables.len() || nodes == [BddPointer::one()] {
+                return Some(clause);
+            }
+            let var = self.variables[level];
+            let low = self.normalize(self.cofactor(&nodes, var, false), level + 1);
+            let high = self.normalize(self.cofactor(&nodes, var, true), level + 1);
+            if low == high {
+                // The variable is irrelevant in this branch.
+                self.stack.push((level + 1, low, clause));
+                continue;
+            }
+            if !high.is_empty() {
+                let mut clause = clause.clone();
+                clause.set_value(var, true);
+                self.stack.push((level + 1, high, clause));
+            }
+            if !low.is_empty() {
+                let mut clause = clause;
+                clause.set_value(var, false);
+                self.stack.push((level + 1, low, clause));
+            }
+        }
+        None
+    }
+}
+
+impl Iterator for BddProjectedValuations<'_> {
+    type Item = BddPartialValuation;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((valuation, free)) = self.current.as_mut() {
+            // Increment the free variables as a binary counter.
+            for var in free.iter() {
+                if valuation.get_value(*var) == Some(false) {
+                    valuation.set_value(*var, true);
+                    return Some(valuation.clone());
+                }
+                valuation.set_value(*var, false);
+            }
+        }
+        let clause = self.clauses.next()?;
+        let free: Vec<BddVariable> = self
+            .variables
+            .iter()
+            .filter(|var| clause.get_value(**var).is_none())
+            .cloned()
+            .collect();
+        let mut valuation = clause;
+        for var in &free {
+            valuation.set_value(*var, false);
+        }
+        self.current = Some((valuation.clone(), free));
+        Some(valuation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Bdd, BddPartialValuation, BddVariable, BddVariableSet};
+    use num_bigint::BigInt;
+    use std::collections::HashSet;
+
+    fn check_projection(bdd: &Bdd, variables: &[BddVariable]) {
+        let others: Vec<BddVariable> = (0..bdd.num_vars())
+            .map(BddVariable)
+            .filter(|it| !variables.contains(it))
+            .collect();
+        let projected = bdd.exists(&others);
+
+        // Clauses are disjoint, only use `variables`, and cover exactly the projection.
+        let clauses: Vec<BddPartialValuation> = bdd.sat_clauses_over(variables).collect();
+        let mut union = Bdd::mk_false(bdd.num_vars());
+        for clause in &clauses {
+            for (var, _) in clause.to_values() {
+                assert!(variables.contains(&var));
+            }
+            let clause = Bdd::mk_partial_valuation(bdd.num_vars(), clause);
+            assert!(union.and(&clause).is_false());
+            union = union.or(&clause);
+        }
+        assert_eq!(projected, union);
+
+        let valuations: Vec<BddPartialValuation> = bdd.sat_valuations_over(variables).collect();
+        let unique: HashSet<Vec<(BddVariable, bool)>> =
+            valuations.iter().map(|it| it.to_values()).collect();
+        assert_eq!(valuations.len(), unique.len());
+        for valuation in &valuations {
+            assert_eq!(variables.len(), usize::from(valuation.cardinality()));
+        }
+        let expected = projected.exact_cardinality() >> others.len();
+        assert_eq!(expected, BigInt::from(valuations.len()));
+        assert_eq!(expected, bdd.exact_cardinality_over(variables));
+    }
+
+    #[test]
+    fn projected_iterators() {
+        let vars = BddVariableSet::new_anonymous(6);
+        let v = vars.variables();
+        let formulas = [
+            "true",
+            "false",
+            "x_0",
+            "(x_0 <=> x_3) & (x_1 <=> x_4) & (x_2 ^ x_5)",
+            "(x_1 & x_3) | (!x_2 & x_5) | (x_0 ^ x_4)",
+            "x_5 & !x_4 & (x_1 | x_2)",
+        ];
+        let subsets: [&[BddVariable]; 5] =
+            [&[], &[v[0]], &[v[0], v[1], v[2]], &[v[5], v[1], v[3]], &v];
+        for formula in formulas {
+            let bdd = vars.eval_expression_string(formula);
+            for subset in subsets {
+                check_projection(&bdd, subset);
+            }
+        }
+
+        let bdd = vars.eval_expression_string("x_0 & (x_2 | x_3)");
+        assert_eq!(1, bdd.sat_clauses_over(&[v[0], v[1]]).count());
+        assert_eq!(2, bdd.sat_valuations_over(&[v[0], v[1]]).count());
+        assert_eq!(0, vars.mk_false().sat_valuations_over(&[]).count());
+        assert_eq!(1, vars.mk_true().sat_valuations_over(&[]).count());
+
+        // Too many projections to enumerate, but they can still be counted.
+        let vars = BddVariableSet::new_anonymous(80);
+        let v = vars.variables();
+        let mut bdd = vars.mk_true();
+        for i in 0..79 {
+            let pair = vars.mk_var(v[i]).or(&vars.mk_var(v[i + 1]));
+            bdd = bdd.and(&pair.or(&vars.mk_var(v[(i + 2) % 80])));
+        }
+        let projected: Vec<BddVariable> = v.iter().step_by(2).cloned().collect();
+        let others: Vec<BddVariable> = v.iter().skip(1).step_by(2).cloned().collect();
+        let expected = bdd.exists(&others).exact_cardinality() >> others.len();
+        assert_eq!(expected, bdd.exact_cardinality_over(&projected));
+    }
+}
@@ -0,0 +1,346 @@
+use crate::_impl_bdd::_impl_util::{cardinality, exact_cardinality, support_set};
+use crate::_impl_bdd_valuation::eval_in;
+use crate::*;
+use num_bigint::BigInt;
+
+/// **(internal)** Number of bytes used by one node in the binary `Bdd` encoding.
+const NODE_BYTES: usize = 10;
+
+impl<'a> BddRef<'a> {
+    /// Create a new `BddRef` view over the given `bytes`, which must contain a `Bdd`
+    /// in the binary format produced by `Bdd::write_as_bytes`.
+    ///
+    /// The method checks that the data is well-formed (i.e. the terminal nodes are present,
+    /// every node has a valid variable, and every link points to an earlier node), but it
+    /// does not copy the data or allocate any memory. The check is linear in the size
+    /// of the `Bdd`.
+    // `usize::is_multiple_of` requires Rust 1.87, which is newer than the rest of the crate.
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<BddRef<'a>, String> {
+        if bytes.is_empty() {
+            return Err("Empty byte slice is not a valid BDD.".to_string());
+        }
+        if bytes.len() % NODE_BYTES != 0 {
+            return Err(format!(
+                "Byte slice of length {} is not a valid BDD (expected multiple of {}).",
+                bytes.len(),
+                NODE_BYTES
+            ));
+        }
+        let bdd = BddRef(bytes);
+        let num_vars = bdd.num_vars();
+        let zero = bdd.read_node(BddPointer::zero());
+        if zero != BddNode::mk_zero(num_vars) {
+            return Err(format!("Invalid zero terminal node {:?}.", zero));
+        }
+        if bdd.size() > 1 {
+            let one = bdd.read_node(BddPointer::one());
+            if one != BddNode::mk_one(num_vars) {
+                return Err(format!("Invalid one terminal node {:?}.", one));
+            }
+        }
+        for index in 2..bdd.size() {
+            let node = bdd.read_node(BddPointer::from_index(index));
+            if node.var.0 >= num_vars {
+                return Err(format!(
+                    "Node {} uses variable {}, but the BDD only has {} variables.",
+                    index, node.var, num_vars
+                ));
+            }
+            if node.low_link.to_index() >= index || node.high_link.to_index() >= index {
+                return Err(format!(
+                    "Node {} has links ({}, {}) that do not point to earlier nodes.",
+                    index, node.low_link, node.high_link
+                ));
+            }
+            // Terminals use `num_vars` as their variable, so they always pass this check.
+            let low_var = bdd.var_of(node.low_link);
+            let high_var = bdd.var_of(node.high_link);
+            if low_var <= node.var || high_var <= node.var {
+                return Err(format!(
+                    "Node {} (variable {}) has children with variables ({}, {}) that break the variable order.",
+                    index, node.var, low_var, high_var
+                ));
+            }
+        }
+        Ok(bdd)
+    }
+
+    /// The underlying bytes of this `BddRef`.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// The number of nodes in this `BddRef`. (Do not confuse with cardinality)
+    pub fn size(&self) -> usize {
+        self.0.len() / NODE_BYTES
+    }
+
+    /// Number of variables in the corresponding `BddVariableSet`.
+    pub fn num_vars(&self) -> u16 {
+        self.var_of(BddPointer::zero()).0
+    }
+
+    /// True if this `BddRef` is exactly the `true` formula.
+    pub fn is_true(&self) -> bool {
+        self.size() == 2
+    }
+
+    /// True if this `BddRef` is exactly the `false` formula.
+    pub fn is_false(&self) -> bool {
+        self.size() == 1
+    }
+
+    /// Pointer to the root of the decision diagram.
+    pub fn root_pointer(&self) -> BddPointer {
+        BddPointer::from_index(self.size() - 1)
+    }
+
+    /// Get the low link of the node at a specified location.
+    pub fn low_link_of(&self, node: BddPointer) -> BddPointer {
+        let i = node.to_index() * NODE_BYTES;
+        let b = self.0;
+        BddPointer::from_le_bytes([b[i + 2], b[i + 3], b[i + 4], b[i + 5]])
+    }
+
+    /// Get the high link of the node at a specified location.
+    pub fn high_link_of(&self, node: BddPointer) -> BddPointer {
+        let i = node.to_index() * NODE_BYTES;
+        let b = self.0;
+        BddPointer::from_le_bytes([b[i + 6], b[i + 7], b[i + 8], b[i + 9]])
+    }
+
+    /// Get the conditioning variable of the node at a specified location.
+    ///
+    /// Note that this also technically works for terminals, but the returned `BddVariable` is
+    /// not valid in this `BddRef`.
+    pub fn var_of(&self, node: BddPointer) -> BddVariable {
+        let i = node.to_index() * NODE_BYTES;
+        BddVariable::from_le_bytes([self.0[i], self.0[i + 1]])
+    }
+
+    /// Evaluate this `BddRef` in a specified `BddValuation`.
+    pub fn eval_in(&self, valuation: &BddValuation) -> bool {
+        eval_in(self, valuation)
+    }
+
+    /// Approximately computes the number of valuations satisfying the formula given
+    /// by this `BddRef`.
+    pub fn cardinality(&self) -> f64 {
+        cardinality(self)
+    }
+
+    /// Exactly computes the number of valuations satisfying the formula given
+    /// by this `BddRef`.
+    pub fn exact_cardinality(&self) -> BigInt {
+        exact_cardinality(self)
+    }
+
+    /// Return the set of all variables that actually appear as decision variables in this `BddRef`.
+    pub fn support_set(&self) -> HashSet<BddVariable> {
+        support_set(self)
+    }
+
+    /// Create an iterator that goes through all the satisfying valuations of this `BddRef`.
+    ///
+    /// See also `Bdd::sat_valuations`.
+    pub fn sat_valuations(&self) -> BddSatisfyingValuations<'a> {
+        BddSatisfyingValuations::from_source(BddSource::Bytes(*self))
+    }
+
+    /// Create an iterator that goes through all paths of this `BddRef`.
+    ///
+    /// See also `Bdd::sat_clauses`.
+    pub fn sat_clauses(&self) -> BddPathIterator<'a> {
+        BddPathIterator::from_source(BddSource::Bytes(*self))
+    }
+
+    /// Copy this view into a standard (owned) `Bdd`.
+    pub fn to_bdd(&self) -> Bdd {
+        Bdd::from_bytes(&mut &self.0[..])
+    }
+
+    /// **(internal)** Decode the full node at the given location.
+    fn read_node(&self, node: BddPointer) -> BddNode {
+        BddNode::mk_node(
+            self.var_of(node),
+            self.low_link_of(node),
+            self.high_link_of(node),
+        )
+    }
+}
+
+impl BddNodes for Bdd {
+    fn size(&self) -> usize {
+        Bdd::size(self)
+    }
+
+    fn num_vars(&self) -> u16 {
+        Bdd::num_vars(self)
+    }
+
+    fn var_of(&self, node: BddPointer) -> BddVariable {
+        Bdd::var_of(self, node)
+    }
+
+    fn low_link_of(&self, node: BddPointer) -> BddPointer {
+        Bdd::low_link_of(self, node)
+    }
+
+    fn high_link_of(&self, node: BddPointer) -> BddPointer {
+        Bdd::high_link_of(self, node)
+    }
+}
+
+impl BddNodes for BddRef<'_> {
+    fn size(&self) -> usize {
+        BddRef::size(self)
+    }
+
+    fn num_vars(&self) -> u16 {
+        BddRef::num_vars(self)
+    }
+
+    fn var_of(&self, node: BddPointer) -> BddVariable {
+        BddRef::var_of(self, node)
+    }
+
+    fn low_link_of(&self, node: BddPointer) -> BddPointer {
+        BddRef::low_link_of(self, node)
+    }
+
+    fn high_link_of(&self, node: BddPointer) -> BddPointer {
+        BddRef::high_link_of(self, node)
+    }
+}
+
+impl BddNodes for BddSource<'_> {
+    fn size(&self) -> usize {
+        match self {
+            BddSource::Owned(bdd) => bdd.size(),
+            BddSource::Bytes(bdd) => bdd.size(),
+        }
+    }
+
+    fn num_vars(&self) -> u16 {
+        match self {
+            BddSource::Owned(bdd) => bdd.num_vars(),
+            BddSource::Bytes(bdd) => bdd.num_vars(),
+        }
+    }
+
+    fn var_of(&self, node: BddPointer) -> BddVariable {
+        match self {
+            BddSource::Owned(bdd) => bdd.var_of(node),
+            BddSource::Bytes(bdd) => bdd.var_of(node),
+        }
+    }
+
+    fn low_link_of(&self, node: BddPointer) -> BddPointer {
+        match self {
+            BddSource::Owned(bdd) => bdd.low_link_of(node),
+            BddSource::Bytes(bdd) => bdd.low_link_of(node),
+        }
+    }
+
+    fn high_link_of(&self, node: BddPointer) -> BddPointer {
+        match self {
+            BddSource::Owned(bdd) => bdd.high_link_of(node),
+            BddSource::Bytes(bdd) => bdd.high_link_of(node),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_small_test_bdd;
+    use crate::*;
+
+    #[test]
+    fn bdd_ref_basic() {
+        let vars = BddVariableSet::new_anonymous(5);
+        let bdd = vars.eval_expression_string("(x_0 & !x_1) | (x_2 <=> x_4)");
+        let bytes = bdd.to_bytes();
+        let bdd_ref = BddRef::from_bytes(&bytes).unwrap();
+
+        assert_eq!(bdd.size(), bdd_ref.size());
+        assert_eq!(bdd.num_vars(), bdd_ref.num_vars());
+        assert_eq!(bdd.root_pointer(), bdd_ref.root_pointer());
+        assert_eq!(bdd.cardinality(), bdd_ref.cardinality());
+        assert_eq!(bdd.exact_cardinality(), bdd_ref.exact_cardinality());
+        assert_eq!(bdd.support_set(), bdd_ref.support_set());
+        assert_eq!(bdd, bdd_ref.to_bdd());
+
+        for valuation in ValuationsOfClauseIterator::new_unconstrained(5) {
+            assert_eq!(bdd.eval_in(&valuation), bdd_ref.eval_in(&valuation));
+        }
+
+        let expected = bdd.sat_valuations().collect::<Vec<_>>();
+        let actual = bdd_ref.sat_valuations().collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+
+        let expected = bdd.sat_clauses().collect::<Vec<_>>();
+        let actual = bdd_ref.sat_clauses().collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn bdd_ref_constants() {
+        let vars = BddVariableSet::new_anonymous(3);
+        let tt = vars.mk_true().to_bytes();
+        let ff = vars.mk_false().to_bytes();
+        let tt = BddRef::from_bytes(&tt).unwrap();
+        let ff = BddRef::from_bytes(&ff).unwrap();
+        assert!(tt.is_true());
+        assert!(ff.is_false());
+        assert_eq!(8.0, tt.cardinality());
+        assert_eq!(0.0, ff.cardinality());
+        assert_eq!(8, tt.sat_valuations().count());
+        assert_eq!(0, ff.sat_valuations().count());
+    }
+
+    #[test]
+    fn bdd_ref_apply() {
+        let vars = BddVariableSet::new_anonymous(5);
+        let a = vars.eval_expression_string("x_0 | (x_3 & !x_4)");
+        let b = vars.eval_expression_string("x_1 ^ x_3");
+        let bytes = a.to_bytes();
+        let a_ref = BddRef::from_bytes(&bytes).unwrap();
+
+        assert_eq!(a.and(&b), a_ref.and(&b));
+        assert_eq!(a.or(&b), a_ref.or(&b));
+        assert_eq!(a.xor(&b), a_ref.xor(&b));
+        assert_eq!(a.imp(&b), a_ref.imp(&b));
+        assert_eq!(a.iff(&b), a_ref.iff(&b));
+        assert_eq!(a.and_not(&b), a_ref.and_not(&b));
+        assert_eq!(
+            Bdd::binary_op(&a, &b, op_function::and_not),
+            a_ref.binary_op(&b, op_function::and_not)
+        );
+    }
+
+    #[test]
+    fn bdd_ref_invalid() {
+        let bytes = mk_small_test_bdd().to_bytes();
+        assert!(BddRef::from_bytes(&[]).is_err());
+        assert!(BddRef::from_bytes(&bytes[..15]).is_err());
+        // Swap the two decision nodes, breaking the link order.
+        let mut broken = bytes.clone();
+        broken[20..30].copy_from_slice(&bytes[30..40]);
+        broken[30..40].copy_from_slice(&bytes[20..30]);
+        assert!(BddRef::from_bytes(&broken).is_err());
+        // Links point to earlier nodes, but the variable order is broken.
+        let mut unordered = Bdd::mk_true(3);
+        unordered.push_node(BddNode::mk_node(
+            BddVariable(1),
+            BddPointer::zero(),
+            BddPointer::one(),
+        ));
+        unordered.push_node(BddNode::mk_node(
+            BddVariable(2),
+            BddPointer::zero(),
+            BddPointer::from_index(2),
+        ));
+        assert!(BddRef::from_bytes(&unordered.to_bytes()).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
-    Bdd, BddPathIterator, BddSatisfyingValuations, BddValuation, ValuationsOfClauseIterator,
+    Bdd, BddNodes, BddPathIterator, BddSatisfyingValuations, BddSource, BddValuation,
+    ValuationsOfClauseIterator,
 };
 
 impl Bdd {
@@ -7,29 +8,36 @@ impl Bdd {
     ///
     /// Note that the number of such valuations can be substantial and can be approximated
     /// using `Bdd.cardinality`.
-    pub fn sat_valuations(&self) -> BddSatisfyingValuations {
-        let mut path_iter = BddPathIterator::new(self);
+    pub fn sat_valuations(&self) -> BddSatisfyingValuations<'_> {
+        BddSatisfyingValuations::from_source(BddSource::Owned(self))
+    }
+
+    /// Create an iterator that goes through all paths of this `Bdd`. Each path is represented
+    /// as a *conjunctive clause* in the form of `BddPartialValuation`.
+    ///
+    /// The whole formula represented by a `Bdd` can be then seen as a disjunction of these
+    /// clauses/paths.
+    pub fn sat_clauses(&self) -> BddPathIterator<'_> {
+        BddPathIterator::new(self)
+    }
+}
+
+impl BddSatisfyingValuations<'_> {
+    /// **(internal)** Create a valuation iterator for any supported `Bdd` representation.
+    pub(crate) fn from_source(bdd: BddSource<'_>) -> BddSatisfyingValuations<'_> {
+        let mut path_iter = BddPathIterator::from_source(bdd);
         let val_iter = if let Some(first) = path_iter.next() {
-            ValuationsOfClauseIterator::new(first, self.num_vars())
+            ValuationsOfClauseIterator::new(first, bdd.num_vars())
         } else {
             // This is a special case for the `false` BDD.
             ValuationsOfClauseIterator::empty()
         };
         BddSatisfyingValuations {
-            bdd: self,
+            num_vars: bdd.num_vars(),
             paths: path_iter,
             valuations: val_iter,
         }
     }
-
-    /// Create an iterator that goes through all paths of this `Bdd`. Each path is represented
-    /// as a *conjunctive clause* in the form of `BddPartialValuation`.
-    ///
-    /// The whole formula represented by a `Bdd` can be then seen as a disjunction of these
-    /// clauses/paths.
-    pub fn sat_clauses(&self) -> BddPathIterator {
-        BddPathIterator::new(self)
-    }
 }
 
 impl Iterator for BddSatisfyingValuations<'_> {
@@ -40,7 +48,7 @@ impl Iterator for BddSatisfyingValuations<'_> {
         if next_valuation.is_some() {
             next_valuation
         } else if let Some(next_path) = self.paths.next() {
-            self.valuations = ValuationsOfClauseIterator::new(next_path, self.bdd.num_vars());
+            self.valuations = ValuationsOfClauseIterator::new(next_path, self.num_vars);
             // A new valuations iterator is never empty unless created using the `empty` constructor.
             self.valuations.next()
         } else {
@@ -67,12 +75,9 @@ mod tests {
         expected.sort();
 
         assert_eq!(sat_valuations.len(), 16);
-        sat_valuations
-            .into_iter()
-            .zip(expected.into_iter())
-            .for_each(|(a, b)| {
-                assert_eq!(a, b);
-            });
+        sat_valuations.into_iter().zip(expected).for_each(|(a, b)| {
+            assert_eq!(a, b);
+        });
     }
 
     #[test]
@@ -89,11 +94,8 @@ mod tests {
         expected.sort();
 
         assert_eq!(sat_valuations.len(), expected.len());
-        sat_valuations
-            .into_iter()
-            .zip(expected.into_iter())
-            .for_each(|(a, b)| {
-                assert_eq!(a, b);
-            });
+        sat_valuations.into_iter().zip(expected).for_each(|(a, b)| {
+            assert_eq!(a, b);
+        });
     }
 }
@@ -1,74 +1,169 @@
 use super::{Bdd, BddValuation, BddVariable};
-use crate::{BddNode, BddPartialValuation, BddPointer, ValuationsOfClauseIterator};
+use crate::{BddNode, BddNodes, BddPartialValuation, BddPointer, ValuationsOfClauseIterator};
+use std::cmp::Ordering;
 use std::convert::TryFrom;
-use std::fmt::{Display, Error, Formatter};
-use std::ops::{Index, IndexMut};
+use std::fmt::{Debug, Display, Error, Formatter};
+use std::ops::Index;
+
+/// **(internal)** The number of `u64` words needed to store `num_vars` bits.
+pub(crate) fn words_for(num_vars: usize) -> usize {
+    num_vars.div_ceil(64)
+}
+
+/// **(internal)** The word index and the bit mask of the given variable.
+#[inline]
+pub(crate) fn bit_of(variable: BddVariable) -> (usize, u64) {
+    let index = usize::from(variable.0);
+    (index / 64, 1u64 << (index % 64))
+}
 
 impl BddValuation {
     /// Create a new valuation from a vector of variables.
     pub fn new(values: Vec<bool>) -> BddValuation {
-        BddValuation(values)
+        let Ok(num_vars) = u16::try_from(values.len()) else {
+            panic!("BddValuation is limited to u16::MAX values.");
+        };
+        let mut result = BddValuation::all_false(num_vars);
+        for (i, value) in values.into_iter().enumerate() {
+            if value {
+                result.words[i / 64] |= 1u64 << (i % 64);
+            }
+        }
+        result
     }
 
     /// Create a valuation with all variables set to false.
     pub fn all_false(num_vars: u16) -> BddValuation {
-        BddValuation(vec![false; num_vars as usize])
+        BddValuation {
+            num_vars,
+            words: vec![0; words_for(usize::from(num_vars))],
+        }
     }
 
     /// Create a valuation with all variables set to true.
     pub fn all_true(num_vars: u16) -> BddValuation {
-        BddValuation(vec![true; num_vars as usize])
+        let mut result = BddValuation {
+            num_vars,
+            words: vec![u64::MAX; words_for(usize::from(num_vars))],
+        };
+        result.clear_unused_bits();
+        result
+    }
+
+    /// Create a valuation of `num_vars` variables from packed `words` (variable $x_i$ is bit
+    /// $i \bmod 64$ of the word $\lfloor i / 64 \rfloor$). Missing words are treated as zero
+    /// and bits beyond `num_vars` are ignored.
+    pub fn from_words(num_vars: u16, words: &[u64]) -> BddValuation {
+        let mut result = BddValuation::all_false(num_vars);
+        for (target, source) in result.words.iter_mut().zip(words) {
+            *target = *source;
+        }
+        result.clear_unused_bits();
+        result
+    }
+
+    /// The packed representation of this valuation (see `BddValuation::from_words`).
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Create a valuation of `num_vars` variables from the bits of an integer (variable $x_i$
+    /// is the $i$-th least significant bit).
+    ///
+    /// *Panics:* `num_vars` must be at most 64.
+    pub fn from_u64(num_vars: u16, value: u64) -> BddValuation {
+        if num_vars > 64 {
+            panic!(
+                "Cannot create a valuation of {} variables from u64.",
+                num_vars
+            );
+        }
+        BddValuation::from_words(num_vars, &[value])
+    }
+
+    /// Convert this valuation to an integer (see `BddValuation::from_u64`). Returns `None`
+    /// if there are more than 64 variables.
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.num_vars > 64 {
+            None
+        } else {
+            Some(self.words.first().cloned().unwrap_or(0))
+        }
+    }
+
+    /// The number of variables that are `true` in this valuation.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|it| it.count_ones() as usize).sum()
+    }
+
+    /// The number of variables which have a different value in this and the `other`
+    /// valuation.
+    ///
+    /// *Panics:* Both valuations must have the same number of variables.
+    pub fn hamming_distance(&self, other: &BddValuation) -> usize {
+        if self.num_vars != other.num_vars {
+            panic!(
+                "Incompatible valuations: {} != {} variables.",
+                self.num_vars, other.num_vars
+            );
+        }
+        self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a ^ b).count_ones() as usize)
+            .sum()
     }
 
     /// Flip the value of a given Bdd variable.
     pub fn flip_value(&mut self, variable: BddVariable) {
-        let i = variable.0 as usize;
-        self.0[i] = !self.0[i];
+        let (word, mask) = self.bit_checked(variable);
+        self.words[word] ^= mask;
     }
 
     /// Set the value of the given `variable` to `false`.
     pub fn clear(&mut self, variable: BddVariable) {
-        self.0[variable.0 as usize] = false;
+        self.set_value(variable, false);
     }
 
     /// Set the value of the given `variable` to `true`.
     pub fn set(&mut self, variable: BddVariable) {
-        self.0[variable.0 as usize] = true;
+        self.set_value(variable, true);
     }
 
     /// Update `value` of the given `variable`.
     pub fn set_value(&mut self, variable: BddVariable, value: bool) {
-        self.0[variable.0 as usize] = value;
+        let (word, mask) = self.bit_checked(variable);
+        if value {
+            self.words[word] |= mask;
+        } else {
+            self.words[word] &= !mask;
+        }
     }
 
     /// Convert the valuation to its underlying vector.
     pub fn vector(self) -> Vec<bool> {
-        self.0
+        (0..self.num_vars)
+            .map(|it| self.value(BddVariable(it)))
+            .collect()
     }
 
     /// Convert [BddValuation] to a vector of tagged values in the way that is compatible
     /// with [BddPartialValuation] representation.
     pub fn to_values(&self) -> Vec<(BddVariable, bool)> {
-        self.0
-            .iter()
-            .enumerate()
-            .map(|(i, v)| {
-                let Ok(i) = u16::try_from(i) else {
-                    unreachable!("BddValuation is limited to u16::MAX values.")
-                };
-                (BddVariable(i), *v)
-            })
-            .collect::<Vec<_>>()
+        (0..self.num_vars)
+            .map(|it| (BddVariable(it), self.value(BddVariable(it))))
+            .collect()
     }
 
     /// Get a value of a specific BDD variable in this valuation.
     pub fn value(&self, variable: BddVariable) -> bool {
-        self.0[variable.0 as usize]
+        let (word, mask) = self.bit_checked(variable);
+        self.words[word] & mask != 0
     }
 
     /// Number of variables in this valuation (used mostly for consistency checks).
     pub fn num_vars(&self) -> u16 {
-        self.0.len() as u16
+        self.num_vars
     }
 
     /// Returns true if the values set in this valuation match the values fixed in the
@@ -76,16 +171,13 @@ impl BddValuation {
     ///
     /// In other words `this >= valuation` in terms of specificity.
     pub fn extends(&self, valuation: &BddPartialValuation) -> bool {
-        for var_id in 0..self.num_vars() {
-            let var = BddVariable(var_id);
-            if let Some(value) = valuation.get_value(var) {
-                if value != self.value(var) {
-                    return false;
-                }
-            }
-        }
-
-        true
+        // Variables beyond `num_vars` are ignored.
+        valuation
+            .fixed
+            .iter()
+            .zip(&valuation.values)
+            .zip(&self.words)
+            .all(|((fixed, values), words)| (words ^ values) & fixed == 0)
     }
 
     /// **(internal)** "Increment" this valuation if possible. Interpret the valuation as bit-vector and
@@ -97,26 +189,24 @@ impl BddValuation {
     /// `clause` (i.e. the valuation and clause are mutually compatible) and **panics** if
     /// inconsistencies are found.
     pub(crate) fn next(&self, clause: &BddPartialValuation) -> Option<BddValuation> {
+        assert!(self.extends(clause), "Valuation does not match the clause.");
         let mut result = self.clone();
         let mut carry = true; // first value needs to be incremented
-
-        for var_id in 0..self.num_vars() {
-            let variable = BddVariable(var_id);
-
-            if clause.has_value(variable) {
-                // Do not increment variables that are fixed.
-                assert_eq!(clause.get_value(variable), Some(self.value(variable)));
-                continue;
+        let last = self.words.len();
+        for (i, word) in result.words.iter_mut().enumerate() {
+            // Fixed bits (and unused bits of the last word) are set to one, such that
+            // the carry propagates through them.
+            let mut fixed = clause.fixed.get(i).cloned().unwrap_or(0);
+            #[allow(clippy::manual_is_multiple_of)] // `is_multiple_of` requires Rust 1.87.
+            if i + 1 == last && self.num_vars % 64 != 0 {
+                fixed |= u64::MAX << (self.num_vars % 64);
             }
-
-            let new_value = self.value(variable) ^ carry;
-            let new_carry = self.value(variable) && carry;
-
-            result.set_value(variable, new_value);
-            carry = new_carry;
-            if !new_carry {
+            let (incremented, overflow) = (*word | fixed).overflowing_add(u64::from(carry));
+            *word = (incremented & !fixed) | (*word & fixed);
+            carry = overflow;
+            if !carry {
                 break;
-            } // No need to continue incrementing.
+            }
         }
 
         if carry {
@@ -125,20 +215,69 @@ impl BddValuation {
             Some(result)
         }
     }
+
+    /// **(internal)** Bit position of a variable, panicking if it is out of bounds.
+    fn bit_checked(&self, variable: BddVariable) -> (usize, u64) {
+        if variable.0 >= self.num_vars {
+            panic!(
+                "Variable {} is out of bounds ({} variables).",
+                variable, self.num_vars
+            );
+        }
+        bit_of(variable)
+    }
+
+    /// **(internal)** Reset the bits of the last word that do not correspond to any variable.
+    fn clear_unused_bits(&mut self) {
+        let rest = self.num_vars % 64;
+        if rest != 0 {
+            if let Some(last) = self.words.last_mut() {
+                *last &= !(u64::MAX << rest);
+            }
+        }
+    }
 }
 
-impl Display for BddValuation {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-        if self.0.is_empty() {
-            write!(f, "[]")?;
-        } else {
-            write!(f, "[{}", i32::from(self.0[0]))?;
-            for i in 1..self.0.len() {
-                write!(f, ",{}", i32::from(self.0[i]))?
+/// The ordering is lexicographic in the variable order (i.e. the same as for `Vec<bool>`).
+impl Ord for BddValuation {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let common = usize::from(self.num_vars.min(other.num_vars));
+        for (i, (a, b)) in self.words.iter().zip(&other.words).enumerate() {
+            let difference = a ^ b;
+            if difference != 0 {
+                let bit = difference.trailing_zeros() as usize;
+                if i * 64 + bit >= common {
+                    break;
+                }
+                return if a & (1u64 << bit) == 0 {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
             }
-            write!(f, "]")?;
         }
-        Ok(())
+        self.num_vars.cmp(&other.num_vars)
+    }
+}
+
+impl PartialOrd for BddValuation {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Debug for BddValuation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "BddValuation({})", self)
+    }
+}
+
+impl Display for BddValuation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        let values: Vec<String> = (0..self.num_vars)
+            .map(|it| i32::from(self.value(BddVariable(it))).to_string())
+            .collect();
+        write!(f, "[{}]", values.join(","))
     }
 }
 
@@ -147,13 +286,11 @@ impl Index<BddVariable> for BddValuation {
     type Output = bool;
 
     fn index(&self, index: BddVariable) -> &Self::Output {
-        &self.0[usize::from(index.0)]
-    }
-}
-
-impl IndexMut<BddVariable> for BddValuation {
-    fn index_mut(&mut self, index: BddVariable) -> &mut Self::Output {
-        &mut self.0[usize::from(index.0)]
+        if self.value(index) {
+            &true
+        } else {
+            &false
+        }
     }
 }
 
@@ -161,21 +298,26 @@ impl IndexMut<BddVariable> for BddValuation {
 impl Bdd {
     /// Evaluate this `Bdd` in a specified `BddValuation`.
     pub fn eval_in(&self, valuation: &BddValuation) -> bool {
-        debug_assert!(
-            valuation.num_vars() == self.num_vars(),
-            "Incompatible variable count."
-        );
-        let mut node = self.root_pointer();
-        while !node.is_terminal() {
-            let var = self.var_of(node);
-            node = if valuation[var] {
-                self.high_link_of(node)
-            } else {
-                self.low_link_of(node)
-            }
+        eval_in(self, valuation)
+    }
+}
+
+/// **(internal)** Shared implementation of `Bdd::eval_in` and `BddRef::eval_in`.
+pub(crate) fn eval_in<B: BddNodes>(bdd: &B, valuation: &BddValuation) -> bool {
+    debug_assert!(
+        valuation.num_vars() == bdd.num_vars(),
+        "Incompatible variable count."
+    );
+    let mut node = bdd.root_pointer();
+    while !node.is_terminal() {
+        let var = bdd.var_of(node);
+        node = if valuation[var] {
+            bdd.high_link_of(node)
+        } else {
+            bdd.low_link_of(node)
         }
-        node.is_one()
     }
+    node.is_one()
 }
 
 /// Convert a BddValuation to a Bdd with, well, exactly that one valuation.
@@ -201,13 +343,20 @@ impl From<BddValuation> for Bdd {
     }
 }
 
-/// If possible, convert the given partial valuation to valuation with the same
-/// number of variables. Partial valuation must contain values of all variables.
+/// Convert a `BddPartialValuation` where all variables up to the last fixed variable are
+/// fixed into a `BddValuation` (with the last fixed variable being the last variable of the
+/// valuation). Otherwise, return `Err`.
+///
+/// *Breaking change:* When partial valuations were stored as `Vec<Option<bool>>`,
+/// the number of variables was given by the length of the vector, so a partial valuation
+/// with an unset trailing variable (e.g. after `unset_value`) could not be converted.
+/// Such trailing variables are now ignored.
 impl TryFrom<BddPartialValuation> for BddValuation {
     type Error = ();
 
     fn try_from(value: BddPartialValuation) -> Result<Self, Self::Error> {
-        let mut result = BddValuation::all_false(value.0.len() as u16);
+        let num_vars = value.last_fixed_variable().map(|it| it.0 + 1).unwrap_or(0);
+        let mut result = BddValuation::all_false(num_vars);
         for var_id in 0..result.num_vars() {
             let var = BddVariable(var_id);
             if let Some(value) = value.get_value(var) {
@@ -253,10 +402,10 @@ mod tests {
         let v1 = universe.mk_var_by_name("x_0");
         let v2 = universe.mk_var_by_name("x_1");
         let bdd = bdd!(v1 & (!v2));
-        assert_eq!(true, bdd.eval_in(&BddValuation::new(vec![true, false])));
-        assert_eq!(false, bdd.eval_in(&BddValuation::new(vec![true, true])));
-        assert_eq!(false, bdd.eval_in(&BddValuation::new(vec![false, false])));
-        assert_eq!(false, bdd.eval_in(&BddValuation::new(vec![false, false])));
+        assert!(bdd.eval_in(&BddValuation::new(vec![true, false])));
+        assert!(!bdd.eval_in(&BddValuation::new(vec![true, true])));
+        assert!(!bdd.eval_in(&BddValuation::new(vec![false, false])));
+        assert!(!bdd.eval_in(&BddValuation::new(vec![false, false])));
     }
 
     #[test]
@@ -301,4 +450,77 @@ mod tests {
 
         assert!(bdd_2.iff(&bdd).is_true());
     }
+
+    #[test]
+    fn valuation_packing() {
+        let mut values = vec![false; 130];
+        for i in [0, 5, 63, 64, 100, 129] {
+            values[i] = true;
+        }
+        let valuation = BddValuation::new(values.clone());
+        assert_eq!(130, valuation.num_vars());
+        assert_eq!(3, valuation.as_words().len());
+        assert_eq!(6, valuation.count_ones());
+        assert!(valuation[BddVariable(64)] && !valuation[BddVariable(65)]);
+        assert_eq!(values, valuation.clone().vector());
+        assert_eq!(
+            valuation,
+            BddValuation::from_words(130, valuation.as_words())
+        );
+        assert_eq!(None, valuation.to_u64());
+
+        let all = BddValuation::all_true(130);
+        assert_eq!(130, all.count_ones());
+        assert_eq!(124, all.hamming_distance(&valuation));
+        assert_eq!(all, BddValuation::from_words(130, &[u64::MAX; 3]));
+
+        let small = BddValuation::from_u64(4, 0b1101_0110);
+        assert_eq!(BddValuation::new(vec![false, true, true, false]), small);
+        assert_eq!(Some(0b0110), small.to_u64());
+    }
+
+    #[test]
+    fn valuation_ordering() {
+        let vectors = vec![
+            vec![],
+            vec![false],
+            vec![true],
+            vec![false, true],
+            vec![true, false, false],
+            vec![true, false],
+            vec![false; 70],
+            [vec![false; 69], vec![true]].concat(),
+        ];
+        for a in &vectors {
+            for b in &vectors {
+                let (x, y) = (BddValuation::new(a.clone()), BddValuation::new(b.clone()));
+                assert_eq!(a.cmp(b), x.cmp(&y));
+            }
+        }
+    }
+
+    #[test]
+    fn valuation_increment() {
+        let clause =
+            BddPartialValuation::from_values(&[(BddVariable(1), true), (BddVariable(65), false)]);
+        let mut valuation = BddValuation::all_false(67);
+        valuation.set(BddVariable(1));
+        let mut count = 1;
+        while let Some(next) = valuation.next(&clause) {
+            assert!(next.extends(&clause));
+            valuation = next;
+            count += 1;
+            if count > 4 {
+                break;
+            }
+        }
+        // Free variables (0, 2, 3, ...) encode the number 4.
+        let expected = BddValuation::from_words(67, &[0b1010, 0]);
+        assert_eq!(expected, valuation);
+
+        // The last valuation has all free variables set.
+        let mut last = BddValuation::all_true(67);
+        last.clear(BddVariable(65));
+        assert_eq!(None, last.next(&clause));
+    }
 }
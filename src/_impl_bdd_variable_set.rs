@@ -1,5 +1,5 @@
 use super::*;
-use std::convert::TryFrom;
+use crate::_impl_budget::{unwrap_unlimited, Tracker, Unlimited};
 
 impl BddVariableSet {
     /// Create a new `BddVariableSet` with anonymous variables $(x_0, \ldots, x_n)$ where $n$ is
@@ -15,6 +15,8 @@ impl BddVariableSet {
             num_vars,
             var_names: (0..num_vars).map(|i| format!("x_{}", i)).collect(),
             var_index_mapping: (0..num_vars).map(|i| (format!("x_{}", i), i)).collect(),
+            var_kinds: vec![None; usize::from(num_vars)],
+            var_groups: HashMap::new(),
         }
     }
 
@@ -49,6 +51,58 @@ impl BddVariableSet {
         self.var_names[variable.0 as usize].clone()
     }
 
+    /// The kind of the given `variable`, if it was set (see `BddVariableSetBuilder::set_kind`).
+    pub fn kind_of(&self, variable: BddVariable) -> Option<&BddVariableKind> {
+        self.var_kinds[variable.to_index()].as_ref()
+    }
+
+    /// All variables of the given `kind`.
+    pub fn variables_of_kind(&self, kind: &BddVariableKind) -> Vec<BddVariable> {
+        self.variables()
+            .into_iter()
+            .filter(|it| self.kind_of(*it) == Some(kind))
+            .collect()
+    }
+
+    /// The (sorted) variables of the group with the given `name`, or `None` if no such group
+    /// exists (see `BddVariableSetBuilder::make_group`).
+    ///
+    /// The result can be used directly in methods like `Bdd::exists` or `Bdd::pick`.
+    pub fn group(&self, name: &str) -> Option<Vec<BddVariable>> {
+        self.var_groups.get(name).cloned()
+    }
+
+    /// The (sorted) names of all variable groups.
+    pub fn group_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.var_groups.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// The (sorted) names of the groups which contain the given `variable`.
+    pub fn groups_of(&self, variable: BddVariable) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .var_groups
+            .iter()
+            .filter(|(_, group)| group.contains(&variable))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Create a `BddVariableSetBuilder` which contains the variables of this set, including
+    /// their kinds and groups.
+    pub fn to_builder(&self) -> BddVariableSetBuilder {
+        let mut builder = BddVariableSetBuilder::new();
+        for name in &self.var_names {
+            builder.make_variable(name);
+        }
+        builder.var_kinds = self.var_kinds.clone();
+        builder.var_groups = self.var_groups.clone();
+        builder
+    }
+
     /// Create a `Bdd` corresponding to the `true` formula.
     pub fn mk_true(&self) -> Bdd {
         Bdd::mk_true(self.num_vars)
@@ -114,24 +168,19 @@ impl BddVariableSet {
         let mut result = self.mk_true();
         // It is important to iterate in this direction, otherwise we are going to mess with
         // variable ordering.
-        for (index, value) in clause.0.iter().enumerate().rev() {
-            if let Some(value) = value {
-                assert!(index < self.num_vars as usize);
-                // This is safe because valuation cannot contain larger indices due to the way
-                // it is constructed.
-                debug_assert!(u16::try_from(index).is_ok());
-                let variable = BddVariable(index as u16);
-
-                let node = if *value {
-                    // Value is true, so high link "continues", and low link goes to zero.
-                    BddNode::mk_node(variable, BddPointer::zero(), result.root_pointer())
-                } else {
-                    // Value is false, so low link "continues", and high link goes to zero.
-                    BddNode::mk_node(variable, result.root_pointer(), BddPointer::zero())
-                };
-
-                result.push_node(node);
-            }
+        let literals = clause.to_values();
+        for (variable, value) in literals.into_iter().rev() {
+            assert!(variable.0 < self.num_vars);
+
+            let node = if value {
+                // Value is true, so high link "continues", and low link goes to zero.
+                BddNode::mk_node(variable, BddPointer::zero(), result.root_pointer())
+            } else {
+                // Value is false, so low link "continues", and high link goes to zero.
+                BddNode::mk_node(variable, result.root_pointer(), BddPointer::zero())
+            };
+
+            result.push_node(node);
         }
 
         result
@@ -155,21 +204,18 @@ impl BddVariableSet {
         // zero as the root instead of one. So we use a variable which is pre-set in the
         // first iteration but will evaluate to real root in later iterations.
         let mut shadow_root = BddPointer::zero();
-        for (index, value) in clause.0.iter().enumerate().rev() {
-            if let Some(value) = value {
-                assert!(index < self.num_vars as usize);
-                debug_assert!(u16::try_from(index).is_ok());
-                let variable = BddVariable(index as u16);
-
-                let node = if *value {
-                    BddNode::mk_node(variable, shadow_root, BddPointer::one())
-                } else {
-                    BddNode::mk_node(variable, BddPointer::one(), shadow_root)
-                };
-
-                result.push_node(node);
-                shadow_root = result.root_pointer();
-            }
+        let literals = clause.to_values();
+        for (variable, value) in literals.into_iter().rev() {
+            assert!(variable.0 < self.num_vars);
+
+            let node = if value {
+                BddNode::mk_node(variable, shadow_root, BddPointer::one())
+            } else {
+                BddNode::mk_node(variable, BddPointer::one(), shadow_root)
+            };
+
+            result.push_node(node);
+            shadow_root = result.root_pointer();
         }
 
         result
@@ -189,35 +235,43 @@ impl BddVariableSet {
         Bdd::mk_dnf(self.num_vars, dnf)
     }
 
+    /// Same as `BddVariableSet::mk_cnf`, but the construction is aborted once the given
+    /// `budget` is exceeded.
+    pub fn mk_cnf_with_budget(
+        &self,
+        cnf: &[BddPartialValuation],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        Bdd::mk_cnf_with_tracker(self.num_vars, cnf, &mut budget.start())
+    }
+
+    /// Same as `BddVariableSet::mk_dnf`, but the construction is aborted once the given
+    /// `budget` is exceeded.
+    pub fn mk_dnf_with_budget(
+        &self,
+        dnf: &[BddPartialValuation],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        Bdd::mk_dnf_with_tracker(self.num_vars, dnf, &mut budget.start())
+    }
+
     /// Build a BDD that is satisfied by all valuations where *up to* $k$ `variables` are `true`.
     ///
     /// Intuitively, this implements a "threshold function" $f(x) = (\sum_{i} x_i \leq k)$
     /// over the given `variables`.
     pub fn mk_sat_up_to_k(&self, k: usize, variables: &[BddVariable]) -> Bdd {
-        // This is the same as sat_exactly_k, we just carry the k-1 result over to the next round.
-        let mut valuation = BddPartialValuation::empty();
-        for var in variables {
-            valuation.set_value(*var, false);
-        }
-        let mut result = self.mk_conjunctive_clause(&valuation);
-        for _i in 0..k {
-            let mut result_plus_one = result.clone();
-            for var in variables {
-                let var_is_false = self.mk_not_var(*var);
-                // result = result | flip(var, k_minus_one and var_is_false)
-                let propagate = Bdd::fused_binary_flip_op(
-                    (&result, None),
-                    (&var_is_false, None),
-                    Some(*var),
-                    op_function::and,
-                );
-                result_plus_one = result_plus_one.or(&propagate);
-            }
-
-            result = result_plus_one
-        }
+        unwrap_unlimited(self.mk_sat_k_with_tracker(k, variables, true, &mut Unlimited))
+    }
 
-        result
+    /// Same as `BddVariableSet::mk_sat_up_to_k`, but the construction is aborted once
+    /// the given `budget` is exceeded.
+    pub fn mk_sat_up_to_k_with_budget(
+        &self,
+        k: usize,
+        variables: &[BddVariable],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        self.mk_sat_k_with_tracker(k, variables, true, &mut budget.start())
     }
 
     /// Build a BDD that is satisfied by all valuations where *exactly* $k$ `variables` are `true`.
@@ -225,30 +279,65 @@ impl BddVariableSet {
     /// Intuitively, this implements a "equality function" $f(x) = (\sum_{i} x_i = k)$
     /// over the given `variables`.
     pub fn mk_sat_exactly_k(&self, k: usize, variables: &[BddVariable]) -> Bdd {
-        // This is based on the recursion SAT_k = \cup_{v} SAT_{k-1}[flip v].
+        unwrap_unlimited(self.mk_sat_k_with_tracker(k, variables, false, &mut Unlimited))
+    }
+
+    /// Same as `BddVariableSet::mk_sat_exactly_k`, but the construction is aborted once
+    /// the given `budget` is exceeded.
+    pub fn mk_sat_exactly_k_with_budget(
+        &self,
+        k: usize,
+        variables: &[BddVariable],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        self.mk_sat_k_with_tracker(k, variables, false, &mut budget.start())
+    }
+
+    /// **(internal)** Implementation of `mk_sat_up_to_k` (when `up_to` is set)
+    /// and `mk_sat_exactly_k` which uses the given `tracker`.
+    fn mk_sat_k_with_tracker<K: Tracker>(
+        &self,
+        k: usize,
+        variables: &[BddVariable],
+        up_to: bool,
+        tracker: &mut K,
+    ) -> Result<Bdd, BudgetExceeded> {
+        // This is based on the recursion SAT_k = \cup_{v} SAT_{k-1}[flip v]. For `up_to`,
+        // we just carry the k-1 result over to the next round.
         let mut valuation = BddPartialValuation::empty();
         for var in variables {
             valuation.set_value(*var, false);
         }
         let mut result = self.mk_conjunctive_clause(&valuation);
         for _i in 0..k {
-            let mut result_plus_one = self.mk_false();
+            let mut result_plus_one = if up_to {
+                result.clone()
+            } else {
+                self.mk_false()
+            };
             for var in variables {
                 let var_is_false = self.mk_not_var(*var);
                 // result = result | flip(var, k_minus_one and var_is_false)
-                let propagate = Bdd::fused_binary_flip_op(
+                let propagate = Bdd::fused_binary_flip_op_with_tracker(
                     (&result, None),
                     (&var_is_false, None),
                     Some(*var),
                     op_function::and,
-                );
-                result_plus_one = result_plus_one.or(&propagate);
+                    tracker,
+                )?;
+                result_plus_one = Bdd::fused_binary_flip_op_with_tracker(
+                    (&result_plus_one, None),
+                    (&propagate, None),
+                    None,
+                    op_function::or,
+                    tracker,
+                )?;
             }
 
             result = result_plus_one
         }
 
-        result
+        Ok(result)
     }
 
     /// This function takes a [Bdd] `bdd` together with its [BddVariableSet] `ctx` and attempts
@@ -321,6 +410,104 @@ impl BddVariableSet {
 
         Some(new_bdd)
     }
+
+    /// Create a new `BddVariableSet` which contains all variables of this set (including
+    /// their kinds and groups), followed by new variables with the given `names`.
+    ///
+    /// The existing variables keep their `BddVariable` identifiers, so a `Bdd` from this set
+    /// can be lifted into the extended set using `transfer_from` (which never fails in this
+    /// case and does not change the structure of the `Bdd`).
+    ///
+    /// *Panics:* The new names must be unique and allowed as variable names.
+    pub fn extend(&self, names: &[&str]) -> BddVariableSet {
+        let mut builder = self.to_builder();
+        builder.make_variables(names);
+        builder.build()
+    }
+
+    /// Create a new `BddVariableSet` which contains the variables of this and the `other` set,
+    /// matched by name.
+    ///
+    /// The ordering of the result is compatible with both sets, so `transfer_from` can be used
+    /// to move a `Bdd` from either set into the merged set. Variables that only appear in this
+    /// set are placed before variables that only appear in the `other` set. Returns `None` if
+    /// the shared variables are not ordered in the same way in both sets.
+    ///
+    /// The groups of both sets are merged as well. If a variable has a kind in both sets,
+    /// the kind from this set is used.
+    pub fn merge(&self, other: &BddVariableSet) -> Option<BddVariableSet> {
+        let shared_in_self: Vec<&String> = self
+            .var_names
+            .iter()
+            .filter(|it| other.var_index_mapping.contains_key(*it))
+            .collect();
+        let shared_in_other: Vec<&String> = other
+            .var_names
+            .iter()
+            .filter(|it| self.var_index_mapping.contains_key(*it))
+            .collect();
+        if shared_in_self != shared_in_other {
+            return None;
+        }
+
+        let mut names = Vec::new();
+        let mut other_names = other.var_names.iter().peekable();
+        for name in &self.var_names {
+            if other.var_index_mapping.contains_key(name) {
+                // Output all exclusive variables of `other` that precede this shared variable.
+                for other_name in other_names.by_ref() {
+                    if other_name == name {
+                        break;
+                    }
+                    names.push(other_name.clone());
+                }
+            }
+            names.push(name.clone());
+        }
+        names.extend(other_names.cloned());
+
+        let mut builder = BddVariableSetBuilder::new();
+        for name in &names {
+            builder.make_variable(name);
+        }
+        builder.copy_metadata(other);
+        builder.copy_metadata(self);
+        Some(builder.build())
+    }
+
+    /// Create a new `BddVariableSet` which contains only the given `variables` of this set
+    /// (in the order of this set, regardless of the order in `variables`). The kinds are
+    /// preserved and the groups are restricted to the remaining variables.
+    ///
+    /// Use `project_from` to move a `Bdd` from this set into the slice.
+    ///
+    /// *Panics:* `variables` must be valid variables in this set.
+    pub fn slice(&self, variables: &[BddVariable]) -> BddVariableSet {
+        let mut variables = variables.to_vec();
+        variables.sort();
+        variables.dedup();
+        let mut builder = BddVariableSetBuilder::new();
+        for variable in variables {
+            builder.make_variable(&self.name_of(variable));
+        }
+        builder.copy_metadata(self);
+        builder.build()
+    }
+
+    /// Project the given `bdd` from the `ctx` set into this set: the variables of `ctx` that
+    /// do not appear in this set are existentially quantified, and the result is then
+    /// transferred using `transfer_from`.
+    ///
+    /// Returns `None` if the remaining variables cannot be transferred (see `transfer_from`).
+    /// This never happens when this set is a `slice` of `ctx`.
+    pub fn project_from(&self, bdd: &Bdd, ctx: &BddVariableSet) -> Option<Bdd> {
+        let removed: Vec<BddVariable> = ctx
+            .variables()
+            .into_iter()
+            .filter(|it| self.var_by_name(&ctx.name_of(*it)).is_none())
+            .collect();
+        self.transfer_from(&bdd.exists(&removed), ctx)
+    }
 }
 
 impl FromIterator<String> for BddVariableSet {
@@ -565,4 +752,136 @@ mod tests {
         let f1 = ctx_1.eval_expression_string("a & y | !c");
         assert_eq!(None, ctx_2.transfer_from(&f1, &ctx_1));
     }
+
+    #[test]
+    fn bdd_variable_set_extend() {
+        let ctx = BddVariableSet::new(&["a", "b"]);
+        let extended = ctx.extend(&["a'", "b'"]);
+        assert_eq!(4, extended.num_vars());
+        assert_eq!(Some(BddVariable(1)), extended.var_by_name("b"));
+        assert_eq!(Some(BddVariable(3)), extended.var_by_name("b'"));
+
+        let f = ctx.eval_expression_string("a & !b");
+        let lifted = extended.transfer_from(&f, &ctx).unwrap();
+        assert_eq!(extended.eval_expression_string("a & !b"), lifted);
+        assert_eq!(f.size(), lifted.size());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_variable_set_extend_duplicate() {
+        BddVariableSet::new(&["a", "b"]).extend(&["b"]);
+    }
+
+    #[test]
+    fn bdd_variable_set_merge() {
+        let ctx_1 = BddVariableSet::new(&["a", "b", "x", "c"]);
+        let ctx_2 = BddVariableSet::new(&["y", "a", "x", "z", "c", "w"]);
+        let merged = ctx_1.merge(&ctx_2).unwrap();
+        let names: Vec<String> = merged
+            .variables()
+            .into_iter()
+            .map(|it| merged.name_of(it))
+            .collect();
+        assert_eq!(vec!["y", "a", "b", "x", "z", "c", "w"], names);
+
+        let f1 = ctx_1.eval_expression_string("a & b | !c");
+        let f2 = ctx_2.eval_expression_string("(y <=> z) & x");
+        assert_eq!(
+            merged.eval_expression_string("a & b | !c"),
+            merged.transfer_from(&f1, &ctx_1).unwrap()
+        );
+        assert_eq!(
+            merged.eval_expression_string("(y <=> z) & x"),
+            merged.transfer_from(&f2, &ctx_2).unwrap()
+        );
+
+        let ctx_3 = BddVariableSet::new(&["c", "a"]);
+        assert!(ctx_1.merge(&ctx_3).is_none());
+        assert_eq!(ctx_1.var_names, ctx_1.merge(&ctx_1).unwrap().var_names);
+    }
+
+    #[test]
+    fn bdd_variable_set_slice() {
+        let ctx = BddVariableSet::new(&["a", "b", "c", "d"]);
+        let [a, b, c, d] = <[BddVariable; 4]>::try_from(ctx.variables()).unwrap();
+        let slice = ctx.slice(&[d, b]);
+        assert_eq!(vec!["b".to_string(), "d".to_string()], slice.var_names);
+
+        let f = ctx.eval_expression_string("(a & b) | (c & !d)");
+        assert_eq!(
+            slice.eval_expression_string("b | !d"),
+            slice.project_from(&f, &ctx).unwrap()
+        );
+        let g = ctx.mk_var(a).and(&ctx.mk_var(c));
+        assert!(slice.project_from(&g, &ctx).unwrap().is_true());
+    }
+
+    #[test]
+    fn bdd_variable_set_metadata() {
+        let mut builder = BddVariableSetBuilder::new();
+        let x = builder.make_variable_of_kind("x", BddVariableKind::State);
+        let y = builder.make_variable_of_kind("y", BddVariableKind::State);
+        let p = builder.make_variable_of_kind("p", BddVariableKind::Parameter);
+        let q = builder.make_variable_of_kind("q", BddVariableKind::Parameter);
+        builder.make_group("inputs", &[p, x]);
+        let ctx = builder.build();
+
+        assert_eq!(vec![x, y], ctx.variables_of_kind(&BddVariableKind::State));
+        assert_eq!(
+            vec![p, q],
+            ctx.variables_of_kind(&BddVariableKind::Parameter)
+        );
+        assert!(ctx.variables_of_kind(&BddVariableKind::Primed).is_empty());
+        assert_eq!(vec!["inputs".to_string()], ctx.group_names());
+        assert_eq!(vec!["inputs".to_string()], ctx.groups_of(p));
+        assert!(ctx.groups_of(q).is_empty());
+        assert_eq!(None, ctx.group("outputs"));
+
+        // Groups can be used directly in `Bdd` operations.
+        let f = ctx.eval_expression_string("(x & p) | (y & !q)");
+        let parameters = ctx.variables_of_kind(&BddVariableKind::Parameter);
+        assert_eq!(ctx.eval_expression_string("x | y"), f.exists(&parameters));
+        let inputs = ctx.group("inputs").unwrap();
+        assert_eq!(ctx.eval_expression_string("y & !q"), f.for_all(&inputs));
+
+        // Extend keeps the metadata.
+        let mut builder = ctx.to_builder();
+        let x_primed = builder.make_variable_of_kind("x'", BddVariableKind::Primed);
+        builder.make_group("inputs", &[x_primed]);
+        let extended = builder.build();
+        assert_eq!(vec![x, p, x_primed], extended.group("inputs").unwrap());
+        let extended = ctx.extend(&["z"]);
+        assert_eq!(Some(&BddVariableKind::State), extended.kind_of(y));
+        assert_eq!(None, extended.kind_of(BddVariable(4)));
+        assert_eq!(ctx.group("inputs"), extended.group("inputs"));
+
+        // Slice restricts the groups.
+        let slice = ctx.slice(&[x, q]);
+        assert_eq!(Some(vec![BddVariable(0)]), slice.group("inputs"));
+        assert_eq!(
+            Some(&BddVariableKind::Parameter),
+            slice.kind_of(BddVariable(1))
+        );
+
+        // Merge combines the groups and prefers kinds of the first set.
+        let mut builder = BddVariableSetBuilder::new();
+        let [a, p_other] = builder.make(&["a", "p"]);
+        builder.set_kind(p_other, BddVariableKind::Auxiliary);
+        builder.set_kind(a, BddVariableKind::Other("input".to_string()));
+        builder.make_group("inputs", &[a, p_other]);
+        let other = builder.build();
+        let merged = ctx.merge(&other).unwrap();
+        let merged_a = merged.var_by_name("a").unwrap();
+        let merged_p = merged.var_by_name("p").unwrap();
+        let merged_x = merged.var_by_name("x").unwrap();
+        assert_eq!(Some(&BddVariableKind::Parameter), merged.kind_of(merged_p));
+        assert_eq!(
+            Some(&BddVariableKind::Other("input".to_string())),
+            merged.kind_of(merged_a)
+        );
+        let mut expected = vec![merged_a, merged_p, merged_x];
+        expected.sort();
+        assert_eq!(Some(expected), merged.group("inputs"));
+    }
 }
@@ -8,6 +8,8 @@ impl BddVariableSetBuilder {
         BddVariableSetBuilder {
             var_names: Vec::new(),
             var_names_set: HashSet::new(),
+            var_kinds: Vec::new(),
+            var_groups: HashMap::new(),
         }
     }
 
@@ -37,9 +39,81 @@ impl BddVariableSetBuilder {
         }
         self.var_names_set.insert(name.to_string());
         self.var_names.push(name.to_string());
+        self.var_kinds.push(None);
         BddVariable(new_variable_id as u16)
     }
 
+    /// Same as `make_variable`, but also sets the `kind` of the new variable.
+    pub fn make_variable_of_kind(&mut self, name: &str, kind: BddVariableKind) -> BddVariable {
+        let variable = self.make_variable(name);
+        self.set_kind(variable, kind);
+        variable
+    }
+
+    /// Set the `kind` of an existing `variable`.
+    ///
+    /// *Panics:* The `variable` must have been created by this builder.
+    pub fn set_kind(&mut self, variable: BddVariable, kind: BddVariableKind) {
+        self.check_variable(variable);
+        self.var_kinds[variable.to_index()] = Some(kind);
+    }
+
+    /// Add the given `variables` into the group with the given `name` (the group is created
+    /// if it does not exist yet). A variable can belong to any number of groups.
+    ///
+    /// *Panics:* The `variables` must have been created by this builder.
+    pub fn make_group(&mut self, name: &str, variables: &[BddVariable]) {
+        for variable in variables {
+            self.check_variable(*variable);
+        }
+        let group = self.var_groups.entry(name.to_string()).or_default();
+        group.extend_from_slice(variables);
+        group.sort();
+        group.dedup();
+    }
+
+    /// **(internal)** Copy the kinds and groups of the variables in `source` to the variables
+    /// with the same name in this builder (other variables are ignored). Existing kinds are
+    /// overwritten and groups are extended.
+    pub(crate) fn copy_metadata(&mut self, source: &BddVariableSet) {
+        let index: HashMap<&str, BddVariable> = self
+            .var_names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), BddVariable::from_index(i)))
+            .collect();
+        let translate = |variable: &BddVariable| -> Option<BddVariable> {
+            index
+                .get(source.var_names[variable.to_index()].as_str())
+                .cloned()
+        };
+        let kinds: Vec<(BddVariable, BddVariableKind)> = source
+            .variables()
+            .iter()
+            .filter_map(|it| Some((translate(it)?, source.kind_of(*it)?.clone())))
+            .collect();
+        let groups: Vec<(String, Vec<BddVariable>)> = source
+            .var_groups
+            .iter()
+            .map(|(name, group)| (name.clone(), group.iter().filter_map(translate).collect()))
+            .collect();
+        for (variable, kind) in kinds {
+            self.set_kind(variable, kind);
+        }
+        for (name, group) in groups {
+            if !group.is_empty() {
+                self.make_group(&name, &group);
+            }
+        }
+    }
+
+    /// **(internal)** Panic if the `variable` was not created by this builder.
+    fn check_variable(&self, variable: BddVariable) {
+        if variable.to_index() >= self.var_names.len() {
+            panic!("Variable {} is not known in this builder.", variable);
+        }
+    }
+
     /// A more convenient version of `make_variables` which allows irrefutable pattern matching
     /// on the result, because it is an array instead of a vector.
     pub fn make<const X: usize>(&mut self, names: &[&str; X]) -> [BddVariable; X] {
@@ -62,6 +136,8 @@ impl BddVariableSetBuilder {
             num_vars: self.var_names.len() as u16,
             var_names: self.var_names,
             var_index_mapping: mapping,
+            var_kinds: self.var_kinds,
+            var_groups: self.var_groups,
         }
     }
 }
@@ -125,4 +201,28 @@ mod tests {
         let mut builder = BddVariableSetBuilder::new();
         builder.make_variable("a^b");
     }
+
+    #[test]
+    fn bdd_variables_builder_metadata() {
+        let mut builder = BddVariableSetBuilder::new();
+        let x = builder.make_variable_of_kind("x", BddVariableKind::State);
+        let p = builder.make_variable("p");
+        let y = builder.make_variable("y");
+        builder.set_kind(p, BddVariableKind::Parameter);
+        builder.make_group("network", &[y, x]);
+        builder.make_group("network", &[x]);
+        let variables = builder.build();
+        assert_eq!(Some(&BddVariableKind::State), variables.kind_of(x));
+        assert_eq!(Some(&BddVariableKind::Parameter), variables.kind_of(p));
+        assert_eq!(None, variables.kind_of(y));
+        assert_eq!(Some(vec![x, y]), variables.group("network"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_variables_builder_invalid_group() {
+        let mut builder = BddVariableSetBuilder::new();
+        builder.make_variable("x");
+        builder.make_group("g", &[BddVariable(1)]);
+    }
 }
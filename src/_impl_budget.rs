@@ -0,0 +1,290 @@
+use crate::{Budget, BudgetExceeded};
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// **(internal)** The deadline and the cancel flag are only checked once every this many steps,
+/// because reading the clock is relatively expensive.
+const CHECK_INTERVAL: usize = 1024;
+
+impl Budget {
+    /// Create a new unlimited budget.
+    pub fn unlimited() -> Budget {
+        Budget::default()
+    }
+
+    /// Limit the number of nodes that can be created by the operation.
+    ///
+    /// Note that for some operations (e.g. quantification), this also includes intermediate
+    /// nodes that do not appear in the final result.
+    pub fn with_node_limit(mut self, max_nodes: usize) -> Budget {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    /// Limit the number of steps (i.e. low-level tasks) of the operation.
+    pub fn with_step_limit(mut self, max_steps: usize) -> Budget {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Abort the operation once the given `deadline` has passed.
+    pub fn with_deadline(mut self, deadline: Instant) -> Budget {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Abort the operation once the given `timeout` (measured from now) has elapsed.
+    pub fn with_timeout(self, timeout: Duration) -> Budget {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Abort the operation once the given `flag` is set to `true`.
+    pub fn with_cancel_flag(mut self, flag: Arc<AtomicBool>) -> Budget {
+        self.cancel_flag = Some(flag);
+        self
+    }
+
+    /// The node limit of this budget (if any).
+    pub fn node_limit(&self) -> Option<usize> {
+        self.max_nodes
+    }
+
+    /// The step limit of this budget (if any).
+    pub fn step_limit(&self) -> Option<usize> {
+        self.max_steps
+    }
+
+    /// The deadline of this budget (if any).
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// True if this budget has a cancel flag which is currently set.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_flag
+            .as_ref()
+            .map(|it| it.load(Ordering::Relaxed))
+            .unwrap_or(false)
+    }
+
+    /// **(internal)** Start tracking the resources of one operation.
+    pub(crate) fn start(&self) -> BudgetTracker<'_> {
+        BudgetTracker {
+            budget: self,
+            steps: 0,
+        }
+    }
+}
+
+impl Display for BudgetExceeded {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            BudgetExceeded::Nodes => "Node limit exceeded.",
+            BudgetExceeded::Steps => "Step limit exceeded.",
+            BudgetExceeded::Deadline => "Deadline exceeded.",
+            BudgetExceeded::Cancelled => "Operation cancelled.",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// **(internal)** Tracks the resources consumed by a single operation with a `Budget`.
+pub(crate) struct BudgetTracker<'a> {
+    budget: &'a Budget,
+    steps: usize,
+}
+
+/// **(internal)** Resource accounting of the long-running algorithms.
+///
+/// The algorithms are generic over this trait: operations without a budget use `Unlimited`,
+/// for which all checks are trivially `Ok` and are completely optimized away by the compiler.
+pub(crate) trait Tracker {
+    /// Record one step of the algorithm.
+    fn step(&mut self) -> Result<(), BudgetExceeded>;
+    /// Check that the operation can still use the given number of nodes.
+    fn check_nodes(&self, nodes: usize) -> Result<(), BudgetExceeded>;
+}
+
+/// **(internal)** A `Tracker` which never stops the operation.
+pub(crate) struct Unlimited;
+
+impl Tracker for Unlimited {
+    #[inline(always)]
+    fn step(&mut self) -> Result<(), BudgetExceeded> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn check_nodes(&self, _nodes: usize) -> Result<(), BudgetExceeded> {
+        Ok(())
+    }
+}
+
+impl Tracker for BudgetTracker<'_> {
+    /// In the first step and then periodically, this also checks the deadline and
+    /// the cancel flag.
+    #[inline]
+    fn step(&mut self) -> Result<(), BudgetExceeded> {
+        self.steps += 1;
+        if let Some(max_steps) = self.budget.max_steps {
+            if self.steps > max_steps {
+                return Err(BudgetExceeded::Steps);
+            }
+        }
+        if self.steps % CHECK_INTERVAL == 1 {
+            self.check_time()?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn check_nodes(&self, nodes: usize) -> Result<(), BudgetExceeded> {
+        match self.budget.max_nodes {
+            Some(max_nodes) if nodes > max_nodes => Err(BudgetExceeded::Nodes),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl BudgetTracker<'_> {
+    /// Check the deadline and the cancel flag.
+    pub(crate) fn check_time(&self) -> Result<(), BudgetExceeded> {
+        if self.budget.is_cancelled() {
+            return Err(BudgetExceeded::Cancelled);
+        }
+        if let Some(deadline) = self.budget.deadline {
+            if Instant::now() > deadline {
+                return Err(BudgetExceeded::Deadline);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// **(internal)** Unwrap the result of an operation which was performed with an unlimited
+/// budget (such operation cannot fail).
+pub(crate) fn unwrap_unlimited<T>(result: Result<T, BudgetExceeded>) -> T {
+    result.unwrap_or_else(|_| unreachable!("Unlimited budget cannot be exceeded."))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_impl_budget::Tracker;
+    use crate::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn budget_tracker() {
+        let budget = Budget::unlimited();
+        let mut tracker = budget.start();
+        for _ in 0..10_000 {
+            tracker.step().unwrap();
+        }
+        tracker.check_nodes(usize::MAX).unwrap();
+
+        let budget = Budget::unlimited().with_step_limit(5).with_node_limit(3);
+        assert_eq!(Some(5), budget.step_limit());
+        assert_eq!(Some(3), budget.node_limit());
+        let mut tracker = budget.start();
+        for _ in 0..5 {
+            tracker.step().unwrap();
+        }
+        assert_eq!(Err(BudgetExceeded::Steps), tracker.step());
+        assert_eq!(Ok(()), tracker.check_nodes(3));
+        assert_eq!(Err(BudgetExceeded::Nodes), tracker.check_nodes(4));
+
+        let flag = Arc::new(AtomicBool::new(false));
+        let budget = Budget::unlimited().with_cancel_flag(flag.clone());
+        assert!(budget.start().check_time().is_ok());
+        flag.store(true, Ordering::Relaxed);
+        assert!(budget.is_cancelled());
+        assert_eq!(Err(BudgetExceeded::Cancelled), budget.start().check_time());
+
+        let budget = Budget::unlimited().with_deadline(Instant::now() - Duration::from_secs(1));
+        assert_eq!(Err(BudgetExceeded::Deadline), budget.start().check_time());
+        let budget = Budget::unlimited().with_timeout(Duration::from_secs(3600));
+        assert!(budget.start().check_time().is_ok());
+        assert_eq!(
+            "Operation cancelled.",
+            BudgetExceeded::Cancelled.to_string()
+        );
+    }
+
+    /// **(internal)** Check that `op` succeeds with an unlimited budget (producing `expected`)
+    /// and fails with a step limit, an expired deadline and a cancelled flag.
+    fn check_budget_op<F>(expected: &Bdd, op: F)
+    where
+        F: Fn(&Budget) -> Result<Bdd, BudgetExceeded>,
+    {
+        assert_eq!(Ok(expected.clone()), op(&Budget::unlimited()));
+        let steps = Budget::unlimited().with_step_limit(1);
+        assert_eq!(Err(BudgetExceeded::Steps), op(&steps));
+        let past = Instant::now() - Duration::from_secs(1);
+        let deadline = Budget::unlimited().with_deadline(past);
+        assert_eq!(Err(BudgetExceeded::Deadline), op(&deadline));
+        let cancelled = Budget::unlimited().with_cancel_flag(Arc::new(AtomicBool::new(true)));
+        assert_eq!(Err(BudgetExceeded::Cancelled), op(&cancelled));
+    }
+
+    #[test]
+    fn budget_public_operations() {
+        let vars = BddVariableSet::new_anonymous(6);
+        let v = vars.variables();
+        let a = vars.eval_expression_string("(x_0 & x_1) | (x_2 ^ x_3) | (x_4 <=> !x_5)");
+        let b = vars.eval_expression_string("(x_0 | !x_2) & (x_3 ^ x_5)");
+        let c = vars.eval_expression_string("x_1 => (x_4 & !x_0)");
+        let and = op_function::and;
+
+        check_budget_op(&a.and(&b), |budget| {
+            Bdd::binary_op_with_budget(&a, &b, and, budget)
+        });
+        check_budget_op(&a.and(&b), |budget| {
+            Bdd::fused_binary_flip_op_with_budget((&a, None), (&b, None), None, and, budget)
+        });
+        check_budget_op(&Bdd::if_then_else(&a, &b, &c), |budget| {
+            let ite = |x: Option<bool>, y: Option<bool>, z: Option<bool>| match x {
+                Some(true) => y,
+                Some(false) => z,
+                None => None,
+            };
+            Bdd::ternary_op_with_budget(&a, &b, &c, ite, budget)
+        });
+        check_budget_op(&a.exists(&v[0..3]), |budget| {
+            a.exists_with_budget(&v[0..3], budget)
+        });
+        check_budget_op(&a.for_all(&v[2..4]), |budget| {
+            a.for_all_with_budget(&v[2..4], budget)
+        });
+        check_budget_op(&a.and(&b).exists(&[v[1]]), |budget| {
+            Bdd::binary_op_with_exists_with_budget(&a, &b, and, &[v[1]], budget)
+        });
+
+        let selection = [(v[0], true), (v[3], false)];
+        check_budget_op(&a.select(&selection), |budget| {
+            a.select_with_budget(&selection, budget)
+        });
+        check_budget_op(&a.restrict(&selection), |budget| {
+            a.restrict_with_budget(&selection, budget)
+        });
+        check_budget_op(&a.pick(&v[1..4]), |budget| {
+            a.pick_with_budget(&v[1..4], budget)
+        });
+        check_budget_op(&vars.mk_sat_up_to_k(2, &v), |budget| {
+            vars.mk_sat_up_to_k_with_budget(2, &v, budget)
+        });
+        check_budget_op(&vars.mk_sat_exactly_k(2, &v), |budget| {
+            vars.mk_sat_exactly_k_with_budget(2, &v, budget)
+        });
+
+        let cnf = a.to_cnf();
+        check_budget_op(&a, |budget| vars.mk_cnf_with_budget(&cnf, budget));
+        let dnf = b.to_dnf();
+        check_budget_op(&b, |budget| vars.mk_dnf_with_budget(&dnf, budget));
+    }
+}
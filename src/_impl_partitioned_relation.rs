@@ -0,0 +1,302 @@
+use crate::_impl_budget::{unwrap_unlimited, Tracker, Unlimited};
+use crate::*;
+
+impl PartitionedRelation {
+    /// Create a new relation from the given list of conjuncts.
+    ///
+    /// *Panics:* All conjuncts must use the same number of variables.
+    pub fn new(conjuncts: Vec<Bdd>) -> PartitionedRelation {
+        if let Some(first) = conjuncts.first() {
+            for conjunct in &conjuncts {
+                if conjunct.num_vars() != first.num_vars() {
+                    panic!(
+                        "Conjuncts have different number of variables ({} vs. {}).",
+                        first.num_vars(),
+                        conjunct.num_vars()
+                    );
+                }
+            }
+        }
+        PartitionedRelation { conjuncts }
+    }
+
+    /// The conjuncts of this relation.
+    pub fn conjuncts(&self) -> &[Bdd] {
+        &self.conjuncts
+    }
+
+    /// Compute the explicit (monolithic) `Bdd` of this relation. This can be very expensive
+    /// and is mostly useful for testing.
+    ///
+    /// *Panics:* The relation must have at least one conjunct (otherwise the number of
+    /// variables is unknown).
+    pub fn to_bdd(&self) -> Bdd {
+        unwrap_unlimited(self.to_bdd_with_tracker(&mut Unlimited))
+    }
+
+    /// Same as `PartitionedRelation::to_bdd`, but the computation is aborted once the given
+    /// `budget` is exceeded. The step limit applies to all conjunctions together, the node
+    /// limit applies to each intermediate result.
+    pub fn to_bdd_with_budget(&self, budget: &Budget) -> Result<Bdd, BudgetExceeded> {
+        self.to_bdd_with_tracker(&mut budget.start())
+    }
+
+    /// **(internal)** Implementation of `to_bdd` which uses the given `tracker`.
+    fn to_bdd_with_tracker<K: Tracker>(&self, tracker: &mut K) -> Result<Bdd, BudgetExceeded> {
+        let Some((first, rest)) = self.conjuncts.split_first() else {
+            panic!("Cannot build an empty partitioned relation.");
+        };
+        let mut result = first.clone();
+        for conjunct in rest {
+            result = Bdd::fused_binary_flip_op_with_tracker(
+                (&result, None),
+                (conjunct, None),
+                None,
+                op_function::and,
+                tracker,
+            )?;
+        }
+        Ok(result)
+    }
+
+    /// Merge neighbouring conjuncts as long as the size of the merged `Bdd` does not
+    /// exceed `max_size`. Larger clusters mean fewer image steps, but each step is
+    /// more expensive.
+    pub fn cluster(&self, max_size: usize) -> PartitionedRelation {
+        let mut result: Vec<Bdd> = Vec::new();
+        for conjunct in &self.conjuncts {
+            if let Some(last) = result.last_mut() {
+                let merged = last.and(conjunct);
+                if merged.size() <= max_size {
+                    *last = merged;
+                    continue;
+                }
+            }
+            result.push(conjunct.clone());
+        }
+        PartitionedRelation { conjuncts: result }
+    }
+
+    /// Compute $\exists V: S \land \bigwedge_i R_i$, where $S$ is the given `set`, $R_i$ are
+    /// the conjuncts of this relation and $V$ are the given `variables`.
+    ///
+    /// Typically, $V$ are the "current state" variables and the result is the image of $S$
+    /// expressed over the "next state" variables. The conjuncts are processed in a greedy
+    /// order based on their support sets, such that variables can be eliminated early
+    /// (using `Bdd::binary_op_with_exists`). A variable is quantified in the first step after
+    /// which no remaining conjunct depends on it.
+    pub fn image(&self, set: &Bdd, variables: &[BddVariable]) -> Bdd {
+        unwrap_unlimited(self.image_with_tracker(set, variables, &mut Unlimited))
+    }
+
+    /// Same as `PartitionedRelation::image`, but the computation is aborted once the given
+    /// `budget` is exceeded. The step limit applies to all image steps together, the node
+    /// limit applies to each intermediate result.
+    pub fn image_with_budget(
+        &self,
+        set: &Bdd,
+        variables: &[BddVariable],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        self.image_with_tracker(set, variables, &mut budget.start())
+    }
+
+    /// **(internal)** Implementation of `image` which uses the given `tracker`.
+    fn image_with_tracker<K: Tracker>(
+        &self,
+        set: &Bdd,
+        variables: &[BddVariable],
+        tracker: &mut K,
+    ) -> Result<Bdd, BudgetExceeded> {
+        let supports: Vec<HashSet<BddVariable>> =
+            self.conjuncts.iter().map(|it| it.support_set()).collect();
+        let quantified: HashSet<BddVariable> = variables.iter().cloned().collect();
+
+        // Variables that do not appear in any conjunct can be eliminated immediately.
+        let early: Vec<BddVariable> = variables
+            .iter()
+            .filter(|var| supports.iter().all(|it| !it.contains(var)))
+            .cloned()
+            .collect();
+        let and = op_function::and;
+        let mut result = Bdd::binary_op_with_exists_with_tracker(set, set, and, &early, tracker)?;
+
+        for (index, to_quantify) in schedule(&supports, &quantified) {
+            result = Bdd::binary_op_with_exists_with_tracker(
+                &result,
+                &self.conjuncts[index],
+                and,
+                &to_quantify,
+                tracker,
+            )?;
+            if result.is_false() {
+                break;
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// **(internal)** Greedily compute the order in which the conjuncts (given by their
+/// `supports`) should be processed, together with the variables which can be quantified
+/// after each step.
+///
+/// In each step, we pick the conjunct which allows the most `quantified` variables to be
+/// eliminated (i.e. variables which do not appear in any other remaining conjunct). Ties are
+/// broken by the smallest number of variables that are added to the intermediate result,
+/// and then by the original order.
+fn schedule(
+    supports: &[HashSet<BddVariable>],
+    quantified: &HashSet<BddVariable>,
+) -> Vec<(usize, Vec<BddVariable>)> {
+    // How many remaining conjuncts depend on each quantified variable.
+    let mut occurrences: HashMap<BddVariable, usize> = HashMap::new();
+    for support in supports {
+        for var in support.intersection(quantified) {
+            *occurrences.entry(*var).or_default() += 1;
+        }
+    }
+
+    let mut remaining: Vec<usize> = (0..supports.len()).collect();
+    let mut seen: HashSet<BddVariable> = HashSet::new();
+    let mut result = Vec::with_capacity(supports.len());
+    while !remaining.is_empty() {
+        let score = |index: usize| {
+            let support = &supports[index];
+            let eliminated = support
+                .iter()
+                .filter(|var| occurrences.get(var) == Some(&1))
+                .count();
+            let added = support.difference(&seen).count();
+            (
+                eliminated,
+                std::cmp::Reverse(added),
+                std::cmp::Reverse(index),
+            )
+        };
+        let position = (0..remaining.len())
+            .max_by_key(|i| score(remaining[*i]))
+            .unwrap();
+        let index = remaining.remove(position);
+
+        let mut to_quantify = Vec::new();
+        for var in supports[index].intersection(quantified) {
+            let count = occurrences.get_mut(var).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                to_quantify.push(*var);
+            }
+        }
+        to_quantify.sort();
+        seen.extend(supports[index].iter().cloned());
+        result.push((index, to_quantify));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    /// A simple "counter" system over `n` bits, with variables `x_i` (current state)
+    /// and `y_i` (next state), together with a conjunct for each next state bit.
+    fn counter_relation(n: usize) -> (BddVariableSet, Vec<BddVariable>, PartitionedRelation) {
+        let mut builder = BddVariableSetBuilder::new();
+        let mut x = Vec::new();
+        let mut y = Vec::new();
+        for i in 0..n {
+            x.push(builder.make_variable(&format!("x_{}", i)));
+            y.push(builder.make_variable(&format!("y_{}", i)));
+        }
+        let vars = builder.build();
+        let mut conjuncts = Vec::new();
+        // y_i <=> x_i ^ (x_0 & ... & x_{i-1})
+        let mut carry = vars.mk_true();
+        for i in 0..n {
+            let x_i = vars.mk_var(x[i]);
+            let y_i = vars.mk_var(y[i]);
+            conjuncts.push(y_i.iff(&x_i.xor(&carry)));
+            carry = carry.and(&x_i);
+        }
+        (vars, x, PartitionedRelation::new(conjuncts))
+    }
+
+    #[test]
+    fn partitioned_image_matches_monolithic() {
+        let (vars, x, relation) = counter_relation(5);
+        let monolithic = relation.to_bdd();
+        let sets = [
+            vars.mk_true(),
+            vars.mk_false(),
+            vars.eval_expression_string("x_0 & !x_3"),
+            vars.eval_expression_string("(x_1 ^ x_2) | x_4"),
+        ];
+        for set in &sets {
+            let expected = Bdd::binary_op_with_exists(set, &monolithic, op_function::and, &x);
+            assert_eq!(expected, relation.image(set, &x));
+            for max_size in [0, 10, 100, 10_000] {
+                let clustered = relation.cluster(max_size);
+                assert_eq!(expected, clustered.image(set, &x));
+            }
+        }
+        // The image of a single state is its successor.
+        let state = vars.eval_expression_string("x_0 & x_1 & !x_2 & !x_3 & !x_4");
+        let expected = vars.eval_expression_string("!y_0 & !y_1 & y_2 & !y_3 & !y_4");
+        assert_eq!(expected, relation.image(&state, &x).exists(&x));
+    }
+
+    #[test]
+    fn partitioned_with_budget() {
+        let (vars, x, relation) = counter_relation(6);
+        let set = vars.eval_expression_string("x_0 & !x_3");
+        let unlimited = Budget::unlimited();
+        assert_eq!(
+            relation.image(&set, &x),
+            relation.image_with_budget(&set, &x, &unlimited).unwrap()
+        );
+        assert_eq!(
+            relation.to_bdd(),
+            relation.to_bdd_with_budget(&unlimited).unwrap()
+        );
+        let steps = Budget::unlimited().with_step_limit(10);
+        assert_eq!(
+            Err(BudgetExceeded::Steps),
+            relation.image_with_budget(&set, &x, &steps)
+        );
+        assert_eq!(
+            Err(BudgetExceeded::Steps),
+            relation.to_bdd_with_budget(&steps)
+        );
+        let nodes = Budget::unlimited().with_node_limit(5);
+        assert_eq!(
+            Err(BudgetExceeded::Nodes),
+            relation.to_bdd_with_budget(&nodes)
+        );
+    }
+
+    #[test]
+    fn partitioned_cluster() {
+        let (_, _, relation) = counter_relation(4);
+        assert_eq!(4, relation.conjuncts().len());
+        assert_eq!(4, relation.cluster(0).conjuncts().len());
+        assert_eq!(1, relation.cluster(usize::MAX).conjuncts().len());
+        assert_eq!(relation.to_bdd(), relation.cluster(usize::MAX).to_bdd());
+    }
+
+    #[test]
+    fn partitioned_schedule_quantifies_early() {
+        let vars = BddVariableSet::new_anonymous(4);
+        let v = vars.variables();
+        let supports = vec![
+            [v[0], v[1]].into_iter().collect(),
+            [v[1], v[2]].into_iter().collect(),
+            [v[3]].into_iter().collect(),
+        ];
+        let quantified = [v[0], v[1], v[3]].into_iter().collect();
+        let schedule = super::schedule(&supports, &quantified);
+        // First conjunct 0 (eliminates x_0) or 2 (eliminates x_3), preferring smaller support.
+        assert_eq!((2, vec![v[3]]), schedule[0]);
+        assert_eq!((0, vec![v[0]]), schedule[1]);
+        assert_eq!((1, vec![v[1]]), schedule[2]);
+    }
+}
@@ -0,0 +1,423 @@
+use crate::_impl_budget::{unwrap_unlimited, Tracker, Unlimited};
+use crate::*;
+use fxhash::FxBuildHasher;
+
+/// **(internal)** The set of accepted values of a linear sum: either the inclusive interval
+/// `[low, high]`, or (if `negated`) its complement.
+#[derive(Clone, Copy, Debug)]
+struct Accepted {
+    low: i128,
+    high: i128,
+    negated: bool,
+}
+
+impl Accepted {
+    /// Sums $x$ such that $x \bowtie k$, where $\bowtie$ is the `comparator` and `k`
+    /// is the `bound`.
+    fn compare(comparator: Comparator, bound: i64) -> Accepted {
+        let k = i128::from(bound);
+        let (low, high, negated) = match comparator {
+            Comparator::Lt => (i128::MIN, k - 1, false),
+            Comparator::Le => (i128::MIN, k, false),
+            Comparator::Eq => (k, k, false),
+            Comparator::Ne => (k, k, true),
+            Comparator::Ge => (k, i128::MAX, false),
+            Comparator::Gt => (k + 1, i128::MAX, false),
+        };
+        Accepted { low, high, negated }
+    }
+
+    /// Sums in the inclusive range `[low, high]`.
+    fn between(low: i64, high: i64) -> Accepted {
+        Accepted {
+            low: i128::from(low),
+            high: i128::from(high),
+            negated: false,
+        }
+    }
+
+    /// Decide if all values in `[min, max]` are accepted (`Some(true)`), none of them are
+    /// accepted (`Some(false)`), or the result is not known yet (`None`).
+    fn decide(&self, min: i128, max: i128) -> Option<bool> {
+        let result = if min >= self.low && max <= self.high {
+            Some(true)
+        } else if max < self.low || min > self.high {
+            Some(false)
+        } else {
+            None
+        };
+        result.map(|it| it != self.negated)
+    }
+}
+
+/// Methods for building `Bdd`s of (weighted) cardinality constraints.
+impl BddVariableSet {
+    /// Build a `Bdd` of the linear pseudo-Boolean constraint
+    /// $\sum_{i} w_i \cdot x_i \bowtie k$, where `terms` are the pairs $(w_i, x_i)$,
+    /// $\bowtie$ is the `comparator` and `k` is the `bound`.
+    ///
+    /// Weights can be negative and a variable can appear in several terms (the weights are
+    /// then added together). The `Bdd` is constructed directly using dynamic programming
+    /// over the partial sums: each of the $n$ distinct variables is paired with every
+    /// reachable partial sum of the preceding terms that does not decide the constraint yet.
+    /// The running time and memory are thus $O(n \cdot \min(2^n, W))$, where $W$ is the
+    /// number of distinct partial sums (at most $\sum_i |w_i| + 1$). This is pseudo-polynomial:
+    /// small weights give a small `Bdd`, but large incomparable weights can give an
+    /// exponential one.
+    pub fn mk_pseudo_boolean(
+        &self,
+        terms: &[(i64, BddVariable)],
+        comparator: Comparator,
+        bound: i64,
+    ) -> Bdd {
+        let accepted = Accepted::compare(comparator, bound);
+        unwrap_unlimited(mk_linear_constraint(
+            self.num_vars,
+            terms,
+            accepted,
+            &mut Unlimited,
+        ))
+    }
+
+    /// Same as `BddVariableSet::mk_pseudo_boolean`, but the construction is aborted once
+    /// the given `budget` is exceeded.
+    pub fn mk_pseudo_boolean_with_budget(
+        &self,
+        terms: &[(i64, BddVariable)],
+        comparator: Comparator,
+        bound: i64,
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        let accepted = Accepted::compare(comparator, bound);
+        mk_linear_constraint(self.num_vars, terms, accepted, &mut budget.start())
+    }
+
+    /// Build a `Bdd` of the linear pseudo-Boolean constraint
+    /// $low \leq \sum_{i} w_i \cdot x_i \leq high$ (see also `mk_pseudo_boolean`).
+    pub fn mk_pseudo_boolean_between(
+        &self,
+        terms: &[(i64, BddVariable)],
+        low: i64,
+        high: i64,
+    ) -> Bdd {
+        let accepted = Accepted::between(low, high);
+        unwrap_unlimited(mk_linear_constraint(
+            self.num_vars,
+            terms,
+            accepted,
+            &mut Unlimited,
+        ))
+    }
+
+    /// Same as `BddVariableSet::mk_pseudo_boolean_between`, but the construction is aborted
+    /// once the given `budget` is exceeded.
+    pub fn mk_pseudo_boolean_between_with_budget(
+        &self,
+        terms: &[(i64, BddVariable)],
+        low: i64,
+        high: i64,
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        let accepted = Accepted::between(low, high);
+        mk_linear_constraint(self.num_vars, terms, accepted, &mut budget.start())
+    }
+
+    /// Build a BDD that is satisfied by all valuations where *at least* $k$ `variables`
+    /// are `true`.
+    pub fn mk_sat_at_least_k(&self, k: usize, variables: &[BddVariable]) -> Bdd {
+        let terms = unit_terms(variables);
+        self.mk_pseudo_boolean(&terms, Comparator::Ge, saturate(k))
+    }
+
+    /// Same as `BddVariableSet::mk_sat_at_least_k`, but the construction is aborted once
+    /// the given `budget` is exceeded.
+    pub fn mk_sat_at_least_k_with_budget(
+        &self,
+        k: usize,
+        variables: &[BddVariable],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        let terms = unit_terms(variables);
+        self.mk_pseudo_boolean_with_budget(&terms, Comparator::Ge, saturate(k), budget)
+    }
+
+    /// Build a BDD that is satisfied by all valuations where *at least* $low$ and *at most*
+    /// $high$ `variables` are `true`.
+    pub fn mk_sat_between_k(&self, low: usize, high: usize, variables: &[BddVariable]) -> Bdd {
+        let terms = unit_terms(variables);
+        self.mk_pseudo_boolean_between(&terms, saturate(low), saturate(high))
+    }
+
+    /// Same as `BddVariableSet::mk_sat_between_k`, but the construction is aborted once
+    /// the given `budget` is exceeded.
+    pub fn mk_sat_between_k_with_budget(
+        &self,
+        low: usize,
+        high: usize,
+        variables: &[BddVariable],
+        budget: &Budget,
+    ) -> Result<Bdd, BudgetExceeded> {
+        let terms = unit_terms(variables);
+        self.mk_pseudo_boolean_between_with_budget(&terms, saturate(low), saturate(high), budget)
+    }
+}
+
+/// **(internal)** Terms of a plain cardinality constraint (all weights are one).
+fn unit_terms(variables: &[BddVariable]) -> Vec<(i64, BddVariable)> {
+    variables.iter().map(|it| (1, *it)).collect()
+}
+
+/// **(internal)** Convert a cardinality bound to `i64`, saturating at `i64::MAX`.
+fn saturate(value: usize) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+/// **(internal)** Build the `Bdd` of a linear constraint with the given set of `accepted`
+/// sums.
+///
+/// The `Bdd` is built by a depth-first search with an explicit stack, with the partial sum
+/// of the already decided terms as the memoisation key on each level. Same as in
+/// `Bdd::mk_dnf`, the high branch is explored first, such that the resulting node array has
+/// the canonical ordering.
+fn mk_linear_constraint<K: Tracker>(
+    num_vars: u16,
+    terms: &[(i64, BddVariable)],
+    accepted: Accepted,
+    tracker: &mut K,
+) -> Result<Bdd, BudgetExceeded> {
+    // Merge weights of the same variable and sort terms by the variable ordering.
+    let mut weights: HashMap<BddVariable, i128, FxBuildHasher> = HashMap::default();
+    for (weight, var) in terms {
+        if var.0 >= num_vars {
+            panic!("Variable {} is not in the variable set.", var);
+        }
+        *weights.entry(*var).or_default() += i128::from(*weight);
+    }
+    let mut terms: Vec<(BddVariable, i128)> =
+        weights.into_iter().filter(|(_, w)| *w != 0).collect();
+    terms.sort();
+
+    // `min_rest[i]`/`max_rest[i]` is the minimal/maximal sum of terms `i..`.
+    let mut min_rest = vec![0i128; terms.len() + 1];
+    let mut max_rest = vec![0i128; terms.len() + 1];
+    for i in (0..terms.len()).rev() {
+        let weight = terms[i].1;
+        min_rest[i] = min_rest[i + 1] + weight.min(0);
+        max_rest[i] = max_rest[i + 1] + weight.max(0);
+    }
+
+    // Resolve the task `(level, sum)` into a pointer if it is already known: either
+    // the constraint is decided by the sum, or the task is in the cache.
+    let resolve =
+        |cache: &HashMap<(usize, i128), BddPointer, FxBuildHasher>, level: usize, sum: i128| {
+            let min = sum + min_rest[level];
+            let max = sum + max_rest[level];
+            if let Some(value) = accepted.decide(min, max) {
+                Some(BddPointer::from_bool(value))
+            } else {
+                cache.get(&(level, sum)).cloned()
+            }
+        };
+
+    let mut result = Bdd::mk_true(num_vars);
+    let mut node_cache: HashMap<BddNode, BddPointer, FxBuildHasher> = HashMap::default();
+    let mut task_cache: HashMap<(usize, i128), BddPointer, FxBuildHasher> = HashMap::default();
+
+    // The sums can be undecided even if all achievable sums are (not) accepted, in which
+    // case the result collapses into a terminal.
+    if let Some(root) = resolve(&task_cache, 0, 0) {
+        return Ok(if root.is_zero() {
+            Bdd::mk_false(num_vars)
+        } else {
+            Bdd::mk_true(num_vars)
+        });
+    }
+
+    let mut stack: Vec<(usize, i128)> = Vec::with_capacity(terms.len());
+    stack.push((0, 0));
+    while let Some(&(level, sum)) = stack.last() {
+        tracker.step()?;
+        if task_cache.contains_key(&(level, sum)) {
+            // The task was already pushed by another parent and then completed.
+            stack.pop();
+            continue;
+        }
+        // The task is not decided, so there must be some terms left.
+        let (var, weight) = terms[level];
+        let high = resolve(&task_cache, level + 1, sum + weight);
+        let low = resolve(&task_cache, level + 1, sum);
+        if let (Some(high), Some(low)) = (high, low) {
+            let pointer = if high == low {
+                high
+            } else {
+                let node = BddNode::mk_node(var, low, high);
+                if let Some(id) = node_cache.get(&node) {
+                    *id
+                } else {
+                    result.push_node(node);
+                    tracker.check_nodes(result.size())?;
+                    node_cache.insert(node, result.root_pointer());
+                    result.root_pointer()
+                }
+            };
+            task_cache.insert((level, sum), pointer);
+            stack.pop();
+        } else {
+            // Push the low task first, such that the high task is processed first.
+            if low.is_none() {
+                stack.push((level + 1, sum));
+            }
+            if high.is_none() {
+                stack.push((level + 1, sum + weight));
+            }
+        }
+    }
+
+    Ok(if task_cache[&(0, 0)].is_zero() {
+        Bdd::mk_false(num_vars)
+    } else {
+        result
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn pseudo_boolean_exhaustive() {
+        let vars = BddVariableSet::new_anonymous(5);
+        let v = vars.variables();
+        let terms = [(3, v[0]), (-2, v[1]), (5, v[3]), (-2, v[4]), (1, v[1])];
+        let comparators = [
+            (Comparator::Lt, (|a, b| a < b) as fn(i64, i64) -> bool),
+            (Comparator::Le, |a, b| a <= b),
+            (Comparator::Eq, |a, b| a == b),
+            (Comparator::Ne, |a, b| a != b),
+            (Comparator::Ge, |a, b| a >= b),
+            (Comparator::Gt, |a, b| a > b),
+        ];
+        for bound in -5..10 {
+            for (comparator, check) in comparators {
+                let bdd = vars.mk_pseudo_boolean(&terms, comparator, bound);
+                let between = vars.mk_pseudo_boolean_between(&terms, bound, bound + 3);
+                for valuation in ValuationsOfClauseIterator::new_unconstrained(5) {
+                    let sum: i64 = terms
+                        .iter()
+                        .filter(|(_, var)| valuation[*var])
+                        .map(|(w, _)| *w)
+                        .sum();
+                    assert_eq!(check(sum, bound), bdd.eval_in(&valuation));
+                    let expected = sum >= bound && sum <= bound + 3;
+                    assert_eq!(expected, between.eval_in(&valuation));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pseudo_boolean_constants() {
+        let vars = BddVariableSet::new_anonymous(3);
+        let v = vars.variables();
+        assert!(vars.mk_pseudo_boolean(&[], Comparator::Le, 0).is_true());
+        assert!(vars.mk_pseudo_boolean(&[], Comparator::Lt, 0).is_false());
+        // Sums 0, 2, 4 are all accepted, even though the range 0..=4 contains 1.
+        assert!(vars
+            .mk_pseudo_boolean(&[(2, v[0]), (2, v[1])], Comparator::Ne, 1)
+            .is_true());
+        assert!(vars
+            .mk_pseudo_boolean(&[(2, v[0]), (2, v[1])], Comparator::Eq, 1)
+            .is_false());
+        assert!(vars
+            .mk_pseudo_boolean(&[(2, v[0]), (3, v[1])], Comparator::Le, 5)
+            .is_true());
+        assert!(vars
+            .mk_pseudo_boolean(&[(2, v[0]), (-2, v[0])], Comparator::Eq, 1)
+            .is_false());
+        assert_eq!(
+            vars.mk_var(v[2]),
+            vars.mk_pseudo_boolean(&[(4, v[2]), (1, v[1])], Comparator::Gt, 1)
+        );
+    }
+
+    #[test]
+    fn sat_at_least_and_between() {
+        let vars = BddVariableSet::new_anonymous(6);
+        let v = &vars.variables()[1..];
+        for k in 0..7 {
+            if k > 0 {
+                let expected = vars.mk_sat_up_to_k(k - 1, v).not();
+                assert_eq!(expected, vars.mk_sat_at_least_k(k, v));
+            }
+            for high in k..7 {
+                let expected = vars
+                    .mk_sat_at_least_k(k, v)
+                    .and(&vars.mk_sat_up_to_k(high, v));
+                assert_eq!(expected, vars.mk_sat_between_k(k, high, v));
+            }
+        }
+        assert!(vars.mk_sat_at_least_k(0, v).is_true());
+        assert_eq!(vars.mk_sat_exactly_k(2, v), vars.mk_sat_between_k(2, 2, v));
+    }
+
+    #[test]
+    fn pseudo_boolean_with_budget() {
+        let vars = BddVariableSet::new_anonymous(8);
+        let v = vars.variables();
+        let terms: Vec<(i64, BddVariable)> = v.iter().map(|it| (3, *it)).collect();
+        let unlimited = Budget::unlimited();
+        assert_eq!(
+            vars.mk_pseudo_boolean(&terms, Comparator::Ne, 9),
+            vars.mk_pseudo_boolean_with_budget(&terms, Comparator::Ne, 9, &unlimited)
+                .unwrap()
+        );
+        assert_eq!(
+            vars.mk_pseudo_boolean_between(&terms, 6, 12),
+            vars.mk_pseudo_boolean_between_with_budget(&terms, 6, 12, &unlimited)
+                .unwrap()
+        );
+        assert_eq!(
+            vars.mk_sat_at_least_k(3, &v),
+            vars.mk_sat_at_least_k_with_budget(3, &v, &unlimited)
+                .unwrap()
+        );
+        assert_eq!(
+            vars.mk_sat_between_k(2, 5, &v),
+            vars.mk_sat_between_k_with_budget(2, 5, &v, &unlimited)
+                .unwrap()
+        );
+        let steps = Budget::unlimited().with_step_limit(5);
+        assert_eq!(
+            Err(BudgetExceeded::Steps),
+            vars.mk_sat_between_k_with_budget(2, 5, &v, &steps)
+        );
+        let nodes = Budget::unlimited().with_node_limit(5);
+        assert_eq!(
+            Err(BudgetExceeded::Nodes),
+            vars.mk_pseudo_boolean_with_budget(&terms, Comparator::Eq, 9, &nodes)
+        );
+    }
+
+    #[test]
+    fn pseudo_boolean_many_terms() {
+        // Deep enough to overflow the stack of a test thread if the construction was recursive.
+        let vars = BddVariableSet::new_anonymous(20_000);
+        let v = vars.variables();
+        let terms: Vec<(i64, BddVariable)> = v.iter().map(|it| (1, *it)).collect();
+        let none = vars.mk_conjunctive_clause(&BddPartialValuation::from_values(
+            &v.iter().map(|it| (*it, false)).collect::<Vec<_>>(),
+        ));
+        assert_eq!(
+            none.not(),
+            vars.mk_pseudo_boolean(&terms, Comparator::Ge, 1)
+        );
+        let exactly_one = vars.mk_pseudo_boolean(&terms, Comparator::Eq, 1);
+        assert_eq!(2 * v.len() + 1, exactly_one.size());
+        let mut valuation = BddValuation::all_false(20_000);
+        assert!(!exactly_one.eval_in(&valuation));
+        valuation.set_value(v[12_345], true);
+        assert!(exactly_one.eval_in(&valuation));
+        valuation.set_value(v[19_999], true);
+        assert!(!exactly_one.eval_in(&valuation));
+    }
+}
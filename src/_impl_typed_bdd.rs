@@ -0,0 +1,364 @@
+use crate::{Bdd, BddValuation, BddVariable, BddVariableSet, TypedBdd, TypedBddVariableSet};
+use fxhash::FxHasher;
+use num_bigint::BigInt;
+use std::collections::HashSet;
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+impl<Ctx> TypedBdd<Ctx> {
+    /// **(internal)** Brand the given `bdd` with the context `Ctx`, where `fingerprint`
+    /// identifies the `TypedBddVariableSet` that created it.
+    pub(crate) fn new(bdd: Bdd, fingerprint: u64) -> TypedBdd<Ctx> {
+        TypedBdd {
+            bdd,
+            fingerprint,
+            context: PhantomData,
+        }
+    }
+
+    /// **(internal)** Brand the result of an operation on this `TypedBdd`.
+    fn derive(&self, bdd: Bdd) -> TypedBdd<Ctx> {
+        TypedBdd::new(bdd, self.fingerprint)
+    }
+
+    /// **(internal)** In debug builds, check that `other` was created by an equivalent
+    /// `TypedBddVariableSet`.
+    fn check_context(&self, other: &TypedBdd<Ctx>) {
+        debug_assert_eq!(
+            self.fingerprint, other.fingerprint,
+            "TypedBdds were created by different variable sets."
+        );
+    }
+
+    /// **(internal)** Brand the result of a binary `op` applied to this and the `other`
+    /// `TypedBdd`.
+    fn combine<F>(&self, other: &TypedBdd<Ctx>, op: F) -> TypedBdd<Ctx>
+    where
+        F: FnOnce(&Bdd, &Bdd) -> Bdd,
+    {
+        self.check_context(other);
+        self.derive(op(&self.bdd, &other.bdd))
+    }
+
+    /// A reference to the underlying untyped `Bdd`.
+    pub fn as_bdd(&self) -> &Bdd {
+        &self.bdd
+    }
+
+    /// Convert this `TypedBdd` into the underlying untyped `Bdd`.
+    pub fn into_bdd(self) -> Bdd {
+        self.bdd
+    }
+
+    /// Same as `Bdd::not`.
+    pub fn not(&self) -> TypedBdd<Ctx> {
+        self.derive(self.bdd.not())
+    }
+
+    /// Same as `Bdd::and`.
+    pub fn and(&self, right: &TypedBdd<Ctx>) -> TypedBdd<Ctx> {
+        self.combine(right, Bdd::and)
+    }
+
+    /// Same as `Bdd::or`.
+    pub fn or(&self, right: &TypedBdd<Ctx>) -> TypedBdd<Ctx> {
+        self.combine(right, Bdd::or)
+    }
+
+    /// Same as `Bdd::imp`.
+    pub fn imp(&self, right: &TypedBdd<Ctx>) -> TypedBdd<Ctx> {
+        self.combine(right, Bdd::imp)
+    }
+
+    /// Same as `Bdd::iff`.
+    pub fn iff(&self, right: &TypedBdd<Ctx>) -> TypedBdd<Ctx> {
+        self.combine(right, Bdd::iff)
+    }
+
+    /// Same as `Bdd::xor`.
+    pub fn xor(&self, right: &TypedBdd<Ctx>) -> TypedBdd<Ctx> {
+        self.combine(right, Bdd::xor)
+    }
+
+    /// Same as `Bdd::and_not`.
+    pub fn and_not(&self, right: &TypedBdd<Ctx>) -> TypedBdd<Ctx> {
+        self.combine(right, Bdd::and_not)
+    }
+
+    /// Same as `Bdd::if_then_else`.
+    pub fn if_then_else(a: &TypedBdd<Ctx>, b: &TypedBdd<Ctx>, c: &TypedBdd<Ctx>) -> TypedBdd<Ctx> {
+        a.check_context(b);
+        a.check_context(c);
+        a.derive(Bdd::if_then_else(&a.bdd, &b.bdd, &c.bdd))
+    }
+
+    /// Same as `Bdd::binary_op`.
+    pub fn binary_op<T>(
+        left: &TypedBdd<Ctx>,
+        right: &TypedBdd<Ctx>,
+        op_function: T,
+    ) -> TypedBdd<Ctx>
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        left.combine(right, |l, r| Bdd::binary_op(l, r, op_function))
+    }
+
+    /// Same as `Bdd::exists`.
+    pub fn exists(&self, variables: &[BddVariable]) -> TypedBdd<Ctx> {
+        self.derive(self.bdd.exists(variables))
+    }
+
+    /// Same as `Bdd::for_all`.
+    pub fn for_all(&self, variables: &[BddVariable]) -> TypedBdd<Ctx> {
+        self.derive(self.bdd.for_all(variables))
+    }
+
+    /// Same as `Bdd::restrict`.
+    pub fn restrict(&self, variables: &[(BddVariable, bool)]) -> TypedBdd<Ctx> {
+        self.derive(self.bdd.restrict(variables))
+    }
+
+    /// Same as `Bdd::select`.
+    pub fn select(&self, variables: &[(BddVariable, bool)]) -> TypedBdd<Ctx> {
+        self.derive(self.bdd.select(variables))
+    }
+
+    /// Same as `Bdd::num_vars`.
+    pub fn num_vars(&self) -> u16 {
+        self.bdd.num_vars()
+    }
+
+    /// Same as `Bdd::size`.
+    pub fn size(&self) -> usize {
+        self.bdd.size()
+    }
+
+    /// Same as `Bdd::is_true`.
+    pub fn is_true(&self) -> bool {
+        self.bdd.is_true()
+    }
+
+    /// Same as `Bdd::is_false`.
+    pub fn is_false(&self) -> bool {
+        self.bdd.is_false()
+    }
+
+    /// Same as `Bdd::cardinality`.
+    pub fn cardinality(&self) -> f64 {
+        self.bdd.cardinality()
+    }
+
+    /// Same as `Bdd::exact_cardinality`.
+    pub fn exact_cardinality(&self) -> BigInt {
+        self.bdd.exact_cardinality()
+    }
+
+    /// Same as `Bdd::sat_witness`.
+    pub fn sat_witness(&self) -> Option<BddValuation> {
+        self.bdd.sat_witness()
+    }
+
+    /// Same as `Bdd::support_set`.
+    pub fn support_set(&self) -> HashSet<BddVariable> {
+        self.bdd.support_set()
+    }
+}
+
+impl<Ctx> TypedBddVariableSet<Ctx> {
+    /// Brand the given `BddVariableSet` with the context `Ctx`.
+    ///
+    /// Note that the type system cannot prevent several variable sets with the same `Ctx`.
+    /// In debug builds, combining `TypedBdd`s from sets with different variables panics.
+    pub fn new(variables: BddVariableSet) -> TypedBddVariableSet<Ctx> {
+        let mut hasher = FxHasher::default();
+        variables.var_names.hash(&mut hasher);
+        TypedBddVariableSet {
+            variables,
+            fingerprint: hasher.finish(),
+            context: PhantomData,
+        }
+    }
+
+    /// A reference to the underlying untyped `BddVariableSet`.
+    pub fn as_variable_set(&self) -> &BddVariableSet {
+        &self.variables
+    }
+
+    /// Brand a `Bdd` that was created using the underlying `BddVariableSet`.
+    ///
+    /// *Panics:* The `bdd` must have the same number of variables as this set.
+    pub fn wrap(&self, bdd: Bdd) -> TypedBdd<Ctx> {
+        if bdd.num_vars() != self.variables.num_vars() {
+            panic!(
+                "Bdd is incompatible with the variable set ({} vs. {} variables).",
+                bdd.num_vars(),
+                self.variables.num_vars()
+            );
+        }
+        self.brand(bdd)
+    }
+
+    /// **(internal)** Brand a `Bdd` created by the underlying `BddVariableSet`.
+    fn brand(&self, bdd: Bdd) -> TypedBdd<Ctx> {
+        TypedBdd::new(bdd, self.fingerprint)
+    }
+
+    /// Same as `BddVariableSet::mk_true`.
+    pub fn mk_true(&self) -> TypedBdd<Ctx> {
+        self.brand(self.variables.mk_true())
+    }
+
+    /// Same as `BddVariableSet::mk_false`.
+    pub fn mk_false(&self) -> TypedBdd<Ctx> {
+        self.brand(self.variables.mk_false())
+    }
+
+    /// Same as `BddVariableSet::mk_var`.
+    pub fn mk_var(&self, var: BddVariable) -> TypedBdd<Ctx> {
+        self.brand(self.variables.mk_var(var))
+    }
+
+    /// Same as `BddVariableSet::mk_literal`.
+    pub fn mk_literal(&self, var: BddVariable, value: bool) -> TypedBdd<Ctx> {
+        self.brand(self.variables.mk_literal(var, value))
+    }
+
+    /// Same as `BddVariableSet::mk_var_by_name`.
+    pub fn mk_var_by_name(&self, var: &str) -> TypedBdd<Ctx> {
+        self.brand(self.variables.mk_var_by_name(var))
+    }
+
+    /// Same as `BddVariableSet::eval_expression_string`.
+    pub fn eval_expression_string(&self, expression: &str) -> TypedBdd<Ctx> {
+        self.brand(self.variables.eval_expression_string(expression))
+    }
+
+    /// Evaluate the `bdd` in the given `valuation`.
+    pub fn eval_in(&self, bdd: &TypedBdd<Ctx>, valuation: &BddValuation) -> bool {
+        debug_assert_eq!(
+            self.fingerprint, bdd.fingerprint,
+            "TypedBdd was created by a different variable set."
+        );
+        bdd.bdd.eval_in(valuation)
+    }
+}
+
+// The standard traits are implemented manually, because `derive` would require the same
+// traits to be implemented also by the `Ctx` marker type.
+
+impl<Ctx> Clone for TypedBdd<Ctx> {
+    fn clone(&self) -> Self {
+        self.derive(self.bdd.clone())
+    }
+}
+
+impl<Ctx> Debug for TypedBdd<Ctx> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TypedBdd").field(&self.bdd).finish()
+    }
+}
+
+impl<Ctx> PartialEq for TypedBdd<Ctx> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bdd == other.bdd
+    }
+}
+
+impl<Ctx> Eq for TypedBdd<Ctx> {}
+
+impl<Ctx> Hash for TypedBdd<Ctx> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bdd.hash(state)
+    }
+}
+
+impl<Ctx> Deref for TypedBddVariableSet<Ctx> {
+    type Target = BddVariableSet;
+
+    fn deref(&self) -> &Self::Target {
+        &self.variables
+    }
+}
+
+impl<Ctx> Clone for TypedBddVariableSet<Ctx> {
+    fn clone(&self) -> Self {
+        TypedBddVariableSet {
+            variables: self.variables.clone(),
+            fingerprint: self.fingerprint,
+            context: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{op_function, BddVariableSet, TypedBdd, TypedBddVariableSet};
+
+    struct States;
+    struct Parameters;
+
+    #[test]
+    fn typed_bdd_operations() {
+        let states = TypedBddVariableSet::<States>::new(BddVariableSet::new(&["a", "b", "c"]));
+        let a = states.mk_var_by_name("a");
+        let b = states.mk_var_by_name("b");
+        let c = states.mk_var(states.var_by_name("c").unwrap());
+
+        let untyped = states.eval_expression_string("(a & !b) | c");
+        let typed = a.and(&b.not()).or(&c);
+        assert_eq!(untyped, typed);
+        assert_eq!(untyped.as_bdd(), typed.as_bdd());
+        assert_eq!(
+            states
+                .as_variable_set()
+                .eval_expression_string("(a & !b) | c"),
+            typed.clone().into_bdd()
+        );
+
+        // Read-only `Bdd` methods are forwarded.
+        assert_eq!(5.0, typed.cardinality());
+        assert!(a.imp(&a.or(&b)).is_true());
+        assert!(a.iff(&a.not()).is_false());
+        assert_eq!(a.xor(&b), TypedBdd::binary_op(&a, &b, op_function::xor));
+        assert_eq!(
+            a.and_not(&b),
+            a.and(&states.mk_literal(states.var_by_name("b").unwrap(), false))
+        );
+        assert_eq!(
+            states.mk_true(),
+            TypedBdd::if_then_else(&a, &states.mk_true(), &a.not())
+        );
+        let va = states.var_by_name("a").unwrap();
+        assert_eq!(b.not().or(&c), typed.exists(&[va]));
+        assert_eq!(c, typed.for_all(&[va]));
+        assert_eq!(c, typed.restrict(&[(va, false)]));
+        assert_eq!(a.not().and(&c), typed.select(&[(va, false)]));
+        assert!(states.eval_in(&typed, &typed.sat_witness().unwrap()));
+
+        // Contexts with the same number of variables, which are now distinguished.
+        let parameters = TypedBddVariableSet::<Parameters>::new(BddVariableSet::new_anonymous(3));
+        let p = parameters.wrap(parameters.as_variable_set().mk_var_by_name("x_0"));
+        assert_eq!(p.as_bdd(), a.as_bdd());
+        assert!(parameters.mk_false().and(&p).is_false());
+    }
+
+    #[test]
+    #[should_panic]
+    fn typed_bdd_wrap_invalid() {
+        let states = TypedBddVariableSet::<States>::new(BddVariableSet::new_anonymous(3));
+        states.wrap(BddVariableSet::new_anonymous(2).mk_true());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn typed_bdd_different_sets() {
+        // Two different variable sets with the same `Ctx` are detected in debug builds.
+        let first = TypedBddVariableSet::<States>::new(BddVariableSet::new(&["a", "b"]));
+        let second = TypedBddVariableSet::<States>::new(BddVariableSet::new(&["p", "q"]));
+        first.mk_var_by_name("a").and(&second.mk_var_by_name("p"));
+    }
+}
@@ -2,16 +2,16 @@ use crate::_test_util::{mk_5_variable_set, mk_small_test_bdd};
 use crate::*;
 
 fn v1() -> BddVariable {
-    return BddVariable(0);
+    BddVariable(0)
 }
 fn v2() -> BddVariable {
-    return BddVariable(1);
+    BddVariable(1)
 }
 fn v3() -> BddVariable {
-    return BddVariable(2);
+    BddVariable(2)
 }
 fn v4() -> BddVariable {
-    return BddVariable(3);
+    BddVariable(3)
 }
 
 #[test]
@@ -48,7 +48,7 @@ fn basic_ternary_test() {
     let c = variables.mk_var(v3());
     let native = bdd!(((!a) => c) & (a => b));
     let ternary = Bdd::ternary_op(&a, &b, &c, |a, b, c| match (a, b, c) {
-        (Some(a), Some(b), Some(c)) => Some((!(!a) | c) & (!a | b)),
+        (Some(a), Some(b), Some(c)) => Some((a | c) & (!a | b)),
         _ => None,
     });
     assert!(native.iff(&ternary).is_true());
@@ -72,7 +72,7 @@ fn fused_ternary_test() {
         |a, b, c| {
             // (a => (!c | b)) & (!a => !b)
             match (a, b, c) {
-                (Some(a), Some(b), Some(c)) => Some((!a | (!c | b)) & (!(!a) | !b)),
+                (Some(a), Some(b), Some(c)) => Some((!a | (!c | b)) & (a | !b)),
                 _ => None,
             }
         },
@@ -23,11 +23,11 @@ use rand::{RngCore, SeedableRng};
 
 #[derive(Debug)]
 enum BddOp {
-    AND,
-    OR,
-    XOR,
-    IMP,
-    IFF,
+    And,
+    Or,
+    Xor,
+    Imp,
+    Iff,
 }
 
 #[derive(Debug)]
@@ -63,23 +63,23 @@ impl BddOpTree {
                 .map(|_| {
                     let negate = rand.next_u32() % 2 == 0;
                     let op = match rand.next_u32() % 5 {
-                        0 => BddOp::AND,
-                        1 => BddOp::OR,
-                        2 => BddOp::XOR,
-                        3 => BddOp::IMP,
-                        _ => BddOp::IFF,
+                        0 => BddOp::And,
+                        1 => BddOp::Or,
+                        2 => BddOp::Xor,
+                        3 => BddOp::Imp,
+                        _ => BddOp::Iff,
                     };
                     Op { op, negate }
                 })
                 .collect();
             levels.push(level);
-            level_width = level_width / 2;
+            level_width /= 2;
         }
 
-        return BddOpTree {
+        BddOpTree {
             leaves,
             ops: levels,
-        };
+        }
     }
 
     /// Evaluate this op tree to `Bdd` using the given `BddVariableSet`.
@@ -94,11 +94,11 @@ impl BddOpTree {
                 let b = &formulas[i + 1];
                 let op = &level[i / 2];
                 let result = match op.op {
-                    BddOp::AND => a.and(&b),
-                    BddOp::OR => a.or(&b),
-                    BddOp::XOR => a.xor(&b),
-                    BddOp::IMP => a.imp(&b),
-                    BddOp::IFF => a.iff(&b),
+                    BddOp::And => a.and(b),
+                    BddOp::Or => a.or(b),
+                    BddOp::Xor => a.xor(b),
+                    BddOp::Imp => a.imp(b),
+                    BddOp::Iff => a.iff(b),
                 };
                 if op.negate {
                     new_formulas.push(result.not())
@@ -110,7 +110,7 @@ impl BddOpTree {
             formulas = new_formulas;
         }
 
-        return formulas[0].clone();
+        formulas[0].clone()
     }
 
     /// Evaluate this op tree with the specified `BddValuation`.
@@ -125,11 +125,11 @@ impl BddOpTree {
                 let b = values[i + 1];
                 let op = &level[i / 2];
                 let result = match op.op {
-                    BddOp::AND => a && b,
-                    BddOp::OR => a || b,
-                    BddOp::XOR => a ^ b,
-                    BddOp::IMP => (!a) || b,
-                    BddOp::IFF => a == b,
+                    BddOp::And => a && b,
+                    BddOp::Or => a || b,
+                    BddOp::Xor => a ^ b,
+                    BddOp::Imp => (!a) || b,
+                    BddOp::Iff => a == b,
                 };
                 if op.negate {
                     new_values.push(!result)
@@ -141,7 +141,7 @@ impl BddOpTree {
             values = new_values;
         }
 
-        return values[0];
+        values[0]
     }
 }
 
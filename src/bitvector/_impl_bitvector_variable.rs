@@ -0,0 +1,220 @@
+use super::{BitVectorVariable, SymbolicBitVector};
+use crate::BddVariableSetBuilder;
+use crate::{Bdd, BddPartialValuation, BddValuation, BddVariable, BddVariableSet};
+
+impl BddVariableSetBuilder {
+    /// Create a new `width`-bit integer variable whose bits are allocated as a block
+    /// of consecutive `BddVariable`s (starting with the least significant bit).
+    ///
+    /// *Panics:* The `width` must be positive. Same conditions as `make_variable` apply
+    /// to the names of individual bits.
+    pub fn make_bitvector(&mut self, name: &str, width: u16) -> BitVectorVariable {
+        check_width(usize::from(width));
+        let bits = (0..width)
+            .map(|i| self.make_variable(&bit_name(name, i)))
+            .collect();
+        BitVectorVariable {
+            name: name.to_string(),
+            bits,
+        }
+    }
+
+    /// Create several `width`-bit integer variables whose bits are interleaved, i.e.
+    /// the `i`-th bits of all vectors are allocated next to each other.
+    ///
+    /// This ordering is usually much more efficient when the vectors are compared or
+    /// combined using arithmetic operations.
+    ///
+    /// *Panics:* The `width` must be positive. Same conditions as `make_variable` apply
+    /// to the names of individual bits.
+    pub fn make_interleaved_bitvectors(
+        &mut self,
+        names: &[&str],
+        width: u16,
+    ) -> Vec<BitVectorVariable> {
+        check_width(usize::from(width));
+        let mut result: Vec<BitVectorVariable> = names
+            .iter()
+            .map(|name| BitVectorVariable {
+                name: name.to_string(),
+                bits: Vec::with_capacity(usize::from(width)),
+            })
+            .collect();
+        for i in 0..width {
+            for vector in result.iter_mut() {
+                let bit = self.make_variable(&bit_name(&vector.name, i));
+                vector.bits.push(bit);
+            }
+        }
+        result
+    }
+}
+
+impl BitVectorVariable {
+    /// Name of this bit-vector variable.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of bits of this variable.
+    pub fn width(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// The `BddVariable`s encoding this integer, starting with the least significant bit.
+    pub fn bits(&self) -> &[BddVariable] {
+        &self.bits
+    }
+
+    /// Convert this variable to a `SymbolicBitVector` which can be used in arithmetic
+    /// operations.
+    pub fn to_symbolic(&self, variables: &BddVariableSet) -> SymbolicBitVector {
+        SymbolicBitVector(self.bits.iter().map(|it| variables.mk_var(*it)).collect())
+    }
+
+    /// Read the integer value of this variable in the given `valuation`.
+    ///
+    /// *Panics:* The width of the variable must be at most 64 bits.
+    pub fn value_in(&self, valuation: &BddValuation) -> u64 {
+        assert!(self.width() <= 64, "Bit-vector is wider than 64 bits.");
+        self.bits
+            .iter()
+            .enumerate()
+            .filter(|(_, bit)| valuation[**bit])
+            .fold(0, |value, (i, _)| value | (1 << i))
+    }
+
+    /// Read the integer value of this variable in the given partial `valuation`. Returns
+    /// `None` if some of the bits are not fixed.
+    ///
+    /// *Panics:* The width of the variable must be at most 64 bits.
+    pub fn value_in_partial(&self, valuation: &BddPartialValuation) -> Option<u64> {
+        assert!(self.width() <= 64, "Bit-vector is wider than 64 bits.");
+        let mut value = 0;
+        for (i, bit) in self.bits.iter().enumerate() {
+            if valuation.get_value(*bit)? {
+                value |= 1 << i;
+            }
+        }
+        Some(value)
+    }
+
+    /// Create a `Bdd` which is satisfied exactly when this variable has the given `value`.
+    ///
+    /// *Panics:* The `value` must be representable using the width of this variable.
+    pub fn mk_value(&self, variables: &BddVariableSet, value: u64) -> Bdd {
+        check_value(self.width(), value);
+        let literals: Vec<(BddVariable, bool)> = self
+            .bits
+            .iter()
+            .enumerate()
+            .map(|(i, bit)| (*bit, i < 64 && (value >> i) & 1 == 1))
+            .collect();
+        variables.mk_conjunctive_clause(&BddPartialValuation::from_values(&literals))
+    }
+
+    /// Create a `Bdd` which is satisfied exactly when the value of this variable is
+    /// in the (inclusive) range `low..=high`.
+    pub fn mk_in_range(&self, variables: &BddVariableSet, low: u64, high: u64) -> Bdd {
+        self.to_symbolic(variables).in_range(variables, low, high)
+    }
+}
+
+/// **(internal)** Name of the `index`-th bit of the bit-vector `name`.
+fn bit_name(name: &str, index: u16) -> String {
+    format!("{}_b{}", name, index)
+}
+
+/// **(internal)** Panic if `width` is zero (bit-vectors must have at least one bit).
+pub(super) fn check_width(width: usize) {
+    if width == 0 {
+        panic!("Bit-vectors must have at least one bit.");
+    }
+}
+
+/// **(internal)** Panic if `value` does not fit into `width` bits.
+pub(super) fn check_value(width: usize, value: u64) {
+    if width < 64 && value >> width != 0 {
+        panic!("Value {} does not fit into {} bits.", value, width);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BddVariableSetBuilder, ValuationsOfClauseIterator};
+
+    #[test]
+    fn bitvector_allocation() {
+        let mut builder = BddVariableSetBuilder::new();
+        let x = builder.make_bitvector("x", 3);
+        let yz = builder.make_interleaved_bitvectors(&["y", "z"], 2);
+        let variables = builder.build();
+        assert_eq!(7, variables.num_vars());
+        assert_eq!("x", x.name());
+        assert_eq!(3, x.width());
+        assert_eq!(Some(x.bits()[2]), variables.var_by_name("x_b2"));
+        assert_eq!(Some(yz[0].bits()[0]), variables.var_by_name("y_b0"));
+        assert_eq!(Some(yz[1].bits()[0]), variables.var_by_name("z_b0"));
+        assert_eq!(Some(yz[0].bits()[1]), variables.var_by_name("y_b1"));
+        let names: Vec<String> = variables
+            .variables()
+            .into_iter()
+            .map(|it| variables.name_of(it))
+            .collect();
+        assert_eq!(
+            vec!["x_b0", "x_b1", "x_b2", "y_b0", "z_b0", "y_b1", "z_b1"],
+            names
+        );
+    }
+
+    #[test]
+    fn bitvector_values() {
+        let mut builder = BddVariableSetBuilder::new();
+        let x = builder.make_bitvector("x", 3);
+        let variables = builder.build();
+        for value in 0..8 {
+            let bdd = x.mk_value(&variables, value);
+            assert_eq!(1.0, bdd.cardinality());
+            let valuation = bdd.sat_witness().unwrap();
+            assert_eq!(value, x.value_in(&valuation));
+            let clause = bdd.sat_clauses().next().unwrap();
+            assert_eq!(Some(value), x.value_in_partial(&clause));
+        }
+        let range = x.mk_in_range(&variables, 2, 5);
+        assert_eq!(4.0, range.cardinality());
+        for valuation in ValuationsOfClauseIterator::new_unconstrained(3) {
+            let value = x.value_in(&valuation);
+            assert_eq!((2..=5).contains(&value), range.eval_in(&valuation));
+        }
+        assert!(x.mk_in_range(&variables, 5, 2).is_false());
+    }
+
+    #[test]
+    fn bitvector_value_wide() {
+        let mut builder = BddVariableSetBuilder::new();
+        let x = builder.make_bitvector("x", 70);
+        let variables = builder.build();
+        let bdd = x.mk_value(&variables, 1);
+        assert_eq!(1.0, bdd.cardinality());
+        let valuation = bdd.sat_witness().unwrap();
+        assert!(valuation[x.bits()[0]]);
+        for bit in &x.bits()[1..] {
+            assert!(!valuation[*bit]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn bitvector_zero_width() {
+        BddVariableSetBuilder::new().make_bitvector("x", 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bitvector_value_too_large() {
+        let mut builder = BddVariableSetBuilder::new();
+        let x = builder.make_bitvector("x", 3);
+        let variables = builder.build();
+        x.mk_value(&variables, 8);
+    }
+}
@@ -0,0 +1,227 @@
+use super::_impl_bitvector_variable::{check_value, check_width};
+use super::SymbolicBitVector;
+use crate::{Bdd, BddValuation, BddVariableSet};
+
+impl SymbolicBitVector {
+    /// Create a symbolic bit-vector from the `Bdd`s of individual bits (starting with
+    /// the least significant bit).
+    ///
+    /// *Panics:* The vector must have at least one bit.
+    pub fn from_bits(bits: Vec<Bdd>) -> SymbolicBitVector {
+        check_width(bits.len());
+        SymbolicBitVector(bits)
+    }
+
+    /// Create a `width`-bit symbolic bit-vector which is always equal to `value`.
+    ///
+    /// *Panics:* The `width` must be positive and the `value` must be representable using
+    /// `width` bits.
+    pub fn constant(variables: &BddVariableSet, width: usize, value: u64) -> SymbolicBitVector {
+        check_width(width);
+        check_value(width, value);
+        let bits = (0..width)
+            .map(|i| {
+                if i < 64 && (value >> i) & 1 == 1 {
+                    variables.mk_true()
+                } else {
+                    variables.mk_false()
+                }
+            })
+            .collect();
+        SymbolicBitVector(bits)
+    }
+
+    /// Number of bits of this vector.
+    pub fn width(&self) -> usize {
+        self.0.len()
+    }
+
+    /// The `Bdd`s of individual bits, starting with the least significant bit.
+    pub fn bits(&self) -> &[Bdd] {
+        &self.0
+    }
+
+    /// Compute the integer value of this vector in the given `valuation`.
+    ///
+    /// *Panics:* The width of the vector must be at most 64 bits.
+    pub fn eval_in(&self, valuation: &BddValuation) -> u64 {
+        assert!(self.width() <= 64, "Bit-vector is wider than 64 bits.");
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, bit)| bit.eval_in(valuation))
+            .fold(0, |value, (i, _)| value | (1 << i))
+    }
+
+    /// Symbolic addition (modulo $2^{width}$).
+    ///
+    /// *Panics:* Both vectors must have the same width.
+    pub fn add(&self, other: &SymbolicBitVector) -> SymbolicBitVector {
+        self.check_width(other);
+        let carry = self.false_bdd();
+        self.add_with_carry(&other.0, carry)
+    }
+
+    /// Symbolic subtraction (modulo $2^{width}$).
+    ///
+    /// *Panics:* Both vectors must have the same width.
+    pub fn sub(&self, other: &SymbolicBitVector) -> SymbolicBitVector {
+        self.check_width(other);
+        // a - b = a + !b + 1
+        let negated: Vec<Bdd> = other.0.iter().map(|it| it.not()).collect();
+        let carry = self.true_bdd();
+        self.add_with_carry(&negated, carry)
+    }
+
+    /// A `Bdd` which is satisfied when the two vectors are equal.
+    ///
+    /// *Panics:* Both vectors must have the same width.
+    pub fn mk_eq(&self, other: &SymbolicBitVector) -> Bdd {
+        self.check_width(other);
+        self.0
+            .iter()
+            .zip(&other.0)
+            .fold(self.true_bdd(), |result, (a, b)| result.and(&a.iff(b)))
+    }
+
+    /// A `Bdd` which is satisfied when this vector is (unsigned) less than `other`.
+    ///
+    /// *Panics:* Both vectors must have the same width.
+    pub fn lt(&self, other: &SymbolicBitVector) -> Bdd {
+        self.check_width(other);
+        self.compare(other, self.false_bdd())
+    }
+
+    /// A `Bdd` which is satisfied when this vector is (unsigned) less than or equal to `other`.
+    ///
+    /// *Panics:* Both vectors must have the same width.
+    pub fn le(&self, other: &SymbolicBitVector) -> Bdd {
+        self.check_width(other);
+        self.compare(other, self.true_bdd())
+    }
+
+    /// A `Bdd` which is satisfied when the value of this vector is in the (inclusive)
+    /// range `low..=high`. Bounds that do not fit into the width of the vector are
+    /// clamped to its maximal value.
+    pub fn in_range(&self, variables: &BddVariableSet, low: u64, high: u64) -> Bdd {
+        let max = if self.width() >= 64 {
+            u64::MAX
+        } else {
+            (1 << self.width()) - 1
+        };
+        if low > high || low > max {
+            return variables.mk_false();
+        }
+        let low = SymbolicBitVector::constant(variables, self.width(), low);
+        let high = SymbolicBitVector::constant(variables, self.width(), high.min(max));
+        low.le(self).and(&self.le(&high))
+    }
+
+    /// **(internal)** Ripple-carry addition of `self` and `other` with an initial `carry`.
+    fn add_with_carry(&self, other: &[Bdd], mut carry: Bdd) -> SymbolicBitVector {
+        let mut result = Vec::with_capacity(self.width());
+        for (a, b) in self.0.iter().zip(other) {
+            let a_xor_b = a.xor(b);
+            result.push(a_xor_b.xor(&carry));
+            carry = a.and(b).or(&carry.and(&a_xor_b));
+        }
+        SymbolicBitVector(result)
+    }
+
+    /// **(internal)** Unsigned comparison, where `equal` is the result for equal vectors.
+    fn compare(&self, other: &SymbolicBitVector, equal: Bdd) -> Bdd {
+        // Going from the least significant bit, the more significant bits decide the result
+        // unless they are equal.
+        self.0.iter().zip(&other.0).fold(equal, |result, (a, b)| {
+            let smaller = a.not().and(b);
+            smaller.or(&a.iff(b).and(&result))
+        })
+    }
+
+    /// **(internal)** A `false` constant with the same number of variables as the bits
+    /// (there is always at least one bit).
+    fn false_bdd(&self) -> Bdd {
+        Bdd::mk_false(self.0[0].num_vars())
+    }
+
+    /// **(internal)** A `true` constant with the same number of variables as the bits.
+    fn true_bdd(&self) -> Bdd {
+        self.false_bdd().not()
+    }
+
+    /// **(internal)** Panic if the two vectors have different widths.
+    fn check_width(&self, other: &SymbolicBitVector) {
+        if self.width() != other.width() {
+            panic!(
+                "Bit-vectors have different widths ({} vs. {}).",
+                self.width(),
+                other.width()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitvector::SymbolicBitVector;
+    use crate::{BddVariableSetBuilder, ValuationsOfClauseIterator};
+
+    #[test]
+    fn symbolic_bitvector_arithmetic() {
+        let mut builder = BddVariableSetBuilder::new();
+        let xy = builder.make_interleaved_bitvectors(&["x", "y"], 3);
+        let variables = builder.build();
+        let x = xy[0].to_symbolic(&variables);
+        let y = xy[1].to_symbolic(&variables);
+        let sum = x.add(&y);
+        let diff = x.sub(&y);
+        let eq = x.mk_eq(&y);
+        let lt = x.lt(&y);
+        let le = x.le(&y);
+        for valuation in ValuationsOfClauseIterator::new_unconstrained(6) {
+            let a = xy[0].value_in(&valuation);
+            let b = xy[1].value_in(&valuation);
+            assert_eq!(a, x.eval_in(&valuation));
+            assert_eq!((a + b) % 8, sum.eval_in(&valuation));
+            assert_eq!((a + 8 - b) % 8, diff.eval_in(&valuation));
+            assert_eq!(a == b, eq.eval_in(&valuation));
+            assert_eq!(a < b, lt.eval_in(&valuation));
+            assert_eq!(a <= b, le.eval_in(&valuation));
+        }
+    }
+
+    #[test]
+    fn symbolic_bitvector_constants() {
+        let mut builder = BddVariableSetBuilder::new();
+        let x = builder.make_bitvector("x", 4);
+        let variables = builder.build();
+        let seven = SymbolicBitVector::constant(&variables, 4, 7);
+        assert_eq!(4, seven.width());
+        assert!(seven.bits()[0].is_true());
+        assert!(seven.bits()[3].is_false());
+        let x_sym = x.to_symbolic(&variables);
+        assert_eq!(x.mk_value(&variables, 7), x_sym.mk_eq(&seven));
+        assert_eq!(
+            x.mk_in_range(&variables, 0, 7),
+            x_sym.lt(&SymbolicBitVector::constant(&variables, 4, 8))
+        );
+        assert!(x_sym.in_range(&variables, 0, 100).is_true());
+        assert!(x_sym.in_range(&variables, 16, 100).is_false());
+    }
+
+    #[test]
+    #[should_panic]
+    fn symbolic_bitvector_width_mismatch() {
+        let mut builder = BddVariableSetBuilder::new();
+        let x = builder.make_bitvector("x", 4);
+        let y = builder.make_bitvector("y", 3);
+        let variables = builder.build();
+        x.to_symbolic(&variables).add(&y.to_symbolic(&variables));
+    }
+
+    #[test]
+    #[should_panic]
+    fn symbolic_bitvector_empty() {
+        SymbolicBitVector::from_bits(Vec::new());
+    }
+}
@@ -0,0 +1,53 @@
+//! Bit-vectors encode bounded (unsigned) integer variables using several Boolean `Bdd`
+//! variables.
+//!
+//! A `BitVectorVariable` is allocated using a `BddVariableSetBuilder`, either as a block
+//! of consecutive variables (`make_bitvector`), or interleaved with other bit-vectors
+//! (`make_interleaved_bitvectors`), which is typically much better for arithmetic and
+//! comparisons between vectors. Individual bits are named `{name}_b{i}`.
+//!
+//! A `SymbolicBitVector` is then a vector of `Bdd`s (one for each bit), which can represent
+//! the value of a variable, a constant, or the result of some arithmetic operation. Comparing
+//! two symbolic vectors gives a `Bdd`:
+//!
+//! ```rust
+//! use biodivine_lib_bdd::BddVariableSetBuilder;
+//! use biodivine_lib_bdd::bitvector::SymbolicBitVector;
+//!
+//! let mut builder = BddVariableSetBuilder::new();
+//! let xy = builder.make_interleaved_bitvectors(&["x", "y"], 4);
+//! let variables = builder.build();
+//!
+//! let x = xy[0].to_symbolic(&variables);
+//! let y = xy[1].to_symbolic(&variables);
+//! let five = SymbolicBitVector::constant(&variables, 4, 5);
+//! // All pairs where x + y = 5 (modulo 16) and x < y.
+//! let bdd = x.add(&y).mk_eq(&five).and(&x.lt(&y));
+//! // Pairs (0, 5), (1, 4), (2, 3), plus five pairs where x + y = 21.
+//! assert_eq!(8.0, bdd.cardinality());
+//! for valuation in bdd.sat_valuations() {
+//!     assert!(xy[0].value_in(&valuation) < xy[1].value_in(&valuation));
+//! }
+//! ```
+
+use crate::{Bdd, BddVariable};
+
+/// **(internal)** Allocation of bit-vector variables and operations on them.
+mod _impl_bitvector_variable;
+
+/// **(internal)** Arithmetic and comparison operations on symbolic bit-vectors.
+mod _impl_symbolic_bitvector;
+
+/// An unsigned integer variable encoded using a fixed number of `BddVariable`s.
+///
+/// The bits are stored starting with the least significant bit.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BitVectorVariable {
+    name: String,
+    bits: Vec<BddVariable>,
+}
+
+/// A vector of `Bdd`s, each describing one bit of an unsigned integer (starting with
+/// the least significant bit). Arithmetic is performed modulo $2^{width}$.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SymbolicBitVector(Vec<Bdd>);
@@ -1,12 +1,13 @@
-use super::super::{Bdd, BddVariableSet};
-use super::BooleanExpression;
+use super::super::{Bdd, BddVariable, BddVariableSet};
+use super::_impl_parser::{parse_boolean_expression, parse_boolean_expression_in, NAME_DELIMITERS};
 use super::BooleanExpression::*;
-use super::_impl_parser::parse_boolean_expression;
+use super::{BooleanExpression, ExpressionError, ParseError};
+use crate::NOT_IN_VAR_NAME;
 use std::convert::TryFrom;
 use std::fmt::{Display, Error, Formatter};
 
 impl TryFrom<&str> for BooleanExpression {
-    type Error = String;
+    type Error = ParseError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         parse_boolean_expression(value)
@@ -17,14 +18,124 @@ impl Display for BooleanExpression {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         match self {
             Const(value) => write!(f, "{}", value),
-            Variable(name) => write!(f, "{}", name),
+            Variable(name) => {
+                if needs_quotes(name) {
+                    write!(f, "\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+                } else {
+                    write!(f, "{}", name)
+                }
+            }
             Not(inner) => write!(f, "!{}", inner),
             And(l, r) => write!(f, "({} & {})", l, r),
             Or(l, r) => write!(f, "({} | {})", l, r),
             Xor(l, r) => write!(f, "({} ^ {})", l, r),
             Imp(l, r) => write!(f, "({} => {})", l, r),
             Iff(l, r) => write!(f, "({} <=> {})", l, r),
+            Exists(vars, inner) => {
+                write!(f, "(exists ")?;
+                write_names(f, vars)?;
+                write!(f, ": {})", inner)
+            }
+            ForAll(vars, inner) => {
+                write!(f, "(forall ")?;
+                write_names(f, vars)?;
+                write!(f, ": {})", inner)
+            }
+            Ite(c, t, e) => write!(f, "ite({}, {}, {})", c, t, e),
+            Conjunction(args) => write_function(f, "and", None, args),
+            Disjunction(args) => write_function(f, "or", None, args),
+            AtMost(k, args) => write_function(f, "atmost", Some(*k), args),
+            Exactly(k, args) => write_function(f, "exactly", Some(*k), args),
+        }
+    }
+}
+
+/// **(internal)** Write a comma separated list of (possibly quoted) variable names.
+fn write_names(f: &mut Formatter<'_>, names: &[String]) -> Result<(), Error> {
+    for (i, name) in names.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", Variable(name.clone()))?;
+    }
+    Ok(())
+}
+
+/// **(internal)** Write a function-style operator with an optional numeric first argument.
+fn write_function(
+    f: &mut Formatter<'_>,
+    name: &str,
+    bound: Option<usize>,
+    args: &[BooleanExpression],
+) -> Result<(), Error> {
+    write!(f, "{}(", name)?;
+    let mut first = true;
+    if let Some(bound) = bound {
+        write!(f, "{}", bound)?;
+        first = false;
+    }
+    for arg in args {
+        if !first {
+            write!(f, ", ")?;
         }
+        write!(f, "{}", arg)?;
+        first = false;
+    }
+    write!(f, ")")
+}
+
+/// **(internal)** True if the variable name must be quoted to be parsed as a single variable.
+fn needs_quotes(name: &str) -> bool {
+    let special =
+        |c: char| c.is_whitespace() || NOT_IN_VAR_NAME.contains(&c) || NAME_DELIMITERS.contains(&c);
+    name.is_empty()
+        || matches!(name, "true" | "false" | "0" | "1")
+        || name.contains("->")
+        || name.chars().any(special)
+}
+
+impl ParseError {
+    /// **(internal)** Create a new error at the given position.
+    pub(super) fn new(position: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "At position {}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Display for ExpressionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            ExpressionError::UnknownVariable(name) => write!(f, "Unknown variable '{}'.", name),
+            ExpressionError::TooManyVariables => write!(
+                f,
+                "Too many variables. There can be at most {} variables.",
+                u16::MAX - 1
+            ),
+            ExpressionError::IncompatibleValuation(valuation, variables) => write!(
+                f,
+                "Valuation is incompatible with the variable set ({} vs. {} variables).",
+                valuation, variables
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+/// Allows existing code which expects a `String` error to keep using `?`.
+impl From<ParseError> for String {
+    fn from(value: ParseError) -> Self {
+        value.message
     }
 }
 
@@ -66,7 +177,101 @@ impl BddVariableSet {
                 let right = self.safe_eval_expression(r)?;
                 Some(left.iff(&right))
             }
+            Exists(vars, inner) => {
+                let vars = self.vars_by_names(vars)?;
+                Some(self.safe_eval_expression(inner)?.exists(&vars))
+            }
+            ForAll(vars, inner) => {
+                let vars = self.vars_by_names(vars)?;
+                Some(self.safe_eval_expression(inner)?.for_all(&vars))
+            }
+            Ite(c, t, e) => {
+                let c = self.safe_eval_expression(c)?;
+                let t = self.safe_eval_expression(t)?;
+                let e = self.safe_eval_expression(e)?;
+                Some(Bdd::if_then_else(&c, &t, &e))
+            }
+            Conjunction(args) => {
+                let mut result = self.mk_true();
+                for arg in args {
+                    result = result.and(&self.safe_eval_expression(arg)?);
+                }
+                Some(result)
+            }
+            Disjunction(args) => {
+                let mut result = self.mk_false();
+                for arg in args {
+                    result = result.or(&self.safe_eval_expression(arg)?);
+                }
+                Some(result)
+            }
+            AtMost(k, args) => {
+                // At most `args.len()` arguments can be true, so larger bounds are equivalent.
+                let k = (*k).min(args.len());
+                if let Some(vars) = self.plain_variables(args) {
+                    return Some(self.mk_sat_up_to_k(k, &vars));
+                }
+                let counts = self.eval_counts(k, args)?;
+                Some(counts.iter().fold(self.mk_false(), |a, b| a.or(b)))
+            }
+            Exactly(k, args) => {
+                if *k > args.len() {
+                    // Still check that all variables are known.
+                    for arg in args {
+                        self.safe_eval_expression(arg)?;
+                    }
+                    return Some(self.mk_false());
+                }
+                if let Some(vars) = self.plain_variables(args) {
+                    return Some(self.mk_sat_exactly_k(*k, &vars));
+                }
+                let mut counts = self.eval_counts(*k, args)?;
+                Some(counts.pop().unwrap())
+            }
+        }
+    }
+
+    /// **(internal)** Resolve a list of variable names, or `None` if some name is unknown.
+    fn vars_by_names(&self, names: &[String]) -> Option<Vec<BddVariable>> {
+        names.iter().map(|name| self.var_by_name(name)).collect()
+    }
+
+    /// **(internal)** If all expressions are distinct known variables, return them (used to
+    /// compute cardinality constraints directly). Repeated variables must be counted multiple
+    /// times, which the direct construction cannot do.
+    fn plain_variables(&self, args: &[BooleanExpression]) -> Option<Vec<BddVariable>> {
+        let vars: Vec<BddVariable> = args
+            .iter()
+            .map(|arg| match arg {
+                Variable(name) => self.var_by_name(name),
+                _ => None,
+            })
+            .collect::<Option<_>>()?;
+        let mut unique = vars.clone();
+        unique.sort();
+        unique.dedup();
+        (unique.len() == vars.len()).then_some(vars)
+    }
+
+    /// **(internal)** Compute a vector of `Bdd`s where the `i`-th item is satisfied when
+    /// exactly `i` of the given expressions are true, for `i` in `0..=k`.
+    fn eval_counts(&self, k: usize, args: &[BooleanExpression]) -> Option<Vec<Bdd>> {
+        let mut counts = vec![self.mk_false(); k + 1];
+        counts[0] = self.mk_true();
+        for arg in args {
+            let arg = self.safe_eval_expression(arg)?;
+            let not_arg = arg.not();
+            // Go from the highest count so that `counts[i - 1]` is still the old value.
+            for i in (0..=k).rev() {
+                let stay = counts[i].and(&not_arg);
+                counts[i] = if i > 0 {
+                    stay.or(&counts[i - 1].and(&arg))
+                } else {
+                    stay
+                };
+            }
         }
+        Some(counts)
     }
 
     /// Evaluate the given `BooleanExpression` in the context of this `BddVariableSet`. Panic if some
@@ -77,9 +282,11 @@ impl BddVariableSet {
 
     /// Evaluate the given `String` as a `BooleanExpression` in the context of this `BddVariableSet`.
     ///
-    /// Panics if the expression cannot be parsed or contains unknown variables.
+    /// Panics if the expression cannot be parsed or contains unknown variables. This also
+    /// includes an unquoted `0` or `1` when this set has a variable of the same name, since
+    /// it is not clear whether the constant or the variable is meant.
     pub fn eval_expression_string(&self, expression: &str) -> Bdd {
-        let parsed = BooleanExpression::try_from(expression).unwrap();
+        let parsed = parse_boolean_expression_in(expression, self).unwrap();
         self.eval_expression(&parsed)
     }
 }
@@ -87,7 +294,9 @@ impl BddVariableSet {
 #[cfg(test)]
 mod tests {
     use super::super::super::BddVariableSet;
+    use super::BooleanExpression;
     use crate::bdd;
+    use std::convert::TryFrom;
 
     #[test]
     fn bdd_universe_eval_boolean_formula() {
@@ -104,4 +313,78 @@ mod tests {
 
         assert_eq!(expected, evaluated);
     }
+
+    #[test]
+    fn bdd_universe_eval_extended_formula() {
+        let variables = BddVariableSet::new(&["a", "b", "c", "d"]);
+        let eval = |e: &str| variables.eval_expression_string(e);
+
+        assert_eq!(eval("b | c"), eval("exists a, d: (a & b) | (c & !d)"));
+        assert_eq!(eval("false"), eval("forall a: a & b"));
+        assert_eq!(eval("(a & b) | (!a & c)"), eval("ite(a, b, c)"));
+        assert_eq!(eval("a & b & c"), eval("and(a, b, c)"));
+        assert_eq!(eval("a | b | c"), eval("or(a, b, c)"));
+        assert_eq!(eval("true"), eval("and()"));
+        assert_eq!(eval("false"), eval("or()"));
+
+        let a = variables.var_by_name("a").unwrap();
+        let b = variables.var_by_name("b").unwrap();
+        let c = variables.var_by_name("c").unwrap();
+        assert_eq!(
+            variables.mk_sat_up_to_k(1, &[a, b, c]),
+            eval("atmost(1, a, b, c)")
+        );
+        assert_eq!(
+            variables.mk_sat_exactly_k(2, &[a, b, c]),
+            eval("exactly(2, a, b, c)")
+        );
+        // Non-variable arguments use the general counting procedure.
+        assert_eq!(
+            eval("(a & b & !(c | d)) | (a & !b & (c | d)) | (!a & b & (c | d))"),
+            eval("exactly(2, a, b, c | d)")
+        );
+        assert_eq!(eval("!(a & b & (c | d))"), eval("atmost(2, a, b, (c | d))"));
+        assert_eq!(eval("false"), eval("exactly(3, a, (b & !b))"));
+
+        // Repeated arguments are counted multiple times.
+        assert_eq!(eval("!a"), eval("atmost(1, a, a)"));
+        assert_eq!(eval("atmost(1, a, (a & a))"), eval("atmost(1, a, a)"));
+        assert_eq!(eval("a"), eval("exactly(2, a, a)"));
+        assert_eq!(eval("exactly(2, a, (a & a))"), eval("exactly(2, a, a)"));
+        assert_eq!(eval("!a & b"), eval("exactly(1, a, b, a)"));
+        // Bounds larger than the number of arguments.
+        assert_eq!(eval("true"), eval("atmost(18446744073709551615, a & b)"));
+        assert_eq!(eval("true"), eval("atmost(100000000, a, b, c)"));
+        assert_eq!(eval("false"), eval("exactly(18446744073709551615, a & b)"));
+        assert_eq!(eval("false"), eval("exactly(100000000, a, b, c)"));
+        assert_eq!(eval("a & b & c"), eval("exactly(3, a, b, c)"));
+        assert!(variables
+            .safe_eval_expression(&BooleanExpression::try_from("exists x: a").unwrap())
+            .is_none());
+    }
+
+    #[test]
+    fn bdd_universe_eval_quoted_names() {
+        let variables = BddVariableSet::new(&["a\"b", "x:y", "p,q", "1"]);
+        let e = BooleanExpression::try_from("\"a\\\"b\" & (\"x:y\" | !\"p,q\") ^ \"1\"").unwrap();
+        assert_eq!(
+            e,
+            BooleanExpression::try_from(format!("{}", e).as_str()).unwrap()
+        );
+        let a = variables.mk_var_by_name("a\"b");
+        let x = variables.mk_var_by_name("x:y");
+        let p = variables.mk_var_by_name("p,q");
+        let one = variables.mk_var_by_name("1");
+        let expected = a.and(&x.or(&p.not()).xor(&one));
+        assert_eq!(expected, variables.eval_expression(&e));
+    }
+
+    #[test]
+    fn parse_error_into_string() {
+        fn parse(input: &str) -> Result<BooleanExpression, String> {
+            Ok(BooleanExpression::try_from(input)?)
+        }
+        assert!(parse("a & b").is_ok());
+        assert_eq!(Err("Unexpected ')'.".to_string()), parse("a & b)"));
+    }
 }
@@ -0,0 +1,247 @@
+use super::BooleanExpression::*;
+use super::{BooleanExpression, ExpressionError};
+use crate::op_function;
+use crate::{BddPartialValuation, BddValuation, BddVariable, BddVariableSet};
+
+/// Direct evaluation of `BooleanExpression` trees in (partial) valuations, without
+/// building a `Bdd`.
+impl BooleanExpression {
+    /// Evaluate this expression in the given `valuation`, resolving variable names using
+    /// `variables`.
+    ///
+    /// Returns an error if the expression contains a variable that is not in `variables`,
+    /// or if the `valuation` does not have the same number of variables as `variables`.
+    pub fn eval(
+        &self,
+        variables: &BddVariableSet,
+        valuation: &BddValuation,
+    ) -> Result<bool, ExpressionError> {
+        if valuation.num_vars() != variables.num_vars() {
+            return Err(ExpressionError::IncompatibleValuation(
+                valuation.num_vars(),
+                variables.num_vars(),
+            ));
+        }
+        let value_of = |var: BddVariable| Some(valuation[var]);
+        let result = self.eval_kleene(variables, &value_of, &mut Vec::new())?;
+        Ok(result.expect("Evaluation in a total valuation must have a result."))
+    }
+
+    /// Evaluate this expression in the given partial `valuation` using the three-valued
+    /// (Kleene) semantics: the result is `None` if it depends on the value of some variable
+    /// which is not fixed in the `valuation`.
+    ///
+    /// Note that the semantics is not exact: for example, `a | !a` evaluates to `None`
+    /// when `a` is unknown.
+    ///
+    /// Returns an error if the expression contains a variable that is not in `variables`.
+    pub fn eval_partial(
+        &self,
+        variables: &BddVariableSet,
+        valuation: &BddPartialValuation,
+    ) -> Result<Option<bool>, ExpressionError> {
+        let value_of = |var: BddVariable| valuation.get_value(var);
+        self.eval_kleene(variables, &value_of, &mut Vec::new())
+    }
+
+    /// **(internal)** Three-valued evaluation where variable values are given by `value_of`,
+    /// but they can be overridden by the values of quantified variables in `bound`
+    /// (later items take precedence).
+    fn eval_kleene(
+        &self,
+        variables: &BddVariableSet,
+        value_of: &dyn Fn(BddVariable) -> Option<bool>,
+        bound: &mut Vec<(BddVariable, bool)>,
+    ) -> Result<Option<bool>, ExpressionError> {
+        let mut eval = |e: &BooleanExpression| e.eval_kleene(variables, value_of, bound);
+        Ok(match self {
+            Const(value) => Some(*value),
+            Variable(name) => {
+                let var = resolve(variables, name)?;
+                match bound.iter().rev().find(|(v, _)| *v == var) {
+                    Some((_, value)) => Some(*value),
+                    None => value_of(var),
+                }
+            }
+            Not(inner) => eval(inner)?.map(|it| !it),
+            And(l, r) => op_function::and(eval(l)?, eval(r)?),
+            Or(l, r) => op_function::or(eval(l)?, eval(r)?),
+            Xor(l, r) => op_function::xor(eval(l)?, eval(r)?),
+            Imp(l, r) => op_function::imp(eval(l)?, eval(r)?),
+            Iff(l, r) => op_function::iff(eval(l)?, eval(r)?),
+            Ite(c, t, e) => match (eval(c)?, eval(t)?, eval(e)?) {
+                (Some(true), t, _) => t,
+                (Some(false), _, e) => e,
+                (None, Some(t), Some(e)) if t == e => Some(t),
+                _ => None,
+            },
+            Conjunction(args) => {
+                let mut result = Some(true);
+                for arg in args {
+                    result = op_function::and(result, eval(arg)?);
+                }
+                result
+            }
+            Disjunction(args) => {
+                let mut result = Some(false);
+                for arg in args {
+                    result = op_function::or(result, eval(arg)?);
+                }
+                result
+            }
+            AtMost(k, args) | Exactly(k, args) => {
+                let (mut known_true, mut unknown) = (0, 0);
+                for arg in args {
+                    match eval(arg)? {
+                        Some(true) => known_true += 1,
+                        None => unknown += 1,
+                        Some(false) => {}
+                    }
+                }
+                if matches!(self, AtMost(_, _)) {
+                    if known_true > *k {
+                        Some(false)
+                    } else if known_true + unknown <= *k {
+                        Some(true)
+                    } else {
+                        None
+                    }
+                } else if known_true > *k || known_true + unknown < *k {
+                    Some(false)
+                } else if unknown == 0 {
+                    Some(true)
+                } else {
+                    None
+                }
+            }
+            Exists(vars, inner) | ForAll(vars, inner) => {
+                let vars = vars
+                    .iter()
+                    .map(|name| resolve(variables, name))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let combine = if matches!(self, Exists(_, _)) {
+                    op_function::or
+                } else {
+                    op_function::and
+                };
+                eval_quantified(variables, value_of, bound, &vars, inner, combine)?
+            }
+        })
+    }
+}
+
+/// **(internal)** Resolve a variable name, returning an error if it does not exist.
+fn resolve(variables: &BddVariableSet, name: &str) -> Result<BddVariable, ExpressionError> {
+    variables
+        .var_by_name(name)
+        .ok_or_else(|| ExpressionError::UnknownVariable(name.to_string()))
+}
+
+/// **(internal)** Evaluate `inner` for all values of `vars` and `combine` the results.
+fn eval_quantified(
+    variables: &BddVariableSet,
+    value_of: &dyn Fn(BddVariable) -> Option<bool>,
+    bound: &mut Vec<(BddVariable, bool)>,
+    vars: &[BddVariable],
+    inner: &BooleanExpression,
+    combine: fn(Option<bool>, Option<bool>) -> Option<bool>,
+) -> Result<Option<bool>, ExpressionError> {
+    let Some((var, rest)) = vars.split_first() else {
+        return inner.eval_kleene(variables, value_of, bound);
+    };
+    let mut results = [None, None];
+    for (i, value) in [false, true].into_iter().enumerate() {
+        bound.push((*var, value));
+        let result = eval_quantified(variables, value_of, bound, rest, inner, combine);
+        bound.pop();
+        results[i] = result?;
+    }
+    Ok(combine(results[0], results[1]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::boolean_expression::{BooleanExpression, ExpressionError};
+    use crate::{BddPartialValuation, BddValuation, BddVariableSet, ValuationsOfClauseIterator};
+    use std::convert::TryFrom;
+
+    #[test]
+    fn eval_matches_bdd() {
+        let vars = BddVariableSet::new(&["a", "b", "c", "d"]);
+        let formulas = [
+            "a & !b | c",
+            "!(a => b) <=> (c ^ !d)",
+            "ite(a, b ^ c, !(d | a))",
+            "and(a, !b, or(c, d)) | !or()",
+            "atmost(1, a, b & c, !d)",
+            "exactly(2, a, !b, c, d)",
+            "exists a, b: (a & c) | (b <=> d)",
+            "!forall c: (a => c) | (d & !b)",
+        ];
+        for formula in formulas {
+            let e = BooleanExpression::try_from(formula).unwrap();
+            let bdd = vars.eval_expression(&e);
+            for valuation in ValuationsOfClauseIterator::new_unconstrained(4) {
+                assert_eq!(bdd.eval_in(&valuation), e.eval(&vars, &valuation).unwrap());
+            }
+        }
+        let e = BooleanExpression::try_from("a & x").unwrap();
+        let valuation = ValuationsOfClauseIterator::new_unconstrained(4)
+            .next()
+            .unwrap();
+        assert_eq!(
+            Err(ExpressionError::UnknownVariable("x".to_string())),
+            e.eval(&vars, &valuation)
+        );
+    }
+
+    #[test]
+    fn eval_incompatible_valuation() {
+        let vars = BddVariableSet::new(&["a", "b", "c", "d"]);
+        let e = BooleanExpression::try_from("a & d").unwrap();
+        for num_vars in [2, 5] {
+            let valuation = BddValuation::all_true(num_vars);
+            assert_eq!(
+                Err(ExpressionError::IncompatibleValuation(num_vars, 4)),
+                e.eval(&vars, &valuation)
+            );
+        }
+        assert_eq!(Ok(true), e.eval(&vars, &BddValuation::all_true(4)));
+    }
+
+    #[test]
+    fn eval_partial_kleene() {
+        let vars = BddVariableSet::new(&["a", "b", "c", "d"]);
+        let [a, b, c, _d] = <[_; 4]>::try_from(vars.variables()).unwrap();
+        let valuation = BddPartialValuation::from_values(&[(a, true), (b, false)]);
+        let cases = [
+            ("a | c", Some(true)),
+            ("b & c", Some(false)),
+            ("a & c", None),
+            ("c | !c", None),
+            ("c ^ a", None),
+            ("b => c", Some(true)),
+            ("ite(c, a, !b)", Some(true)),
+            ("ite(a, c, b)", None),
+            ("atmost(1, a, b, c)", None),
+            ("atmost(2, a, b, c)", Some(true)),
+            ("exactly(0, a, c)", Some(false)),
+            ("exactly(1, a, b)", Some(true)),
+            ("exists c: c & a", Some(true)),
+            ("forall c: c | d", None),
+            ("exists a: !a", Some(true)),
+        ];
+        for (formula, expected) in cases {
+            let e = BooleanExpression::try_from(formula).unwrap();
+            assert_eq!(
+                expected,
+                e.eval_partial(&vars, &valuation).unwrap(),
+                "{}",
+                formula
+            );
+        }
+        let valuation = BddPartialValuation::from_values(&[(a, true), (b, true), (c, true)]);
+        let e = BooleanExpression::try_from("and(a, b, c)").unwrap();
+        assert_eq!(Some(true), e.eval_partial(&vars, &valuation).unwrap());
+    }
+}
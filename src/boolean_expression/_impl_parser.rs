@@ -1,203 +1,446 @@
+//! Expressions are first split into a flat list of positioned tokens, which is then parsed
+//! using precedence climbing. The operator precedence (from the weakest) is:
+//! `<=>`, `=>`, `|`, `&` and `^`. All binary operators are right-associative and
+//! negation binds tighter than any binary operator.
 //!
-//! Expression parsing proceeds in recursive manner in the order of operator precedence:
-//! `<=>`, `=>`, `|`, `&` and `^`. For each operator, if there is no occurrence in the root of the
-//! token tree, we forward the tree to next operator. If there is an occurrence, we split
-//! the token tree at this point. Left part goes to the next operator, right part is processed
-//! by the same operator to extract additional occurrences.
-
-use super::super::NOT_IN_VAR_NAME;
-use super::BooleanExpression;
+//! Besides the basic syntax, the parser also accepts operator aliases (`~` for `!`, `&&`
+//! for `&`, `||` for `|`, `->` for `=>` and `<->` for `<=>`), constants `0`/`1`
+//! (in addition to `false`/`true`), quoted variable names (`"my var"`, with `\"`
+//! and `\\` escapes) and line comments starting with `#`.
+//!
+//! Finally, there are quantifiers (`exists x, y: body` and `forall x, y: body`, where the body
+//! extends as far right as possible) and function-style operators: `ite(c, t, e)`, n-ary
+//! `and(...)` and `or(...)`, and cardinality constraints `atmost(k, ...)` and
+//! `exactly(k, ...)`. These keywords are only recognized in the described form, so they can
+//! still be used as ordinary variable names.
+
+use super::super::{BddVariableSet, NOT_IN_VAR_NAME};
 use super::BooleanExpression::*;
-use std::iter::Peekable;
-use std::str::Chars;
+use super::{BooleanExpression, ParseError};
 
 /// **(internal)** Tokens that can appear in the boolean expression.
-/// The tokens form a token tree defined by parenthesis groups.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 enum ExprToken {
-    Not,                    // '!'
-    And,                    // '&'
-    Or,                     // '|'
-    Xor,                    // '^'
-    Imp,                    // '=>'
-    Iff,                    // '<=>'
-    Id(String),             // 'variable'
-    Tokens(Vec<ExprToken>), // A block of tokens inside parentheses
+    Not,            // '!' or '~'
+    And,            // '&' or '&&'
+    Or,             // '|' or '||'
+    Xor,            // '^'
+    Imp,            // '=>' or '->'
+    Iff,            // '<=>' or '<->'
+    LeftParen,      // '('
+    RightParen,     // ')'
+    Comma,          // ','
+    Colon,          // ':'
+    Const(bool),    // 'true', 'false', '1' or '0'
+    Id(String),     // 'variable'
+    Quoted(String), // '"quoted variable"'
+}
+
+/// **(internal)** An `ExprToken` together with the position (character offset) where it starts.
+#[derive(Clone, Debug)]
+struct Token {
+    token: ExprToken,
+    position: usize,
 }
 
 /// Takes a `String` and turns it into a `BooleanExpression` or `Error` if the string is not valid.
 ///
 /// Syntax for the formula is described in the tutorial.
-pub fn parse_boolean_expression(from: &str) -> Result<BooleanExpression, String> {
-    let tokens = tokenize_group(&mut from.chars().peekable(), true)?;
-    Ok(*(parse_formula(&tokens)?))
+pub fn parse_boolean_expression(from: &str) -> Result<BooleanExpression, ParseError> {
+    parse(from, None)
+}
+
+/// Same as `parse_boolean_expression`, but an unquoted `0` or `1` is rejected when
+/// `variables` contain a variable of the same name (such a variable must be quoted
+/// and the constant can be written as `false`/`true`).
+pub(super) fn parse_boolean_expression_in(
+    from: &str,
+    variables: &BddVariableSet,
+) -> Result<BooleanExpression, ParseError> {
+    parse(from, Some(variables))
+}
+
+/// **(internal)** Shared implementation of `parse_boolean_expression` and
+/// `parse_boolean_expression_in`.
+fn parse(from: &str, variables: Option<&BddVariableSet>) -> Result<BooleanExpression, ParseError> {
+    let chars: Vec<char> = from.chars().collect();
+    let tokens = tokenize(&chars, variables)?;
+    let mut parser = Parser {
+        tokens,
+        index: 0,
+        end: chars.len(),
+    };
+    let result = parser.parse_binary(0)?;
+    if let Some(token) = parser.tokens.get(parser.index) {
+        return Err(ParseError::new(
+            token.position,
+            format!("Unexpected {}.", describe(&token.token)),
+        ));
+    }
+    Ok(*result)
+}
+
+/// **(internal)** Characters which (in addition to `NOT_IN_VAR_NAME`) cannot appear in
+/// an unquoted variable name. Variables can still use them, but have to be quoted.
+pub(super) const NAME_DELIMITERS: [char; 5] = ['~', '#', '"', ',', ':'];
+
+/// **(internal)** True if the character cannot appear in an unquoted variable name.
+fn is_name_boundary(chars: &[char], i: usize) -> bool {
+    let c = chars[i];
+    c.is_whitespace()
+        || NOT_IN_VAR_NAME.contains(&c)
+        || NAME_DELIMITERS.contains(&c)
+        || (c == '-' && chars.get(i + 1) == Some(&'>'))
 }
 
-/// **(internal)** Process a peekable iterator of characters into a vector of `ExprToken`s.
+/// **(internal)** Process a slice of characters into a vector of positioned tokens.
 ///
-/// The outer method always consumes the opening parenthesis and the recursive call consumes the
-/// closing parenthesis. Use `top_level` to indicate that there will be no closing parenthesis.
-fn tokenize_group(data: &mut Peekable<Chars>, top_level: bool) -> Result<Vec<ExprToken>, String> {
+/// If `variables` are given, `0` and `1` are only accepted as constants when they are not
+/// also names of variables.
+fn tokenize(chars: &[char], variables: Option<&BddVariableSet>) -> Result<Vec<Token>, ParseError> {
     let mut output = Vec::new();
-    while let Some(c) = data.next() {
-        match c {
-            c if c.is_whitespace() => { /* skip whitespace */ }
-            // single char operators
-            '!' => output.push(ExprToken::Not),
-            '&' => output.push(ExprToken::And),
-            '|' => output.push(ExprToken::Or),
-            '^' => output.push(ExprToken::Xor),
-            '=' => {
-                if Some('>') == data.next() {
-                    output.push(ExprToken::Imp);
-                } else {
-                    return Err("Expected '>' after '='.".to_string());
-                }
+    let mut i = 0;
+    while i < chars.len() {
+        let position = i;
+        // Check if the characters starting at `i` are exactly `expected`.
+        let starts_with = |expected: &str| {
+            expected
+                .chars()
+                .enumerate()
+                .all(|(k, e)| chars.get(i + k) == Some(&e))
+        };
+        let (token, length) = match chars[i] {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
             }
-            '<' => {
-                if Some('=') == data.next() {
-                    if Some('>') == data.next() {
-                        output.push(ExprToken::Iff)
-                    } else {
-                        return Err("Expected '>' after '='.".to_string());
-                    }
-                } else {
-                    return Err("Expected '=' after '<'.".to_string());
+            '#' => {
+                // comment: skip until the end of line
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
                 }
+                continue;
             }
-            // '>' is invalid as a start of a token
-            '>' => return Err("Unexpected '>'.".to_string()),
-            ')' => {
-                return if !top_level {
-                    Ok(output)
-                } else {
-                    Err("Unexpected ')'.".to_string())
+            '!' | '~' => (ExprToken::Not, 1),
+            '^' => (ExprToken::Xor, 1),
+            '(' => (ExprToken::LeftParen, 1),
+            ')' => (ExprToken::RightParen, 1),
+            ',' => (ExprToken::Comma, 1),
+            ':' => (ExprToken::Colon, 1),
+            '&' if starts_with("&&") => (ExprToken::And, 2),
+            '&' => (ExprToken::And, 1),
+            '|' if starts_with("||") => (ExprToken::Or, 2),
+            '|' => (ExprToken::Or, 1),
+            '=' if starts_with("=>") => (ExprToken::Imp, 2),
+            '-' if starts_with("->") => (ExprToken::Imp, 2),
+            '<' if starts_with("<=>") || starts_with("<->") => (ExprToken::Iff, 3),
+            '=' => return Err(ParseError::new(i, "Expected '>' after '='.")),
+            '<' => return Err(ParseError::new(i, "Expected '=>' or '->' after '<'.")),
+            '>' => return Err(ParseError::new(i, "Unexpected '>'.")),
+            '"' => {
+                let mut name = String::new();
+                let mut k = i + 1;
+                loop {
+                    match chars.get(k) {
+                        None => return Err(ParseError::new(i, "Unterminated quoted name.")),
+                        Some('"') => break,
+                        Some('\\') if k + 1 < chars.len() => {
+                            name.push(chars[k + 1]);
+                            k += 2;
+                        }
+                        Some(c) => {
+                            name.push(*c);
+                            k += 1;
+                        }
+                    }
                 }
-            }
-            '(' => {
-                // start a nested token group
-                let tokens = tokenize_group(data, false)?;
-                output.push(ExprToken::Tokens(tokens));
+                (ExprToken::Quoted(name), k + 1 - i)
             }
             _ => {
                 // start of a variable name
-                let mut name = vec![c];
-                while let Some(c) = data.peek() {
-                    if c.is_whitespace() || NOT_IN_VAR_NAME.contains(c) {
-                        break;
-                    } else {
-                        name.push(*c);
-                        data.next(); // advance iterator
-                    }
+                let mut k = i + 1;
+                while k < chars.len() && !is_name_boundary(chars, k) {
+                    k += 1;
                 }
-                output.push(ExprToken::Id(name.into_iter().collect()));
+                let name: String = chars[i..k].iter().collect();
+                let ambiguous = variables.and_then(|it| it.var_by_name(&name)).is_some();
+                if ambiguous && (name == "0" || name == "1") {
+                    return Err(ParseError::new(
+                        i,
+                        format!(
+                            "Ambiguous '{}': quote the variable name or use 'true'/'false'.",
+                            name
+                        ),
+                    ));
+                }
+                let token = match name.as_str() {
+                    "true" | "1" => ExprToken::Const(true),
+                    "false" | "0" => ExprToken::Const(false),
+                    _ => ExprToken::Id(name),
+                };
+                (token, k - i)
             }
-        }
-    }
-    if top_level {
-        Ok(output)
-    } else {
-        Err("Expected ')'.".to_string())
+        };
+        output.push(Token { token, position });
+        i += length;
     }
+    Ok(output)
 }
 
-/// **(internal)** Parse a `ExprToken` tree into a `BooleanExpression` (or error if invalid).
-fn parse_formula(data: &[ExprToken]) -> Result<Box<BooleanExpression>, String> {
-    iff(data)
+/// **(internal)** Human readable description of a token for error messages.
+fn describe(token: &ExprToken) -> String {
+    match token {
+        ExprToken::Not => "'!'".to_string(),
+        ExprToken::And => "'&'".to_string(),
+        ExprToken::Or => "'|'".to_string(),
+        ExprToken::Xor => "'^'".to_string(),
+        ExprToken::Imp => "'=>'".to_string(),
+        ExprToken::Iff => "'<=>'".to_string(),
+        ExprToken::LeftParen => "'('".to_string(),
+        ExprToken::RightParen => "')'".to_string(),
+        ExprToken::Comma => "','".to_string(),
+        ExprToken::Colon => "':'".to_string(),
+        ExprToken::Const(value) => format!("constant '{}'", value),
+        ExprToken::Id(name) | ExprToken::Quoted(name) => format!("variable '{}'", name),
+    }
 }
 
-/// **(internal)** Utility method to find first occurrence of a specific token in the token tree.
-fn index_of_first(data: &[ExprToken], token: ExprToken) -> Option<usize> {
-    data.iter().position(|t| *t == token)
+/// **(internal)** Precedence of a binary operator token (higher binds tighter), or `None`
+/// if the token is not a binary operator.
+fn precedence(token: &ExprToken) -> Option<u8> {
+    match token {
+        ExprToken::Iff => Some(1),
+        ExprToken::Imp => Some(2),
+        ExprToken::Or => Some(3),
+        ExprToken::And => Some(4),
+        ExprToken::Xor => Some(5),
+        _ => None,
+    }
 }
 
-/// **(internal)** Recursive parsing step 1: extract `<=>` operators.
-fn iff(data: &[ExprToken]) -> Result<Box<BooleanExpression>, String> {
-    let iff_token = index_of_first(data, ExprToken::Iff);
-    Ok(if let Some(iff_token) = iff_token {
-        Box::new(Iff(
-            imp(&data[..iff_token])?,
-            iff(&data[(iff_token + 1)..])?,
-        ))
-    } else {
-        imp(data)?
-    })
+/// **(internal)** A precedence climbing parser over a list of tokens.
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    /// Position reported for errors at the end of the input.
+    end: usize,
 }
 
-/// **(internal)** Recursive parsing step 2: extract `=>` operators.
-fn imp(data: &[ExprToken]) -> Result<Box<BooleanExpression>, String> {
-    let imp_token = index_of_first(data, ExprToken::Imp);
-    Ok(if let Some(imp_token) = imp_token {
-        Box::new(Imp(or(&data[..imp_token])?, imp(&data[(imp_token + 1)..])?))
-    } else {
-        or(data)?
-    })
-}
+impl Parser {
+    /// Parse a sequence of unary expressions connected by binary operators with precedence
+    /// at least `min_precedence`.
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Box<BooleanExpression>, ParseError> {
+        let mut left = self.parse_unary()?;
+        while let Some(token) = self.tokens.get(self.index) {
+            let operator = token.token.clone();
+            let Some(op_precedence) = precedence(&operator) else {
+                break;
+            };
+            if op_precedence < min_precedence {
+                break;
+            }
+            self.index += 1;
+            // Using the same precedence for the right side makes the operator right-associative.
+            let right = self.parse_binary(op_precedence)?;
+            left = Box::new(match operator {
+                ExprToken::Iff => Iff(left, right),
+                ExprToken::Imp => Imp(left, right),
+                ExprToken::Or => Or(left, right),
+                ExprToken::And => And(left, right),
+                ExprToken::Xor => Xor(left, right),
+                _ => unreachable!("Only binary operators have precedence."),
+            });
+        }
+        Ok(left)
+    }
 
-/// **(internal)** Recursive parsing step 3: extract `|` operators.
-fn or(data: &[ExprToken]) -> Result<Box<BooleanExpression>, String> {
-    let or_token = index_of_first(data, ExprToken::Or);
-    Ok(if let Some(or_token) = or_token {
-        Box::new(Or(and(&data[..or_token])?, or(&data[(or_token + 1)..])?))
-    } else {
-        and(data)?
-    })
-}
+    /// Parse a negation, a parenthesised expression, a constant or a variable.
+    fn parse_unary(&mut self) -> Result<Box<BooleanExpression>, ParseError> {
+        let Some(token) = self.tokens.get(self.index).cloned() else {
+            return Err(ParseError::new(
+                self.end,
+                "Expected formula, found end of input.",
+            ));
+        };
+        self.index += 1;
+        match token.token {
+            ExprToken::Not => Ok(Box::new(Not(self.parse_unary()?))),
+            ExprToken::Const(value) => Ok(Box::new(Const(value))),
+            ExprToken::Id(name) if name == "exists" || name == "forall" => {
+                if !matches!(self.peek(), Some(ExprToken::Id(_) | ExprToken::Quoted(_))) {
+                    return Ok(Box::new(Variable(name)));
+                }
+                let variables = self.parse_quantified_variables()?;
+                let body = self.parse_binary(0)?;
+                Ok(Box::new(if name == "exists" {
+                    Exists(variables, body)
+                } else {
+                    ForAll(variables, body)
+                }))
+            }
+            ExprToken::Id(name) if self.peek() == Some(&ExprToken::LeftParen) => {
+                self.index += 1;
+                self.parse_function(name, token.position)
+            }
+            ExprToken::Id(name) | ExprToken::Quoted(name) => Ok(Box::new(Variable(name))),
+            ExprToken::LeftParen => {
+                let inner = self.parse_binary(0)?;
+                match self.tokens.get(self.index) {
+                    Some(Token {
+                        token: ExprToken::RightParen,
+                        ..
+                    }) => {
+                        self.index += 1;
+                        Ok(inner)
+                    }
+                    Some(other) => Err(ParseError::new(
+                        other.position,
+                        format!("Expected ')', but found {}.", describe(&other.token)),
+                    )),
+                    None => Err(ParseError::new(
+                        self.end,
+                        format!("Expected ')' matching '(' at position {}.", token.position),
+                    )),
+                }
+            }
+            other => Err(ParseError::new(
+                token.position,
+                format!(
+                    "Expected variable, constant or '(', but found {}.",
+                    describe(&other)
+                ),
+            )),
+        }
+    }
 
-/// **(internal)** Recursive parsing step 4: extract `&` operators.
-fn and(data: &[ExprToken]) -> Result<Box<BooleanExpression>, String> {
-    let and_token = index_of_first(data, ExprToken::And);
-    Ok(if let Some(and_token) = and_token {
-        Box::new(And(
-            xor(&data[..and_token])?,
-            and(&data[(and_token + 1)..])?,
-        ))
-    } else {
-        xor(data)?
-    })
-}
+    /// The next token, if any.
+    fn peek(&self) -> Option<&ExprToken> {
+        self.tokens.get(self.index).map(|it| &it.token)
+    }
 
-/// **(internal)** Recursive parsing step 5: extract `^` operators.
-fn xor(data: &[ExprToken]) -> Result<Box<BooleanExpression>, String> {
-    let xor_token = index_of_first(data, ExprToken::Xor);
-    Ok(if let Some(xor_token) = xor_token {
-        Box::new(Xor(
-            terminal(&data[..xor_token])?,
-            xor(&data[(xor_token + 1)..])?,
-        ))
-    } else {
-        terminal(data)?
-    })
-}
+    /// Consume the next token if it is equal to `expected`, otherwise return an error.
+    fn expect(&mut self, expected: ExprToken) -> Result<(), ParseError> {
+        match self.tokens.get(self.index) {
+            Some(token) if token.token == expected => {
+                self.index += 1;
+                Ok(())
+            }
+            Some(token) => Err(ParseError::new(
+                token.position,
+                format!(
+                    "Expected {}, but found {}.",
+                    describe(&expected),
+                    describe(&token.token)
+                ),
+            )),
+            None => Err(ParseError::new(
+                self.end,
+                format!("Expected {}, found end of input.", describe(&expected)),
+            )),
+        }
+    }
 
-/// **(internal)** Recursive parsing step 6: extract terminals and negations.
-fn terminal(data: &[ExprToken]) -> Result<Box<BooleanExpression>, String> {
-    if data.is_empty() {
-        Err("Expected formula, found nothing :(".to_string())
-    } else if data[0] == ExprToken::Not {
-        Ok(Box::new(Not(terminal(&data[1..])?)))
-    } else if data.len() > 1 {
-        Err(format!(
-            "Expected variable name or (...), but found {:?}.",
-            data
-        ))
-    } else {
-        match &data[0] {
-            ExprToken::Id(name) => {
-                if name == "true" {
-                    Ok(Box::new(Const(true)))
-                } else if name == "false" {
-                    Ok(Box::new(Const(false)))
-                } else {
-                    Ok(Box::new(Variable(name.clone())))
+    /// Parse the comma separated list of variable names of a quantifier, including
+    /// the final colon.
+    fn parse_quantified_variables(&mut self) -> Result<Vec<String>, ParseError> {
+        let mut variables = Vec::new();
+        loop {
+            match self.tokens.get(self.index) {
+                Some(Token {
+                    token: ExprToken::Id(name) | ExprToken::Quoted(name),
+                    ..
+                }) => variables.push(name.clone()),
+                Some(token) => {
+                    return Err(ParseError::new(
+                        token.position,
+                        format!(
+                            "Expected quantified variable, but found {}.",
+                            describe(&token.token)
+                        ),
+                    ))
+                }
+                None => {
+                    return Err(ParseError::new(
+                        self.end,
+                        "Expected quantified variable, found end of input.",
+                    ))
                 }
             }
-            ExprToken::Tokens(inner) => Ok(parse_formula(inner)?),
-            _ => unreachable!(
-                "Other tokens are matched by remaining functions, nothing else should remain."
-            ),
+            self.index += 1;
+            if self.peek() == Some(&ExprToken::Comma) {
+                self.index += 1;
+            } else {
+                self.expect(ExprToken::Colon)?;
+                return Ok(variables);
+            }
         }
     }
+
+    /// Parse the arguments of a function-style operator (the opening parenthesis is already
+    /// consumed) and build the corresponding expression.
+    fn parse_function(
+        &mut self,
+        name: String,
+        position: usize,
+    ) -> Result<Box<BooleanExpression>, ParseError> {
+        let bound = if name == "atmost" || name == "exactly" {
+            let bound = match self.tokens.get(self.index) {
+                Some(Token {
+                    token: ExprToken::Const(value),
+                    ..
+                }) => Some(usize::from(*value)),
+                Some(Token {
+                    token: ExprToken::Id(value),
+                    ..
+                }) => value.parse::<usize>().ok(),
+                _ => None,
+            };
+            let Some(bound) = bound else {
+                let position = self.tokens.get(self.index).map(|it| it.position);
+                return Err(ParseError::new(
+                    position.unwrap_or(self.end),
+                    format!("Expected a number as the first argument of '{}'.", name),
+                ));
+            };
+            self.index += 1;
+            if self.peek() != Some(&ExprToken::RightParen) {
+                self.expect(ExprToken::Comma)?;
+            }
+            Some(bound)
+        } else {
+            None
+        };
+        let mut arguments = Vec::new();
+        if self.peek() != Some(&ExprToken::RightParen) {
+            arguments.push(*self.parse_binary(0)?);
+            while self.peek() == Some(&ExprToken::Comma) {
+                self.index += 1;
+                arguments.push(*self.parse_binary(0)?);
+            }
+        }
+        self.expect(ExprToken::RightParen)?;
+        Ok(Box::new(match (name.as_str(), bound) {
+            ("and", _) => Conjunction(arguments),
+            ("or", _) => Disjunction(arguments),
+            ("atmost", Some(k)) => AtMost(k, arguments),
+            ("exactly", Some(k)) => Exactly(k, arguments),
+            ("ite", _) => {
+                let Ok([c, t, e]) = <[BooleanExpression; 3]>::try_from(arguments) else {
+                    return Err(ParseError::new(
+                        position,
+                        "Operator 'ite' expects exactly three arguments.",
+                    ));
+                };
+                Ite(Box::new(c), Box::new(t), Box::new(e))
+            }
+            _ => {
+                return Err(ParseError::new(
+                    position,
+                    format!("Unknown operator '{}'.", name),
+                ))
+            }
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -330,4 +573,147 @@ mod tests {
     fn parse_boolean_formula_invalid_formula_2() {
         parse_boolean_expression("a & c d & b").unwrap();
     }
+
+    #[test]
+    fn parse_boolean_formula_aliases() {
+        assert_eq!(
+            parse_boolean_expression("!a & b | c => d <=> e").unwrap(),
+            parse_boolean_expression("~a && b || c -> d <-> e").unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_boolean_formula_constants() {
+        assert_eq!(
+            "((true & false) | !true)",
+            format!("{}", parse_boolean_expression("1 & 0 | !1").unwrap())
+        );
+        // Constants are only recognized as whole names.
+        assert_eq!(
+            Variable("x1".to_string()),
+            parse_boolean_expression("x1").unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_boolean_formula_ambiguous_constants() {
+        let variables = BddVariableSet::new(&["1", "z"]);
+        let error = parse_boolean_expression_in("1 & z", &variables).unwrap_err();
+        assert_eq!(0, error.position);
+        assert_eq!(
+            "((\"1\" & z) | false)",
+            format!(
+                "{}",
+                parse_boolean_expression_in("\"1\" & z | 0", &variables).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn parse_boolean_formula_names() {
+        // Dashes are allowed in names, unless they form `->`.
+        assert_eq!(
+            "(x-1 => y)",
+            format!("{}", parse_boolean_expression("x-1->y").unwrap())
+        );
+        let quoted = parse_boolean_expression("\"my var\" & \"a\\\"b\" | \"1\"").unwrap();
+        assert_eq!(
+            Or(
+                Box::new(And(
+                    Box::new(Variable("my var".to_string())),
+                    Box::new(Variable("a\"b".to_string()))
+                )),
+                Box::new(Variable("1".to_string()))
+            ),
+            quoted
+        );
+        // Names that need quotes are quoted when printed.
+        assert_eq!("((\"my var\" & \"a\\\"b\") | \"1\")", format!("{}", quoted));
+        assert_eq!(
+            quoted,
+            parse_boolean_expression(&format!("{}", quoted)).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_boolean_formula_comments() {
+        let input = "# target: a\na & # first part\n  (b | c) # second part";
+        assert_eq!(
+            "(a & (b | c))",
+            format!("{}", parse_boolean_expression(input).unwrap())
+        );
+    }
+
+    #[test]
+    fn parse_boolean_formula_error_positions() {
+        let error = parse_boolean_expression("a = b").unwrap_err();
+        assert_eq!(2, error.position);
+        let error = parse_boolean_expression("a & & b").unwrap_err();
+        assert_eq!(4, error.position);
+        let error = parse_boolean_expression("a & c d & b").unwrap_err();
+        assert_eq!(6, error.position);
+        let error = parse_boolean_expression("a & (b").unwrap_err();
+        assert_eq!(6, error.position);
+        let error = parse_boolean_expression("a & b)").unwrap_err();
+        assert_eq!(5, error.position);
+        let error = parse_boolean_expression("\"abc").unwrap_err();
+        assert_eq!(0, error.position);
+        assert_eq!(
+            "At position 5: Unexpected ')'.",
+            format!("{}", parse_boolean_expression("a & b)").unwrap_err())
+        );
+    }
+
+    #[test]
+    fn parse_boolean_formula_quantifiers() {
+        assert_eq!(
+            "(a & (exists x, y: (x | (y & a))))",
+            format!(
+                "{}",
+                parse_boolean_expression("a & exists x, y: x | y & a").unwrap()
+            )
+        );
+        assert_eq!(
+            "((forall x: (x => a)) | b)",
+            format!(
+                "{}",
+                parse_boolean_expression("(forall x: x => a) | b").unwrap()
+            )
+        );
+        // Keywords are normal variables when not used as quantifiers.
+        assert_eq!(
+            "(exists & forall)",
+            format!("{}", parse_boolean_expression("exists & forall").unwrap())
+        );
+        assert!(parse_boolean_expression("exists x y: a").is_err());
+        assert!(parse_boolean_expression("exists x:").is_err());
+    }
+
+    #[test]
+    fn parse_boolean_formula_functions() {
+        let inputs = vec![
+            "ite(a, (b | c), !d)",
+            "and(a, b, c)",
+            "or(a, and(b, c))",
+            "and()",
+            "atmost(2, a, b, c)",
+            "exactly(1, a, (b & c))",
+            "exactly(0)",
+        ];
+        for input in inputs {
+            assert_eq!(
+                input,
+                format!("{}", parse_boolean_expression(input).unwrap())
+            );
+        }
+        // Function names are normal variables when not followed by '('.
+        assert_eq!(
+            "(and | ite)",
+            format!("{}", parse_boolean_expression("and | ite").unwrap())
+        );
+        assert!(parse_boolean_expression("ite(a, b)").is_err());
+        assert!(parse_boolean_expression("atmost(a, b)").is_err());
+        assert!(parse_boolean_expression("foo(a, b)").is_err());
+        assert!(parse_boolean_expression("and(a, b").is_err());
+    }
 }
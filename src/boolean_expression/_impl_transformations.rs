@@ -0,0 +1,716 @@
+use super::BooleanExpression::*;
+use super::{BooleanExpression, ExpressionError};
+use crate::{BddPartialValuation, BddVariable, BddVariableKind, BddVariableSet};
+use std::collections::{HashMap, HashSet};
+
+/// Syntactic transformations of `BooleanExpression` trees.
+///
+/// None of these methods builds a `Bdd`, so they also work for wide formulas where the `Bdd`
+/// would be too large.
+impl BooleanExpression {
+    /// Return the set of free variables which appear in this expression (variables bound
+    /// by a quantifier are not included, unless they also appear outside of its scope).
+    pub fn support_set(&self) -> HashSet<String> {
+        let mut result = HashSet::new();
+        self.collect_support(&mut Vec::new(), &mut result);
+        result
+    }
+
+    /// Replace free variables in this expression by the expressions given in `mapping`.
+    /// Variables that are not in the mapping are left unchanged.
+    ///
+    /// Variables bound by a quantifier are not substituted within its scope. Note that
+    /// the substitution is not capture-avoiding: if a substituted expression contains
+    /// a variable bound by a quantifier, it becomes bound as well.
+    pub fn substitute(&self, mapping: &HashMap<String, BooleanExpression>) -> BooleanExpression {
+        match self {
+            Variable(name) => mapping.get(name).cloned().unwrap_or_else(|| self.clone()),
+            Exists(vars, _) | ForAll(vars, _) if vars.iter().any(|v| mapping.contains_key(v)) => {
+                let mut mapping = mapping.clone();
+                for var in vars {
+                    mapping.remove(var);
+                }
+                self.map_children(|it| it.substitute(&mapping))
+            }
+            _ => self.map_children(|it| it.substitute(mapping)),
+        }
+    }
+
+    /// Remove all double negations (`!!a` becomes `a`).
+    pub fn eliminate_double_negation(&self) -> BooleanExpression {
+        match self {
+            Not(inner) => match inner.as_ref() {
+                Not(inner) => inner.eliminate_double_negation(),
+                _ => Not(Box::new(inner.eliminate_double_negation())),
+            },
+            _ => self.map_children(|it| it.eliminate_double_negation()),
+        }
+    }
+
+    /// Propagate constants through the expression, such that the result either is a constant,
+    /// or contains no constants at all.
+    pub fn simplify_constants(&self) -> BooleanExpression {
+        let expr = self.map_children(|it| it.simplify_constants());
+        match expr {
+            Not(inner) => match *inner {
+                Const(value) => Const(!value),
+                inner => Not(Box::new(inner)),
+            },
+            And(l, r) => match (*l, *r) {
+                (Const(false), _) | (_, Const(false)) => Const(false),
+                (Const(true), x) | (x, Const(true)) => x,
+                (l, r) => And(Box::new(l), Box::new(r)),
+            },
+            Or(l, r) => match (*l, *r) {
+                (Const(true), _) | (_, Const(true)) => Const(true),
+                (Const(false), x) | (x, Const(false)) => x,
+                (l, r) => Or(Box::new(l), Box::new(r)),
+            },
+            Xor(l, r) => match (*l, *r) {
+                (Const(false), x) | (x, Const(false)) => x,
+                (Const(true), x) | (x, Const(true)) => negate(x),
+                (l, r) => Xor(Box::new(l), Box::new(r)),
+            },
+            Imp(l, r) => match (*l, *r) {
+                (Const(false), _) | (_, Const(true)) => Const(true),
+                (Const(true), x) => x,
+                (x, Const(false)) => negate(x),
+                (l, r) => Imp(Box::new(l), Box::new(r)),
+            },
+            Iff(l, r) => match (*l, *r) {
+                (Const(true), x) | (x, Const(true)) => x,
+                (Const(false), x) | (x, Const(false)) => negate(x),
+                (l, r) => Iff(Box::new(l), Box::new(r)),
+            },
+            Ite(c, t, e) => match (*c, *t, *e) {
+                (Const(true), t, _) => t,
+                (Const(false), _, e) => e,
+                (c, Const(true), Const(false)) => c,
+                (c, Const(false), Const(true)) => negate(c),
+                (c, t, e) => Ite(Box::new(c), Box::new(t), Box::new(e)),
+            },
+            Exists(_, inner) | ForAll(_, inner) if matches!(*inner, Const(_)) => *inner,
+            Conjunction(args) => {
+                if args.contains(&Const(false)) {
+                    return Const(false);
+                }
+                let args: Vec<_> = args.into_iter().filter(|it| *it != Const(true)).collect();
+                match args.len() {
+                    0 => Const(true),
+                    1 => args.into_iter().next().unwrap(),
+                    _ => Conjunction(args),
+                }
+            }
+            Disjunction(args) => {
+                if args.contains(&Const(true)) {
+                    return Const(true);
+                }
+                let args: Vec<_> = args.into_iter().filter(|it| *it != Const(false)).collect();
+                match args.len() {
+                    0 => Const(false),
+                    1 => args.into_iter().next().unwrap(),
+                    _ => Disjunction(args),
+                }
+            }
+            AtMost(k, args) => {
+                let (fixed, args) = split_constants(args);
+                if fixed > k {
+                    Const(false)
+                } else if k - fixed >= args.len() {
+                    Const(true)
+                } else {
+                    AtMost(k - fixed, args)
+                }
+            }
+            Exactly(k, args) => {
+                let (fixed, args) = split_constants(args);
+                if fixed > k || k - fixed > args.len() {
+                    Const(false)
+                } else if args.is_empty() {
+                    Const(true)
+                } else {
+                    Exactly(k - fixed, args)
+                }
+            }
+            expr => expr,
+        }
+    }
+
+    /// Convert this expression into negation normal form, where negation only appears
+    /// directly in front of variables and the only remaining binary operators are `&` and `|`.
+    ///
+    /// Quantifiers and n-ary operators are preserved. Cardinality constraints are expressed
+    /// using `AtMost` (over negated arguments where necessary), since the negation of
+    /// a cardinality constraint is again a cardinality constraint. Note that `<=>`, `^` and
+    /// `ite` duplicate their arguments, so the result can be much larger than the input.
+    pub fn to_nnf(&self) -> BooleanExpression {
+        self.nnf(false)
+    }
+
+    /// Encode this expression into an equisatisfiable CNF using the Tseitin transformation.
+    ///
+    /// The result is a new `BddVariableSet` which extends the given `variables` with auxiliary
+    /// variables (named `_tseitin_{i}`, with kind `BddVariableKind::Auxiliary`), and a list
+    /// of clauses over this set. The original variables keep their `BddVariable` identifiers,
+    /// kinds and groups. Existentially quantifying
+    /// the auxiliary variables out of the CNF yields exactly the original expression.
+    /// Quantifiers are eliminated by expansion before the encoding.
+    ///
+    /// Returns an error if the expression uses a variable which is not in `variables`, or if
+    /// the auxiliary variables would exceed the maximal number of variables in a set.
+    pub fn to_tseitin_cnf(
+        &self,
+        variables: &BddVariableSet,
+    ) -> Result<(BddVariableSet, Vec<BddPartialValuation>), ExpressionError> {
+        let mut encoder = TseitinEncoder {
+            variables,
+            next_var: variables.num_vars(),
+            clauses: Vec::new(),
+        };
+        let expr = self.simplify_constants();
+        match encoder.encode(&expr)? {
+            Literal::Const(true) => {}
+            Literal::Const(false) => encoder.clauses.push(BddPartialValuation::empty()),
+            Literal::Var(var, value) => encoder.push_clause(&[(var, value)]),
+        }
+
+        let mut builder = variables.to_builder();
+        let mut id = 0;
+        for _ in variables.num_vars()..encoder.next_var {
+            let name = loop {
+                let name = format!("_tseitin_{}", id);
+                id += 1;
+                if variables.var_by_name(&name).is_none() {
+                    break name;
+                }
+            };
+            builder.make_variable_of_kind(&name, BddVariableKind::Auxiliary);
+        }
+        Ok((builder.build(), encoder.clauses))
+    }
+
+    /// **(internal)** Create a copy of this expression where each direct child expression
+    /// is transformed using `f`.
+    fn map_children<F>(&self, f: F) -> BooleanExpression
+    where
+        F: Fn(&BooleanExpression) -> BooleanExpression,
+    {
+        let b = |e: &BooleanExpression| Box::new(f(e));
+        match self {
+            Const(_) | Variable(_) => self.clone(),
+            Not(inner) => Not(b(inner)),
+            And(l, r) => And(b(l), b(r)),
+            Or(l, r) => Or(b(l), b(r)),
+            Xor(l, r) => Xor(b(l), b(r)),
+            Imp(l, r) => Imp(b(l), b(r)),
+            Iff(l, r) => Iff(b(l), b(r)),
+            Exists(vars, inner) => Exists(vars.clone(), b(inner)),
+            ForAll(vars, inner) => ForAll(vars.clone(), b(inner)),
+            Ite(c, t, e) => Ite(b(c), b(t), b(e)),
+            Conjunction(args) => Conjunction(args.iter().map(f).collect()),
+            Disjunction(args) => Disjunction(args.iter().map(f).collect()),
+            AtMost(k, args) => AtMost(*k, args.iter().map(f).collect()),
+            Exactly(k, args) => Exactly(*k, args.iter().map(f).collect()),
+        }
+    }
+
+    /// **(internal)** Collect free variables, assuming the names in `bound` are bound
+    /// by some enclosing quantifier.
+    fn collect_support(&self, bound: &mut Vec<String>, result: &mut HashSet<String>) {
+        match self {
+            Const(_) => {}
+            Variable(name) => {
+                if !bound.contains(name) {
+                    result.insert(name.clone());
+                }
+            }
+            Not(inner) => inner.collect_support(bound, result),
+            And(l, r) | Or(l, r) | Xor(l, r) | Imp(l, r) | Iff(l, r) => {
+                l.collect_support(bound, result);
+                r.collect_support(bound, result);
+            }
+            Exists(vars, inner) | ForAll(vars, inner) => {
+                let len = bound.len();
+                bound.extend(vars.iter().cloned());
+                inner.collect_support(bound, result);
+                bound.truncate(len);
+            }
+            Ite(c, t, e) => {
+                c.collect_support(bound, result);
+                t.collect_support(bound, result);
+                e.collect_support(bound, result);
+            }
+            Conjunction(args) | Disjunction(args) | AtMost(_, args) | Exactly(_, args) => {
+                for arg in args {
+                    arg.collect_support(bound, result);
+                }
+            }
+        }
+    }
+
+    /// **(internal)** Compute the negation normal form of this expression (`negated = false`)
+    /// or its negation (`negated = true`).
+    fn nnf(&self, negated: bool) -> BooleanExpression {
+        let and = |l: BooleanExpression, r: BooleanExpression| And(Box::new(l), Box::new(r));
+        let or = |l: BooleanExpression, r: BooleanExpression| Or(Box::new(l), Box::new(r));
+        match self {
+            Const(value) => Const(*value != negated),
+            Variable(_) if negated => Not(Box::new(self.clone())),
+            Variable(_) => self.clone(),
+            Not(inner) => inner.nnf(!negated),
+            And(l, r) if negated => or(l.nnf(true), r.nnf(true)),
+            And(l, r) => and(l.nnf(false), r.nnf(false)),
+            Or(l, r) if negated => and(l.nnf(true), r.nnf(true)),
+            Or(l, r) => or(l.nnf(false), r.nnf(false)),
+            Imp(l, r) if negated => and(l.nnf(false), r.nnf(true)),
+            Imp(l, r) => or(l.nnf(true), r.nnf(false)),
+            // `a <=> b` is `(a & b) | (!a & !b)`, `a ^ b` is `!(a <=> b)`.
+            Iff(l, r) | Xor(l, r) => {
+                let negated = negated != matches!(self, Xor(_, _));
+                if negated {
+                    or(
+                        and(l.nnf(false), r.nnf(true)),
+                        and(l.nnf(true), r.nnf(false)),
+                    )
+                } else {
+                    or(
+                        and(l.nnf(false), r.nnf(false)),
+                        and(l.nnf(true), r.nnf(true)),
+                    )
+                }
+            }
+            Ite(c, t, e) => or(
+                and(c.nnf(false), t.nnf(negated)),
+                and(c.nnf(true), e.nnf(negated)),
+            ),
+            Exists(vars, inner) if negated => ForAll(vars.clone(), Box::new(inner.nnf(true))),
+            Exists(vars, inner) => Exists(vars.clone(), Box::new(inner.nnf(false))),
+            ForAll(vars, inner) if negated => Exists(vars.clone(), Box::new(inner.nnf(true))),
+            ForAll(vars, inner) => ForAll(vars.clone(), Box::new(inner.nnf(false))),
+            Conjunction(args) if negated => Disjunction(nnf_all(args, true)),
+            Conjunction(args) => Conjunction(nnf_all(args, false)),
+            Disjunction(args) if negated => Conjunction(nnf_all(args, true)),
+            Disjunction(args) => Disjunction(nnf_all(args, false)),
+            // `!atmost(k, X)` is `atleast(k + 1, X)`, which is `atmost(n - k - 1, !X)`.
+            AtMost(k, args) if negated => at_least(*k + 1, args),
+            AtMost(k, args) => AtMost(*k, nnf_all(args, false)),
+            // `exactly(k, X)` is `atmost(k, X) & atleast(k, X)`.
+            Exactly(k, args) if negated => {
+                let more = at_least(*k + 1, args);
+                if *k == 0 {
+                    more
+                } else {
+                    or(AtMost(*k - 1, nnf_all(args, false)), more)
+                }
+            }
+            Exactly(k, args) => and(AtMost(*k, nnf_all(args, false)), at_least(*k, args)),
+        }
+    }
+}
+
+/// **(internal)** Negate an expression, removing a double negation if it appears.
+fn negate(expr: BooleanExpression) -> BooleanExpression {
+    match expr {
+        Not(inner) => *inner,
+        expr => Not(Box::new(expr)),
+    }
+}
+
+/// **(internal)** Remove constant arguments from a cardinality constraint, returning
+/// the number of `true` constants and the remaining arguments.
+fn split_constants(args: Vec<BooleanExpression>) -> (usize, Vec<BooleanExpression>) {
+    let fixed = args.iter().filter(|it| **it == Const(true)).count();
+    let args = args
+        .into_iter()
+        .filter(|it| !matches!(it, Const(_)))
+        .collect();
+    (fixed, args)
+}
+
+/// **(internal)** Convert all expressions to (possibly negated) negation normal form.
+fn nnf_all(args: &[BooleanExpression], negated: bool) -> Vec<BooleanExpression> {
+    args.iter().map(|it| it.nnf(negated)).collect()
+}
+
+/// **(internal)** Negation normal form of "at least `k` of `args` are true".
+fn at_least(k: usize, args: &[BooleanExpression]) -> BooleanExpression {
+    if k == 0 {
+        Const(true)
+    } else if k > args.len() {
+        Const(false)
+    } else {
+        AtMost(args.len() - k, nnf_all(args, true))
+    }
+}
+
+/// **(internal)** A literal in the Tseitin encoding, or a constant.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Literal {
+    Const(bool),
+    Var(BddVariable, bool),
+}
+
+impl Literal {
+    fn not(self) -> Literal {
+        match self {
+            Literal::Const(value) => Literal::Const(!value),
+            Literal::Var(var, value) => Literal::Var(var, !value),
+        }
+    }
+}
+
+/// **(internal)** State of the Tseitin encoding.
+struct TseitinEncoder<'a> {
+    variables: &'a BddVariableSet,
+    next_var: u16,
+    clauses: Vec<BddPartialValuation>,
+}
+
+impl TseitinEncoder<'_> {
+    /// Add a clause to the result, unless it is a tautology (contains `x` and `!x`).
+    /// Constant literals are already eliminated by the gate constructors.
+    fn push_clause(&mut self, literals: &[(BddVariable, bool)]) {
+        let mut clause = BddPartialValuation::empty();
+        for (var, value) in literals {
+            if clause.get_value(*var) == Some(!*value) {
+                return;
+            }
+            clause.set_value(*var, *value);
+        }
+        self.clauses.push(clause);
+    }
+
+    /// Create a new auxiliary variable, or return an error if the resulting variable set
+    /// would be larger than what `BddVariableSetBuilder` supports.
+    fn fresh(&mut self) -> Result<BddVariable, ExpressionError> {
+        if self.next_var >= u16::MAX - 1 {
+            return Err(ExpressionError::TooManyVariables);
+        }
+        let var = BddVariable(self.next_var);
+        self.next_var += 1;
+        Ok(var)
+    }
+
+    /// Return a literal equivalent to the conjunction of `args`.
+    fn and_gate(&mut self, args: &[Literal]) -> Result<Literal, ExpressionError> {
+        if args.contains(&Literal::Const(false)) {
+            return Ok(Literal::Const(false));
+        }
+        let args: Vec<(BddVariable, bool)> = args
+            .iter()
+            .filter_map(|it| match it {
+                Literal::Var(var, value) => Some((*var, *value)),
+                Literal::Const(_) => None,
+            })
+            .collect();
+        Ok(match args.len() {
+            0 => Literal::Const(true),
+            1 => Literal::Var(args[0].0, args[0].1),
+            _ => {
+                let t = self.fresh()?;
+                // t => arg_i
+                for (var, value) in &args {
+                    self.push_clause(&[(t, false), (*var, *value)]);
+                }
+                // (arg_1 & ... & arg_n) => t
+                let mut clause: Vec<_> = args.iter().map(|(var, value)| (*var, !*value)).collect();
+                clause.push((t, true));
+                self.push_clause(&clause);
+                Literal::Var(t, true)
+            }
+        })
+    }
+
+    /// Return a literal equivalent to the disjunction of `args`.
+    fn or_gate(&mut self, args: &[Literal]) -> Result<Literal, ExpressionError> {
+        let args: Vec<Literal> = args.iter().map(|it| it.not()).collect();
+        Ok(self.and_gate(&args)?.not())
+    }
+
+    /// Return a literal equivalent to `c ? t : e`.
+    fn ite_gate(&mut self, c: Literal, t: Literal, e: Literal) -> Result<Literal, ExpressionError> {
+        Ok(match (c, t, e) {
+            (Literal::Const(true), t, _) => t,
+            (Literal::Const(false), _, e) => e,
+            (Literal::Var(c, c_v), Literal::Var(t, t_v), Literal::Var(e, e_v)) => {
+                let x = self.fresh()?;
+                self.push_clause(&[(x, false), (c, !c_v), (t, t_v)]);
+                self.push_clause(&[(x, false), (c, c_v), (e, e_v)]);
+                self.push_clause(&[(x, true), (c, !c_v), (t, !t_v)]);
+                self.push_clause(&[(x, true), (c, c_v), (e, !e_v)]);
+                Literal::Var(x, true)
+            }
+            (c, t, e) => {
+                // At least one branch is constant, so we can use the smaller gates.
+                let then_branch = self.and_gate(&[c, t])?;
+                let else_branch = self.and_gate(&[c.not(), e])?;
+                self.or_gate(&[then_branch, else_branch])?
+            }
+        })
+    }
+
+    /// Return literals `result[j]` equivalent to "at least `j` of `args` are true",
+    /// for `j` in `0..=k` (sequential counter encoding).
+    fn counter(&mut self, k: usize, args: &[Literal]) -> Result<Vec<Literal>, ExpressionError> {
+        let mut at_least = vec![Literal::Const(false); k + 1];
+        at_least[0] = Literal::Const(true);
+        for arg in args {
+            for j in (1..=k).rev() {
+                let increment = self.and_gate(&[at_least[j - 1], *arg])?;
+                at_least[j] = self.or_gate(&[at_least[j], increment])?;
+            }
+        }
+        Ok(at_least)
+    }
+
+    /// Encode the given expression and return a literal that is equivalent to it.
+    fn encode(&mut self, expr: &BooleanExpression) -> Result<Literal, ExpressionError> {
+        Ok(match expr {
+            Const(value) => Literal::Const(*value),
+            Variable(name) => {
+                let Some(var) = self.variables.var_by_name(name) else {
+                    return Err(ExpressionError::UnknownVariable(name.clone()));
+                };
+                Literal::Var(var, true)
+            }
+            Not(inner) => self.encode(inner)?.not(),
+            And(l, r) => {
+                let args = [self.encode(l)?, self.encode(r)?];
+                self.and_gate(&args)?
+            }
+            Or(l, r) => {
+                let args = [self.encode(l)?, self.encode(r)?];
+                self.or_gate(&args)?
+            }
+            Imp(l, r) => {
+                let args = [self.encode(l)?.not(), self.encode(r)?];
+                self.or_gate(&args)?
+            }
+            Iff(l, r) | Xor(l, r) => {
+                let l = self.encode(l)?;
+                let r = self.encode(r)?;
+                let r = if matches!(expr, Xor(_, _)) {
+                    r.not()
+                } else {
+                    r
+                };
+                self.ite_gate(l, r, r.not())?
+            }
+            Ite(c, t, e) => {
+                let c = self.encode(c)?;
+                let t = self.encode(t)?;
+                let e = self.encode(e)?;
+                self.ite_gate(c, t, e)?
+            }
+            Exists(vars, inner) | ForAll(vars, inner) => {
+                let expanded = expand_quantifier(vars, inner, matches!(expr, Exists(_, _)));
+                self.encode(&expanded)?
+            }
+            Conjunction(args) | Disjunction(args) => {
+                let args = args
+                    .iter()
+                    .map(|it| self.encode(it))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if matches!(expr, Conjunction(_)) {
+                    self.and_gate(&args)?
+                } else {
+                    self.or_gate(&args)?
+                }
+            }
+            AtMost(k, args) => {
+                let args = args
+                    .iter()
+                    .map(|it| self.encode(it))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if *k >= args.len() {
+                    Literal::Const(true)
+                } else {
+                    self.counter(*k + 1, &args)?[*k + 1].not()
+                }
+            }
+            Exactly(k, args) => {
+                let args = args
+                    .iter()
+                    .map(|it| self.encode(it))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if *k > args.len() {
+                    Literal::Const(false)
+                } else {
+                    let at_least = self.counter(*k + 1, &args)?;
+                    self.and_gate(&[at_least[*k], at_least[*k + 1].not()])?
+                }
+            }
+        })
+    }
+}
+
+/// **(internal)** Eliminate a quantifier by expanding it into a disjunction (`exists`)
+/// or a conjunction (`forall`) over all values of the quantified variables.
+fn expand_quantifier(
+    vars: &[String],
+    inner: &BooleanExpression,
+    exists: bool,
+) -> BooleanExpression {
+    let mut result = inner.clone();
+    for var in vars {
+        let mut mapping = HashMap::new();
+        mapping.insert(var.clone(), Const(false));
+        let low = result.substitute(&mapping);
+        mapping.insert(var.clone(), Const(true));
+        let high = result.substitute(&mapping);
+        result = if exists {
+            Or(Box::new(low), Box::new(high))
+        } else {
+            And(Box::new(low), Box::new(high))
+        };
+    }
+    result.simplify_constants()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::boolean_expression::{BooleanExpression, ExpressionError};
+    use crate::{BddVariableKind, BddVariableSet, BddVariableSetBuilder};
+    use std::collections::{HashMap, HashSet};
+    use std::convert::TryFrom;
+
+    fn parse(e: &str) -> BooleanExpression {
+        BooleanExpression::try_from(e).unwrap()
+    }
+
+    const FORMULAS: [&str; 10] = [
+        "a & !b | c",
+        "!(a => b) <=> (c ^ !d)",
+        "ite(a, b ^ c, !(d | a))",
+        "and(a, !b, or(c, d)) | !or()",
+        "atmost(1, a, b & c, !d)",
+        "!atmost(2, a, b, c | d)",
+        "exactly(2, a, !b, c, d)",
+        "!exactly(1, a ^ b, c, d)",
+        "exists a, b: (a & c) | (b <=> d)",
+        "!forall c: (a => c) | (d & !b)",
+    ];
+
+    #[test]
+    fn support_and_substitution() {
+        let e = parse("a & (exists b: b | c) & b");
+        let expected: HashSet<String> = ["a", "b", "c"].iter().map(|it| it.to_string()).collect();
+        assert_eq!(expected, e.support_set());
+        let e = parse("exists b: b | c");
+        let expected: HashSet<String> = ["c"].iter().map(|it| it.to_string()).collect();
+        assert_eq!(expected, e.support_set());
+
+        let mut mapping = HashMap::new();
+        mapping.insert("b".to_string(), parse("x | y"));
+        mapping.insert("c".to_string(), parse("!z"));
+        let e = parse("a & (exists b: b | c) & b");
+        let expected = parse("a & (exists b: b | !z) & (x | y)");
+        assert_eq!(expected, e.substitute(&mapping));
+    }
+
+    #[test]
+    fn simplify_constants() {
+        let cases = [
+            ("a & true", "a"),
+            ("a & 0 | b", "b"),
+            ("(a ^ 1) => false", "a"),
+            ("(1 <=> a) ^ (0 => b)", "!a"),
+            ("ite(c, 1, 0) | ite(0, a, b)", "c | b"),
+            ("and(a, 1, b)", "and(a, b)"),
+            ("or(a, 0)", "a"),
+            ("or(a, 1)", "1"),
+            ("exists a: 1 & 0", "0"),
+            ("atmost(1, 1, a, 1)", "0"),
+            ("atmost(2, 1, a, b)", "atmost(1, a, b)"),
+            ("atmost(2, 1, a)", "1"),
+            ("exactly(2, 1, a, 0)", "exactly(1, a)"),
+            ("exactly(1, 1, 0)", "1"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse(expected), parse(input).simplify_constants());
+        }
+    }
+
+    #[test]
+    fn eliminate_double_negation() {
+        assert_eq!(
+            parse("a & !b | !(c => d)"),
+            parse("!!a & !!!b | !!!(c => !!d)").eliminate_double_negation()
+        );
+    }
+
+    /// True if negation is only applied to variables and only `&`, `|` are used.
+    fn is_nnf(e: &BooleanExpression) -> bool {
+        use BooleanExpression::*;
+        match e {
+            Const(_) | Variable(_) => true,
+            Not(inner) => matches!(inner.as_ref(), Variable(_)),
+            And(l, r) | Or(l, r) => is_nnf(l) && is_nnf(r),
+            Xor(_, _) | Imp(_, _) | Iff(_, _) | Ite(_, _, _) | Exactly(_, _) => false,
+            Exists(_, inner) | ForAll(_, inner) => is_nnf(inner),
+            Conjunction(args) | Disjunction(args) | AtMost(_, args) => args.iter().all(is_nnf),
+        }
+    }
+
+    #[test]
+    fn nnf_is_equivalent() {
+        let vars = BddVariableSet::new(&["a", "b", "c", "d"]);
+        for formula in FORMULAS {
+            let e = parse(formula);
+            let nnf = e.to_nnf();
+            assert!(is_nnf(&nnf), "{}", nnf);
+            assert_eq!(vars.eval_expression(&e), vars.eval_expression(&nnf));
+            let not_e = parse(&format!("!({})", formula));
+            let not_nnf = not_e.to_nnf();
+            assert!(is_nnf(&not_nnf), "{}", not_nnf);
+            assert_eq!(vars.eval_expression(&not_e), vars.eval_expression(&not_nnf));
+        }
+    }
+
+    #[test]
+    fn tseitin_is_equisatisfiable() {
+        let vars = BddVariableSet::new(&["a", "b", "c", "d"]);
+        for formula in FORMULAS.iter().chain(&["1", "0", "a", "!a", "a & !a"]) {
+            let e = parse(formula);
+            let (ext_vars, cnf) = e.to_tseitin_cnf(&vars).unwrap();
+            assert_eq!(vars.variables(), ext_vars.variables()[..4].to_vec());
+            let auxiliary = &ext_vars.variables()[4..];
+            for var in auxiliary {
+                assert_eq!(Some(&BddVariableKind::Auxiliary), ext_vars.kind_of(*var));
+            }
+            let expected = ext_vars.eval_expression(&e);
+            let actual = ext_vars.mk_cnf(&cnf).exists(auxiliary);
+            assert_eq!(expected, actual, "{}", formula);
+        }
+        assert_eq!(
+            Err(ExpressionError::UnknownVariable("x".to_string())),
+            parse("a & x").to_tseitin_cnf(&vars).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn tseitin_keeps_metadata() {
+        let mut builder = BddVariableSetBuilder::new();
+        let a = builder.make_variable_of_kind("a", BddVariableKind::State);
+        let b = builder.make_variable("b");
+        builder.make_group("inputs", &[a, b]);
+        let vars = builder.build();
+        let (ext_vars, _) = parse("a & b | !a").to_tseitin_cnf(&vars).unwrap();
+        assert_eq!(Some(&BddVariableKind::State), ext_vars.kind_of(a));
+        assert_eq!(None, ext_vars.kind_of(b));
+        assert_eq!(Some(vec![a, b]), ext_vars.group("inputs"));
+    }
+
+    #[test]
+    fn tseitin_too_many_variables() {
+        // The set has room for only two more variables.
+        let vars = BddVariableSet::new_anonymous(u16::MAX - 3);
+        let (ext_vars, _) = parse("(x_0 & x_1) | x_2").to_tseitin_cnf(&vars).unwrap();
+        assert_eq!(u16::MAX - 1, ext_vars.num_vars());
+        let e = parse("(x_0 & x_1) | (x_2 & x_3)");
+        assert_eq!(
+            Err(ExpressionError::TooManyVariables),
+            e.to_tseitin_cnf(&vars).map(|_| ())
+        );
+    }
+}
@@ -8,6 +8,16 @@
 //! let vars = BddVariableSet::new_anonymous(4);
 //! let f: Bdd = vars.eval_expression_string("x_0 & !x_1 => (x_1 ^ x_3 <=> (x_0 | x_1))");
 //! ```
+//!
+//! *Breaking changes:*
+//!
+//!  - Parsing errors are now reported as a `ParseError` (with a position and a message)
+//!    instead of a `String` (i.e. `TryFrom<&str>::Error` is now `ParseError`). A `ParseError`
+//!    converts into the original `String` message (using `From`/`?`), or use
+//!    `ParseError::to_string` to also include the position.
+//!  - `BooleanExpression` has new variants (`Exists`, `ForAll`, `Ite`, `Conjunction`,
+//!    `Disjunction`, `AtMost` and `Exactly`) and is now `#[non_exhaustive]`. Exhaustive
+//!    `match` expressions outside of this crate need a wildcard (`_`) arm.
 
 /// **(internal)** Implements boolean expression evaluation for `BddVariableSet` and some utility methods.
 mod _impl_boolean_expression;
@@ -15,8 +25,17 @@ mod _impl_boolean_expression;
 /// **(internal)** Parsing functions for boolean expressions.
 mod _impl_parser;
 
+/// **(internal)** Direct (three-valued) evaluation of boolean expressions in valuations.
+mod _impl_eval;
+
+/// **(internal)** Syntactic simplifications and normal form transformations of boolean expressions.
+mod _impl_transformations;
+
 /// Recursive type for boolean expression tree.
+///
+/// New kinds of expressions may be added in the future, so the enum is not exhaustive.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum BooleanExpression {
     Const(bool),
     Variable(String),
@@ -26,4 +45,44 @@ pub enum BooleanExpression {
     Xor(Box<BooleanExpression>, Box<BooleanExpression>),
     Imp(Box<BooleanExpression>, Box<BooleanExpression>),
     Iff(Box<BooleanExpression>, Box<BooleanExpression>),
+    /// Existential quantification of the named variables.
+    Exists(Vec<String>, Box<BooleanExpression>),
+    /// Universal quantification of the named variables.
+    ForAll(Vec<String>, Box<BooleanExpression>),
+    /// If-then-else: `(c & t) | (!c & e)`.
+    Ite(
+        Box<BooleanExpression>,
+        Box<BooleanExpression>,
+        Box<BooleanExpression>,
+    ),
+    /// N-ary conjunction (empty conjunction is `true`).
+    Conjunction(Vec<BooleanExpression>),
+    /// N-ary disjunction (empty disjunction is `false`).
+    Disjunction(Vec<BooleanExpression>),
+    /// At most `k` of the arguments are true.
+    AtMost(usize, Vec<BooleanExpression>),
+    /// Exactly `k` of the arguments are true.
+    Exactly(usize, Vec<BooleanExpression>),
+}
+
+/// An error produced when a `BooleanExpression` cannot be parsed. The `position` is
+/// the character offset in the input string where the problem was detected.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+/// An error produced when a `BooleanExpression` cannot be processed in the context
+/// of a `BddVariableSet` (e.g. by `BooleanExpression::to_tseitin_cnf` or
+/// `BooleanExpression::eval`).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ExpressionError {
+    /// The expression uses a variable with the given name which is not in the variable set.
+    UnknownVariable(String),
+    /// The result would need more variables than a `BddVariableSet` can contain.
+    TooManyVariables,
+    /// The valuation has a different number of variables (first) than the variable
+    /// set (second).
+    IncompatibleValuation(u16, u16),
 }
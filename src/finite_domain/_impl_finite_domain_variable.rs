@@ -0,0 +1,312 @@
+use super::{FiniteDomainEncoding, FiniteDomainVariable};
+use crate::{
+    Bdd, BddPartialValuation, BddValuation, BddVariable, BddVariableSet, BddVariableSetBuilder,
+};
+
+impl BddVariableSetBuilder {
+    /// Create a new finite-domain variable with the given `values`, using
+    /// the `FiniteDomainEncoding::Logarithmic` encoding.
+    ///
+    /// *Panics:* The `values` must be non-empty and unique. Same conditions as
+    /// in `make_variable` apply to the names of the created Boolean variables.
+    pub fn make_finite_domain(&mut self, name: &str, values: &[&str]) -> FiniteDomainVariable {
+        self.make_finite_domain_with_encoding(name, values, FiniteDomainEncoding::Logarithmic)
+    }
+
+    /// Create a new finite-domain variable with the given `values` and `encoding`.
+    ///
+    /// The logarithmic encoding uses variables `{name}_fd{i}` (least significant bit first),
+    /// the one-hot encoding uses variables `{name}_v{i}` (one for each value). The names are
+    /// distinct from the `{name}_b{i}` bits of a `BitVectorVariable`.
+    ///
+    /// *Panics:* The `values` must be non-empty and unique. Same conditions as
+    /// in `make_variable` apply to the names of the created Boolean variables.
+    pub fn make_finite_domain_with_encoding(
+        &mut self,
+        name: &str,
+        values: &[&str],
+        encoding: FiniteDomainEncoding,
+    ) -> FiniteDomainVariable {
+        if values.is_empty() {
+            panic!("Finite domain {} has no values.", name);
+        }
+        for (i, value) in values.iter().enumerate() {
+            if values[..i].contains(value) {
+                panic!("Duplicate value {} in finite domain {}.", value, name);
+            }
+        }
+        let bits = match encoding {
+            FiniteDomainEncoding::Logarithmic => {
+                // Number of bits needed to represent indices `0..values.len()`.
+                let width = usize::BITS - (values.len() - 1).leading_zeros();
+                (0..width)
+                    .map(|i| self.make_variable(&format!("{}_fd{}", name, i)))
+                    .collect()
+            }
+            FiniteDomainEncoding::OneHot => (0..values.len())
+                .map(|i| self.make_variable(&format!("{}_v{}", name, i)))
+                .collect(),
+        };
+        FiniteDomainVariable {
+            name: name.to_string(),
+            values: values.iter().map(|it| it.to_string()).collect(),
+            encoding,
+            bits,
+        }
+    }
+}
+
+impl FiniteDomainVariable {
+    /// Name of this variable.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The domain of this variable.
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+
+    /// The encoding used by this variable.
+    pub fn encoding(&self) -> FiniteDomainEncoding {
+        self.encoding
+    }
+
+    /// The Boolean variables used to encode this variable.
+    pub fn bits(&self) -> &[BddVariable] {
+        &self.bits
+    }
+
+    /// Index of the given `value` in the domain, or `None` if it is not in the domain.
+    pub fn index_of(&self, value: &str) -> Option<usize> {
+        self.values.iter().position(|it| it == value)
+    }
+
+    /// Create a `Bdd` which is satisfied exactly when this variable has the given `value`.
+    ///
+    /// *Panics:* The `value` must be in the domain of this variable.
+    pub fn mk_eq(&self, variables: &BddVariableSet, value: &str) -> Bdd {
+        let Some(index) = self.index_of(value) else {
+            panic!("Value {} is not in the domain of {}.", value, self.name);
+        };
+        variables.mk_conjunctive_clause(&self.encode(index))
+    }
+
+    /// Create a `Bdd` which is satisfied exactly when this variable has one of
+    /// the given `values`.
+    ///
+    /// *Panics:* The `values` must be in the domain of this variable.
+    pub fn mk_in(&self, variables: &BddVariableSet, values: &[&str]) -> Bdd {
+        let clauses: Vec<BddPartialValuation> = values
+            .iter()
+            .map(|value| {
+                let Some(index) = self.index_of(value) else {
+                    panic!("Value {} is not in the domain of {}.", value, self.name);
+                };
+                self.encode(index)
+            })
+            .collect();
+        variables.mk_dnf(&clauses)
+    }
+
+    /// Create a `Bdd` which is satisfied exactly when this variable has a value different
+    /// from the given `value`.
+    ///
+    /// Unlike `mk_eq(value).not()`, the result only contains valid encodings.
+    ///
+    /// *Panics:* The `value` must be in the domain of this variable.
+    pub fn mk_ne(&self, variables: &BddVariableSet, value: &str) -> Bdd {
+        self.mk_complement(variables, &self.mk_eq(variables, value))
+    }
+
+    /// Create a `Bdd` which is satisfied exactly when this variable has a value that is not
+    /// one of the given `values`.
+    ///
+    /// Unlike `mk_in(values).not()`, the result only contains valid encodings.
+    ///
+    /// *Panics:* The `values` must be in the domain of this variable.
+    pub fn mk_not_in(&self, variables: &BddVariableSet, values: &[&str]) -> Bdd {
+        self.mk_complement(variables, &self.mk_in(variables, values))
+    }
+
+    /// Negate the given `bdd` with respect to the domain of this variable, i.e. compute
+    /// `!bdd & valid`, where `valid` is given by `mk_valid_encoding`.
+    pub fn mk_complement(&self, variables: &BddVariableSet, bdd: &Bdd) -> Bdd {
+        self.mk_valid_encoding(variables).and_not(bdd)
+    }
+
+    /// Create a `Bdd` which is satisfied exactly by the valid encodings of this variable.
+    pub fn mk_valid_encoding(&self, variables: &BddVariableSet) -> Bdd {
+        match self.encoding {
+            FiniteDomainEncoding::Logarithmic => {
+                let clauses: Vec<BddPartialValuation> =
+                    (0..self.values.len()).map(|i| self.encode(i)).collect();
+                variables.mk_dnf(&clauses)
+            }
+            FiniteDomainEncoding::OneHot => variables.mk_sat_exactly_k(1, &self.bits),
+        }
+    }
+
+    /// Decode the value of this variable in the given `valuation`. Returns `None` if
+    /// the valuation does not contain a valid encoding.
+    pub fn value_in(&self, valuation: &BddValuation) -> Option<&str> {
+        let index = match self.encoding {
+            FiniteDomainEncoding::Logarithmic => self
+                .bits
+                .iter()
+                .enumerate()
+                .filter(|(_, bit)| valuation[**bit])
+                .fold(0, |index, (i, _)| index | (1 << i)),
+            FiniteDomainEncoding::OneHot => {
+                let mut active = (0..self.bits.len()).filter(|i| valuation[self.bits[*i]]);
+                let index = active.next()?;
+                if active.next().is_some() {
+                    return None;
+                }
+                index
+            }
+        };
+        self.values.get(index).map(|it| it.as_str())
+    }
+
+    /// Decode all values of this variable which are compatible with the given partial
+    /// `valuation`, i.e. their encoding does not contradict any fixed variable.
+    pub fn values_in_partial(&self, valuation: &BddPartialValuation) -> Vec<&str> {
+        (0..self.values.len())
+            .filter(|index| {
+                self.encode(*index)
+                    .to_values()
+                    .into_iter()
+                    .all(|(bit, value)| valuation.get_value(bit) != Some(!value))
+            })
+            .map(|index| self.values[index].as_str())
+            .collect()
+    }
+
+    /// **(internal)** Encoding of the value with the given `index` as a partial valuation
+    /// of the encoding variables.
+    fn encode(&self, index: usize) -> BddPartialValuation {
+        let values: Vec<(BddVariable, bool)> = match self.encoding {
+            FiniteDomainEncoding::Logarithmic => self
+                .bits
+                .iter()
+                .enumerate()
+                .map(|(i, bit)| (*bit, (index >> i) & 1 == 1))
+                .collect(),
+            FiniteDomainEncoding::OneHot => self
+                .bits
+                .iter()
+                .enumerate()
+                .map(|(i, bit)| (*bit, i == index))
+                .collect(),
+        };
+        BddPartialValuation::from_values(&values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::finite_domain::FiniteDomainEncoding;
+    use crate::{BddPartialValuation, BddVariableSetBuilder, ValuationsOfClauseIterator};
+
+    #[test]
+    fn finite_domain_allocation() {
+        let mut builder = BddVariableSetBuilder::new();
+        let x = builder.make_finite_domain("x", &["a", "b", "c", "d", "e"]);
+        let y = builder.make_finite_domain_with_encoding(
+            "y",
+            &["a", "b", "c"],
+            FiniteDomainEncoding::OneHot,
+        );
+        let z = builder.make_finite_domain("z", &["only"]);
+        let variables = builder.build();
+        assert_eq!(3, x.bits().len());
+        assert_eq!(3, y.bits().len());
+        assert_eq!(0, z.bits().len());
+        assert_eq!(Some(x.bits()[2]), variables.var_by_name("x_fd2"));
+        assert_eq!(Some(y.bits()[1]), variables.var_by_name("y_v1"));
+        assert_eq!("y", y.name());
+        assert_eq!(FiniteDomainEncoding::OneHot, y.encoding());
+        assert_eq!(Some(2), y.index_of("c"));
+        assert!(z.mk_eq(&variables, "only").is_true());
+    }
+
+    #[test]
+    fn finite_domain_constraints() {
+        for encoding in [
+            FiniteDomainEncoding::Logarithmic,
+            FiniteDomainEncoding::OneHot,
+        ] {
+            let mut builder = BddVariableSetBuilder::new();
+            let values = ["a", "b", "c", "d", "e"];
+            let x = builder.make_finite_domain_with_encoding("x", &values, encoding);
+            let variables = builder.build();
+            let valid = x.mk_valid_encoding(&variables);
+            assert_eq!(5.0, valid.cardinality());
+            for value in values {
+                let eq = x.mk_eq(&variables, value);
+                assert_eq!(1.0, eq.cardinality());
+                assert_eq!(Some(value), x.value_in(&eq.sat_witness().unwrap()));
+            }
+            let set = x.mk_in(&variables, &["b", "e"]);
+            assert_eq!(2.0, set.cardinality());
+            let not_set = x.mk_not_in(&variables, &["b", "e"]);
+            assert_eq!(not_set, x.mk_complement(&variables, &set));
+            assert_eq!(3.0, not_set.cardinality());
+            assert_eq!(4.0, x.mk_ne(&variables, "c").cardinality());
+            for valuation in ValuationsOfClauseIterator::new_unconstrained(variables.num_vars()) {
+                let value = x.value_in(&valuation);
+                assert_eq!(value.is_some(), valid.eval_in(&valuation));
+                assert_eq!(
+                    value == Some("b") || value == Some("e"),
+                    set.eval_in(&valuation)
+                );
+                assert_eq!(
+                    value.is_some() && value != Some("b") && value != Some("e"),
+                    not_set.eval_in(&valuation)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn finite_domain_partial_decoding() {
+        let mut builder = BddVariableSetBuilder::new();
+        let x = builder.make_finite_domain("x", &["a", "b", "c"]);
+        let y = builder.make_finite_domain_with_encoding(
+            "y",
+            &["a", "b", "c"],
+            FiniteDomainEncoding::OneHot,
+        );
+        let _variables = builder.build();
+
+        let empty = BddPartialValuation::empty();
+        assert_eq!(vec!["a", "b", "c"], x.values_in_partial(&empty));
+        let low_bit = BddPartialValuation::from_values(&[(x.bits()[0], false)]);
+        assert_eq!(vec!["a", "c"], x.values_in_partial(&low_bit));
+        let not_b = BddPartialValuation::from_values(&[(y.bits()[1], false)]);
+        assert_eq!(vec!["a", "c"], y.values_in_partial(&not_b));
+        let is_c = BddPartialValuation::from_values(&[(y.bits()[2], true)]);
+        assert_eq!(vec!["c"], y.values_in_partial(&is_c));
+    }
+
+    #[test]
+    fn finite_domain_with_bitvector() {
+        // A finite domain and a bit-vector with the same name do not share variable names.
+        let mut builder = BddVariableSetBuilder::new();
+        let x = builder.make_finite_domain("x", &["a", "b", "c"]);
+        let v = builder.make_bitvector("x", 2);
+        let variables = builder.build();
+        assert_eq!(4, variables.num_vars());
+        assert!(x.bits().iter().all(|bit| !v.bits().contains(bit)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn finite_domain_unknown_value() {
+        let mut builder = BddVariableSetBuilder::new();
+        let x = builder.make_finite_domain("x", &["a", "b"]);
+        let variables = builder.build();
+        x.mk_eq(&variables, "c");
+    }
+}
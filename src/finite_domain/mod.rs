@@ -0,0 +1,48 @@
+//! Finite-domain variables represent multi-valued variables (e.g. the activity levels of
+//! a regulatory network component) using several Boolean `BddVariable`s.
+//!
+//! A `FiniteDomainVariable` is allocated using a `BddVariableSetBuilder` and can use one
+//! of two encodings (see `FiniteDomainEncoding`). Once the `BddVariableSet` is built,
+//! the variable can create `Bdd`s representing constraints like `x == value` or
+//! `x in {v1, v2}`, and decode (partial) valuations back to the domain values:
+//!
+//! ```rust
+//! use biodivine_lib_bdd::BddVariableSetBuilder;
+//!
+//! let mut builder = BddVariableSetBuilder::new();
+//! let x = builder.make_finite_domain("x", &["low", "medium", "high"]);
+//! let variables = builder.build();
+//!
+//! let not_low = x.mk_in(&variables, &["medium", "high"]);
+//! assert_eq!(not_low, x.mk_ne(&variables, "low"));
+//! for valuation in not_low.sat_valuations() {
+//!     assert_ne!(Some("low"), x.value_in(&valuation));
+//! }
+//! ```
+//!
+//! All `Bdd`s created by `FiniteDomainVariable` only contain valid encodings. In particular,
+//! negation should be done using `mk_ne`, `mk_not_in` or `mk_complement`, since a plain
+//! `Bdd::not` also admits the unused (invalid) encodings.
+
+use crate::BddVariable;
+
+/// **(internal)** Allocation and operations of finite-domain variables.
+mod _impl_finite_domain_variable;
+
+/// The way in which the values of a `FiniteDomainVariable` are encoded using Boolean variables.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FiniteDomainEncoding {
+    /// The index of the value is encoded in binary, using $\lceil \log_2 n \rceil$ variables.
+    Logarithmic,
+    /// Each value has its own variable, and exactly one of them is true.
+    OneHot,
+}
+
+/// A multi-valued variable with a finite domain of named values.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FiniteDomainVariable {
+    name: String,
+    values: Vec<String>,
+    encoding: FiniteDomainEncoding,
+    bits: Vec<BddVariable>,
+}
@@ -51,9 +51,16 @@
 //! ```
 //!
 
+use fxhash::FxBuildHasher;
 use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Instant;
 
+pub mod bitvector;
 pub mod boolean_expression;
+pub mod finite_domain;
 pub mod op_function;
 pub mod tutorial;
 
@@ -94,6 +101,36 @@ mod _impl_iterator_valuations_of_clause;
 /// **(internal)** Implementation of the `BddPathIterator`.
 mod _impl_bdd_path_iterator;
 
+/// **(internal)** Implementation of the `BddProjectedClauses` and `BddProjectedValuations`.
+mod _impl_bdd_projected_iterators;
+
+/// **(internal)** Implementation of the `BddDisjointCubes` and `BddMaximalCubes`.
+mod _impl_bdd_cube_iterators;
+
+/// **(internal)** Implementation of the `BddRef`.
+mod _impl_bdd_ref;
+
+/// **(internal)** Construction of `Bdd`s for (weighted) pseudo-Boolean constraints.
+mod _impl_pseudo_boolean;
+
+/// **(internal)** Implementation of the `PartitionedRelation`.
+mod _impl_partitioned_relation;
+
+/// **(internal)** Implementation of the `ApplyContext`.
+mod _impl_apply_context;
+
+/// **(internal)** Implementation of the `Budget` of long-running operations.
+mod _impl_budget;
+
+/// **(internal)** Implementation of the `ApplyObserver` for `()` and `ApplyStatistics`.
+mod _impl_apply_observer;
+
+/// **(internal)** Batch and bit-parallel evaluation of `Bdd`s, including the `CompiledBdd`.
+mod _impl_bdd_eval;
+
+/// **(internal)** Implementation of the `TypedBdd` and `TypedBddVariableSet`.
+mod _impl_typed_bdd;
+
 /// **(internal)** A macro module for simplifying BDD operations.
 mod _macro_bdd;
 
@@ -101,6 +138,18 @@ mod _macro_bdd;
 #[cfg(test)]
 mod _test_util;
 
+/// Comparison operators which can be used in pseudo-Boolean constraints
+/// (see `BddVariableSet::mk_pseudo_boolean`).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Comparator {
+    Lt,
+    Le,
+    Eq,
+    Ne,
+    Ge,
+    Gt,
+}
+
 /// **(internal)** Characters that cannot appear in the variable name
 /// (based on possible tokens in a boolean expression).
 const NOT_IN_VAR_NAME: [char; 9] = ['!', '&', '|', '^', '=', '<', '>', '(', ')'];
@@ -119,16 +168,32 @@ pub struct BddVariable(u16);
 ///
 /// It can be used as a witness of `Bdd` non-emptiness, since one can evaluate every `Bdd`
 /// in some corresponding valuation and get a `true/false` result.
-#[derive(Clone, Debug, Eq, Hash, PartialEq, Ord, PartialOrd)]
-pub struct BddValuation(Vec<bool>);
+///
+/// The values are stored as a packed bit-vector (variable $x_i$ is bit $i \bmod 64$ of the
+/// word $\lfloor i / 64 \rfloor$). Unused bits of the last word are always zero.
+///
+/// *Breaking change:* Since the values are packed, `BddValuation` no longer implements
+/// `IndexMut`. Instead of `valuation[var] = value`, use `BddValuation::set_value`
+/// (or `set`, `clear` and `flip_value`).
+#[derive(Clone, Eq, Hash, PartialEq)]
+pub struct BddValuation {
+    num_vars: u16,
+    words: Vec<u64>,
+}
 
 /// Describes assignment of some arbitrary number of `Bdd` variables.
 ///
 /// A partial valuation can be used to quickly construct simple conjunctive/disjunctive clauses.
 /// It also exactly describes one path in a `Bdd` and hence can be used as an intermediate
 /// value when traversing the valuations of a `Bdd`.
-#[derive(Clone, Debug)]
-pub struct BddPartialValuation(Vec<Option<bool>>);
+///
+/// The valuation is stored as two packed bit-vectors: the set of `fixed` variables and their
+/// `values` (the value bit of a variable that is not fixed is always zero).
+#[derive(Clone)]
+pub struct BddPartialValuation {
+    fixed: Vec<u64>,
+    values: Vec<u64>,
+}
 
 /// Exhaustively iterates over all valuations with a certain number of variables.
 ///
@@ -143,7 +208,7 @@ pub struct BddValuationIterator(ValuationsOfClauseIterator);
 ///
 /// Be aware of the potential exponential number of iterations!
 pub struct BddSatisfyingValuations<'a> {
-    bdd: &'a Bdd,
+    num_vars: u16,
     paths: BddPathIterator<'a>,
     valuations: ValuationsOfClauseIterator,
 }
@@ -151,11 +216,44 @@ pub struct BddSatisfyingValuations<'a> {
 /// An iterator which goes through all paths in the `Bdd`, representing them as clauses using
 /// `BddPartialValuation`.
 pub struct BddPathIterator<'a> {
-    bdd: &'a Bdd,
+    bdd: BddSource<'a>,
     // Stack keeps the last discovered path. If last path was consumed, the stack is empty.
     stack: Vec<BddPointer>,
 }
 
+/// An iterator over disjoint clauses which together describe the projection of a `Bdd`
+/// onto a subset of variables (see `Bdd::sat_clauses_over`).
+pub struct BddProjectedClauses<'a> {
+    bdd: &'a Bdd,
+    variables: Vec<BddVariable>,
+    // Each item is the index of the next variable, a set of nodes whose disjunction is
+    // the remaining projected function, and the clause fixed so far.
+    stack: Vec<(usize, Vec<BddPointer>, BddPartialValuation)>,
+}
+
+/// An iterator over all distinct projections of the satisfying valuations of a `Bdd` onto
+/// a subset of variables (see `Bdd::sat_valuations_over`).
+pub struct BddProjectedValuations<'a> {
+    variables: Vec<BddVariable>,
+    clauses: BddProjectedClauses<'a>,
+    // The last valuation and the variables that are not fixed by its clause.
+    current: Option<(BddPartialValuation, Vec<BddVariable>)>,
+}
+
+/// An iterator over a disjoint cover of a `Bdd` by conjunctive clauses (cubes), which is
+/// typically much smaller than the set of all paths (see `Bdd::sat_disjoint_cubes`).
+pub struct BddDisjointCubes {
+    cubes: std::vec::IntoIter<BddPartialValuation>,
+}
+
+/// An iterator over the maximal cubes (prime implicants) of a `Bdd` that are obtained by
+/// extending the individual paths of the `Bdd` (see `Bdd::sat_maximal_cubes`).
+pub struct BddMaximalCubes<'a> {
+    bdd: &'a Bdd,
+    paths: BddPathIterator<'a>,
+    seen: HashSet<BddPartialValuation>,
+}
+
 /// An iterator which goes through all valuations that satisfy a specific *conjunctive* clause.
 ///
 /// Mind that the number of valuations satisfying a clause can be exponential!
@@ -169,11 +267,16 @@ pub struct ValuationsOfClauseIterator {
 
 /// Maintains the set of variables that can appear in a `Bdd`.
 /// Used to create new `Bdd`s for basic formulas.
+///
+/// Besides names, each variable can have a `BddVariableKind` and it can belong to any number
+/// of named variable groups (see `BddVariableSetBuilder`).
 #[derive(Clone)]
 pub struct BddVariableSet {
     num_vars: u16,
     var_names: Vec<String>,
     var_index_mapping: HashMap<String, u16>,
+    var_kinds: Vec<Option<BddVariableKind>>,
+    var_groups: HashMap<String, Vec<BddVariable>>,
 }
 
 /// Used to safely initialize `BddVariableSet`.
@@ -185,6 +288,20 @@ pub struct BddVariableSet {
 pub struct BddVariableSetBuilder {
     var_names: Vec<String>,
     var_names_set: HashSet<String>,
+    var_kinds: Vec<Option<BddVariableKind>>,
+    var_groups: HashMap<String, Vec<BddVariable>>,
+}
+
+/// The role of a variable in a `BddVariableSet`. The kind has no effect on the `Bdd`
+/// operations, but it can be used to look up variables (see
+/// `BddVariableSet::variables_of_kind`).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum BddVariableKind {
+    State,
+    Parameter,
+    Primed,
+    Auxiliary,
+    Other(String),
 }
 
 /// A type-safe index into the `Bdd` node array representation.
@@ -223,6 +340,243 @@ struct BddNode {
     pub high_link: BddPointer,
 }
 
+/// Configuration of the `.dot` export of `Bdd` graphs.
+///
+/// The default options produce the same output as `Bdd::to_dot_string` with `zero_pruned`
+/// set to false. Use the builder-style methods to modify individual options.
+#[derive(Clone, Debug)]
+pub struct DotExportOptions {
+    zero_pruned: bool,
+    complement: bool,
+    rank_by_level: bool,
+    node_style: Option<String>,
+    terminal_style: Option<String>,
+    high_edge_style: String,
+    low_edge_style: String,
+    highlight: Option<BddPartialValuation>,
+    highlight_style: String,
+}
+
+/// A relation represented as a conjunction of several `Bdd`s (conjuncts), which is never
+/// constructed explicitly.
+///
+/// The main operation is `PartitionedRelation::image`, which computes
+/// $\exists V: S \land \bigwedge_i R_i$ such that each variable from $V$ is quantified as
+/// soon as the remaining conjuncts do not depend on it.
+#[derive(Clone, Debug)]
+pub struct PartitionedRelation {
+    conjuncts: Vec<Bdd>,
+}
+
+/// A reusable workspace for the `apply` algorithm.
+///
+/// Normally, every binary operation (as well as the ternary and nested operations, like
+/// `Bdd::ternary_op` or `Bdd::binary_op_with_exists`) allocates its own working memory (a node
+/// table, a task cache and a stack). When a large number of operations is performed on small `Bdd`s
+/// (e.g. in a fixed-point loop), the allocation often dominates the actual computation.
+/// An `ApplyContext` keeps this memory allocated between operations.
+///
+/// Additionally, the context can memoise the results of the basic logical operations
+/// (`ApplyContext::with_memoisation`), such that repeating an operation on the same operands
+/// does not need to run the `apply` algorithm again.
+pub struct ApplyContext {
+    existing: HashMap<BddNode, BddPointer, FxBuildHasher>,
+    finished: HashMap<_impl_bdd::Task, BddPointer, FxBuildHasher>,
+    stack: Vec<_impl_bdd::Task>,
+    inner_finished: HashMap<_impl_bdd::Task, BddPointer, FxBuildHasher>,
+    ternary_finished: HashMap<_impl_bdd::TernaryTask, BddPointer, FxBuildHasher>,
+    ternary_stack: Vec<_impl_bdd::TernaryTask>,
+    memoisation_limit: usize,
+    interned: Vec<Bdd>,
+    interned_nodes: usize,
+    fingerprints: HashMap<u64, Vec<usize>, FxBuildHasher>,
+    memoised: HashMap<(u8, usize, usize), usize, FxBuildHasher>,
+}
+
+/// Resource limits of a potentially long-running `Bdd` operation (see the `*_with_budget`
+/// methods of `Bdd`, `BddVariableSet` and `PartitionedRelation`).
+///
+/// A budget can limit the number of nodes created by the operation, the number of steps
+/// of the algorithm, the time when the operation has to finish, and it can also contain
+/// a shared flag which cancels the operation once it is set to `true` (e.g. from another
+/// thread). By default, a budget is unlimited.
+#[derive(Clone, Debug, Default)]
+pub struct Budget {
+    max_nodes: Option<usize>,
+    max_steps: Option<usize>,
+    deadline: Option<Instant>,
+    cancel_flag: Option<Arc<AtomicBool>>,
+}
+
+/// The reason why an operation with a `Budget` has been aborted.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BudgetExceeded {
+    Nodes,
+    Steps,
+    Deadline,
+    Cancelled,
+}
+
+/// A `Bdd` which is branded by a type `Ctx` identifying its `BddVariableSet`.
+///
+/// A plain `Bdd` only knows the number of its variables, so two `Bdd`s created by different
+/// variable sets of the same size can be combined without any error. The operations of
+/// `TypedBdd` only accept operands with the same `Ctx`, so such mistakes are detected by
+/// the compiler. The `Ctx` type is just a marker and it is never instantiated:
+///
+/// ```compile_fail
+/// use biodivine_lib_bdd::{BddVariableSet, TypedBddVariableSet};
+///
+/// struct States;
+/// struct Parameters;
+///
+/// let states = TypedBddVariableSet::<States>::new(BddVariableSet::new(&["a", "b"]));
+/// let params = TypedBddVariableSet::<Parameters>::new(BddVariableSet::new(&["p", "q"]));
+/// // Does not compile: the two `Bdd`s belong to different contexts.
+/// states.mk_var_by_name("a").and(&params.mk_var_by_name("p"));
+/// ```
+///
+/// A `TypedBdd` can only be created by a `TypedBddVariableSet`. It does not dereference
+/// to `Bdd`, such that the untyped operations cannot be used by accident. The underlying
+/// `Bdd` is available through `TypedBdd::as_bdd`.
+///
+/// ```compile_fail
+/// use biodivine_lib_bdd::{Bdd, BddVariableSet, TypedBddVariableSet};
+///
+/// struct States;
+///
+/// let states = TypedBddVariableSet::<States>::new(BddVariableSet::new(&["a", "b"]));
+/// let a = states.mk_var_by_name("a");
+/// // Does not compile: `TypedBdd` is not a `Bdd`.
+/// Bdd::and(&a, &a);
+/// ```
+///
+/// The type system cannot prevent two different `TypedBddVariableSet`s with the same `Ctx`.
+/// Therefore, in debug builds, the operations also check that both operands were created
+/// by equivalent variable sets (i.e. sets with the same variable names).
+pub struct TypedBdd<Ctx> {
+    bdd: Bdd,
+    fingerprint: u64,
+    context: PhantomData<Ctx>,
+}
+
+/// A `BddVariableSet` which creates `TypedBdd`s branded by the type `Ctx`.
+///
+/// All methods of `BddVariableSet` are available through `Deref`.
+pub struct TypedBddVariableSet<Ctx> {
+    variables: BddVariableSet,
+    fingerprint: u64,
+    context: PhantomData<Ctx>,
+}
+
+/// The error returned by the checked `Bdd` operations (e.g. `Bdd::try_and`) when the operands
+/// do not have the same number of variables, i.e. they do not belong to the same
+/// `BddVariableSet`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct IncompatibleBdds {
+    left: u16,
+    right: u16,
+}
+
+/// The error returned by `Bdd::lift` when a `Bdd` cannot be moved into a different
+/// `BddVariableSet`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum LiftError {
+    /// The `Bdd` does not belong to the source `BddVariableSet` (the `left` operand is
+    /// the `Bdd`, the `right` operand is the variable set).
+    Incompatible(IncompatibleBdds),
+    /// The variable with the given name is not in the `map` and the target
+    /// `BddVariableSet` has no variable with the same name.
+    UnknownVariable(String),
+    /// The variable with the given name is mapped to a variable which is not valid
+    /// in the target `BddVariableSet`.
+    InvalidVariable(String, BddVariable),
+}
+
+/// Statistics about the progress of one or more `apply` operations, collected using
+/// the `ApplyObserver` trait.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ApplyStatistics {
+    tasks: usize,
+    cache_hits: usize,
+    nodes_created: usize,
+    max_stack_depth: usize,
+    tasks_per_variable: Vec<usize>,
+}
+
+/// A `Bdd` flattened into a decision table which is optimized for repeated evaluation
+/// (see `Bdd::compile`).
+///
+/// Each decision node stores its variable and the indices of its two successors in
+/// contiguous arrays, so evaluation does not need to decode any `BddNode` or `BddPointer`.
+/// Indices `0` and `1` are the `false` and `true` terminals.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompiledBdd {
+    num_vars: u16,
+    variables: Vec<u16>,
+    links: Vec<[u32; 2]>,
+}
+
+/// A read-only view of a `Bdd` that is stored as a slice of bytes, using the same little-endian
+/// encoding as `Bdd::write_as_bytes`.
+///
+/// The bytes are never copied: every node is decoded on demand. This makes it possible to
+/// work with very large `Bdd`s that are, for example, memory-mapped from a file, without
+/// loading the whole node array into memory first. A `BddRef` supports the read-only part
+/// of the `Bdd` API and can be used as an operand of the `apply` algorithm.
+#[derive(Clone, Copy, Debug)]
+pub struct BddRef<'a>(&'a [u8]);
+
+/// **(internal)** A reference to either an owned `Bdd` or a byte-encoded `BddRef`. This is used
+/// by the iterators that should work for both representations.
+#[derive(Clone, Copy)]
+enum BddSource<'a> {
+    Owned(&'a Bdd),
+    Bytes(BddRef<'a>),
+}
+
+/// **(internal)** Read-only access to the node array of a decision diagram. Algorithms that
+/// are generic over this trait work for `Bdd` as well as `BddRef`.
+trait BddNodes {
+    /// Number of nodes in the decision diagram.
+    fn size(&self) -> usize;
+    /// Number of variables in the corresponding `BddVariableSet`.
+    fn num_vars(&self) -> u16;
+    /// Conditioning variable of the given node.
+    fn var_of(&self, node: BddPointer) -> BddVariable;
+    /// Low link of the given node.
+    fn low_link_of(&self, node: BddPointer) -> BddPointer;
+    /// High link of the given node.
+    fn high_link_of(&self, node: BddPointer) -> BddPointer;
+
+    /// Pointer to the root of the decision diagram.
+    fn root_pointer(&self) -> BddPointer {
+        BddPointer::from_index(self.size() - 1)
+    }
+}
+
+/// Receives notifications about the progress of the `apply` algorithms (see the
+/// `*_with_observer` methods of `Bdd`).
+///
+/// All methods do nothing by default. The algorithms are generic over the observer, so when
+/// the no-op observer `()` is used (i.e. in all standard operations), the notifications
+/// are completely optimized away by the compiler.
+pub trait ApplyObserver {
+    /// Called when a task is resolved. The task branches on the `variable` and there are
+    /// `stack_depth` tasks on the stack (including this one).
+    fn on_task(&mut self, variable: BddVariable, stack_depth: usize) {
+        let _ = (variable, stack_depth);
+    }
+
+    /// Called when a result of a sub-task is found in the task cache.
+    fn on_cache_hit(&mut self) {}
+
+    /// Called when a new decision node conditioning on the `variable` is created.
+    fn on_node_created(&mut self, variable: BddVariable) {
+        let _ = variable;
+    }
+}
+
 /// A trait which allows quick conversion of a type into a `Bdd`, assuming an appropriate
 /// `BddVariablesSet` is provided.
 pub trait IntoBdd {
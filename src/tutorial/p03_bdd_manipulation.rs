@@ -45,14 +45,40 @@
 //! ```
 //!
 //! In these expressions, you can use all common logical operators (in the order of precedence:
-//! `<=>`, `=>`, `|`, `&`, `^`, `!`), parentheses, and any variable name which is valid in your
-//! set.
+//! `<=>`, `=>`, `|`, `&`, `^`, `!`), parentheses, constants (`true`/`1` and `false`/`0`) and
+//! any variable name which is valid in your set. Binary operators are right-associative.
+//! The parser also accepts some common aliases (`~`, `&&`, `||`, `->` and `<->`), names
+//! in double quotes (e.g. `"my variable"`) and line comments starting with `#`. If your set
+//! contains a variable named `0` or `1`, it has to be quoted, since a bare `0`/`1` would be
+//! ambiguous (`eval_expression_string` rejects it).
+//!
+//! Finally, expressions can contain quantifiers (`exists x, y: body`, `forall x, y: body`),
+//! an if-then-else operator (`ite(c, t, e)`), n-ary conjunctions and disjunctions
+//! (`and(a, b, c)`, `or(a, b, c)`) and cardinality constraints (`atmost(k, a, b, c)`,
+//! `exactly(k, a, b, c)`):
+//!
+//! ```rust
+//! use biodivine_lib_bdd::BddVariableSet;
+//!
+//! let variables = BddVariableSet::new(&["a", "b", "c"]);
+//! let f1 = variables.eval_expression_string("exists a: ite(a, b, c)");
+//! let f2 = variables.eval_expression_string("atmost(1, !b, !c)");
+//! assert_eq!(f1, f2);
+//! ```
 //!
 //! Notice that if something goes wrong, `eval_expression_string` panics. If you want to use
 //! the same expression repeatedly or allow the user to enter their own expressions, you can parse
 //! the expression safely using `BooleanExpression::try_from` and then use `safe_eval_expression`.
-//! This will give you a human readable error when the parsing fails or there are invalid variables
-//! in the expression.
+//! When the parsing fails, you get a `ParseError` with a human readable message and the position
+//! of the problem in the input string:
+//!
+//! ```rust
+//! use std::convert::TryFrom;
+//! use biodivine_lib_bdd::boolean_expression::BooleanExpression;
+//!
+//! let error = BooleanExpression::try_from("a & (b | c").unwrap_err();
+//! assert_eq!(10, error.position);
+//! ```
 //!
 //! ## `bdd` macro
 //!
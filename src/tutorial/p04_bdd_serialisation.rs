@@ -46,6 +46,22 @@
 //! As in the case of `String` serialisation, each method can be also used with a
 //! custom `Read`/`Write` object.
 //!
+//! If the `Bdd` is very large, you may want to avoid reading it into memory altogether. For
+//! this purpose, a `BddRef` provides a read-only view directly over the bytes (e.g. of
+//! a memory-mapped file). It supports evaluation, cardinality, support set and valuation
+//! iterators, and can be also used as an operand of the basic logical operations:
+//!
+//! ```rust
+//! use biodivine_lib_bdd::{BddRef, BddVariableSet};
+//! let variables = BddVariableSet::new(&["a", "b"]);
+//! let bdd = variables.eval_expression_string("a & !b");
+//! let bdd_bytes: Vec<u8> = bdd.to_bytes();
+//!
+//! let bdd_ref = BddRef::from_bytes(&bdd_bytes).unwrap();
+//! assert_eq!(bdd.cardinality(), bdd_ref.cardinality());
+//! assert_eq!(bdd, bdd_ref.and(&variables.mk_true()));
+//! ```
+//!
 //! ## `.dot` visualisation
 //!
 //! In order to output `Bdd` as a `.dot` graph, we have to specify a `BddVariableSet` as it provides